@@ -1,10 +1,23 @@
-use crate::{enums::TextEncoding, errors::Result};
+use crate::{
+    enums::{
+        AesEncryptionPadding, ChaChaVariant, EciesEncryptionAlgorithm,
+        TextEncoding,
+    },
+    errors::{Error, Result},
+};
 
 pub mod aes;
+pub mod chacha;
 pub mod ecc;
+pub mod ecdh;
 pub mod edwards;
+pub mod hash;
+pub mod hpke;
 pub mod kdf;
+pub mod mac;
 pub mod rsa;
+pub mod sm2;
+pub mod sm4;
 
 pub trait EncryptionDto {
     fn get_input(&self) -> Result<Vec<u8>>;
@@ -12,6 +25,66 @@ pub trait EncryptionDto {
     fn get_output_encoding(&self) -> TextEncoding;
 }
 
+// decodes an optional dto field, whose encoding is required when it is set
+pub(crate) fn decode_optional(
+    value: &Option<String>,
+    encoding: Option<TextEncoding>,
+    name: &str,
+) -> Result<Option<Vec<u8>>> {
+    value
+        .as_ref()
+        .map(|value| {
+            encoding
+                .ok_or(Error::Unsupported(format!(
+                    "{} encoding is required",
+                    name
+                )))
+                .and_then(|encoding| encoding.decode(value))
+        })
+        .transpose()
+}
+
+// seals or opens the ecies payload with the derived key and nonce
+pub(crate) fn encrypt_or_decrypt_ecies(
+    encryption_alg: EciesEncryptionAlgorithm,
+    input: &[u8],
+    key: &[u8],
+    iv: &[u8],
+    for_encryption: bool,
+) -> Result<Vec<u8>> {
+    match (encryption_alg, encryption_alg.as_encryption_mode()) {
+        (EciesEncryptionAlgorithm::Sm4Gcm, Some(mode)) => {
+            sm4::encrypt_or_decrypt_sm4(
+                mode,
+                input,
+                key,
+                Some(iv.to_vec()),
+                None,
+                AesEncryptionPadding::NoPadding,
+                for_encryption,
+            )
+        }
+        (_, Some(mode)) => aes::encrypt_or_decrypt_aes(
+            mode,
+            input,
+            key,
+            Some(iv.to_vec()),
+            None,
+            AesEncryptionPadding::NoPadding,
+            None,
+            for_encryption,
+        ),
+        (_, None) => chacha::encrypt_or_decrypt_chacha(
+            ChaChaVariant::ChaCha20Poly1305,
+            input,
+            key,
+            iv,
+            &[],
+            for_encryption,
+        ),
+    }
+}
+
 #[macro_export]
 macro_rules! add_encryption_trait_impl {
   ($struct_name:ident { $($field_name:ident : $field_type:ty),* }) => {
@@ -40,3 +113,18 @@ macro_rules! add_encryption_trait_impl {
       }
   }
 }
+
+#[cfg(test)]
+pub(crate) mod fixture {
+    use crate::enums::TextEncoding;
+
+    pub(crate) const PLAINTEXT: &str =
+        "The quick brown fox jumps over the lazy dog";
+
+    // consecutive bytes as hex, for keys, nonces and aad in test vectors
+    pub(crate) fn hex(range: std::ops::Range<u8>) -> String {
+        TextEncoding::Hex
+            .encode(&range.collect::<Vec<u8>>())
+            .unwrap()
+    }
+}
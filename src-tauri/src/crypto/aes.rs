@@ -2,16 +2,23 @@ use std::fmt::Debug;
 
 use aes::{
     cipher::{
-        block_padding::Pkcs7, typenum, BlockCipher, BlockDecrypt,
-        BlockDecryptMut, BlockEncrypt, BlockEncryptMut, BlockSizeUser, KeyInit,
-        KeyIvInit,
+        block_padding::Pkcs7, typenum, ArrayLength, AsyncStreamCipher, Block,
+        BlockCipher, BlockDecrypt, BlockDecryptMut, BlockEncrypt,
+        BlockEncryptMut, BlockSizeUser, KeyInit, KeyIvInit, StreamCipher,
     },
-    Aes128, Aes256,
+    Aes128, Aes192, Aes256,
 };
-use aes_gcm::{aead::AeadMutInPlace, AesGcm, Nonce};
+use aes_gcm::{
+    aead::{self, AeadMutInPlace},
+    AesGcm,
+};
+use aes_gcm_siv::AesGcmSiv;
 use anyhow::Context;
 use block_padding::NoPadding;
+use ccm::Ccm;
+use cmac::{Cmac, Mac};
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 use tracing::{debug, info};
 
 use crate::{
@@ -30,6 +37,14 @@ add_encryption_trait_impl!(
         iv_encoding: Option<TextEncoding>,
         aad: Option<String>,
         aad_encoding: Option<TextEncoding>,
+        // xts data unit number, used as the little endian tweak
+        sector: Option<u64>,
+        // gcm and ccm tag length in bytes, defaults to 16
+        tag_length: Option<usize>,
+        // authentication tag kept apart from the ciphertext
+        tag: Option<String>,
+        tag_encoding: Option<TextEncoding>,
+        detached_tag: Option<bool>,
         for_encryption: bool
     }
 );
@@ -46,11 +61,24 @@ impl Debug for AesEncryptoinDto {
             .field("iv_encoding", &self.iv_encoding)
             .field("aad", &self.aad)
             .field("aad_encoding", &self.aad_encoding)
+            .field("sector", &self.sector)
+            .field("tag_length", &self.tag_length)
+            .field("tag", &self.tag)
+            .field("tag_encoding", &self.tag_encoding)
+            .field("detached_tag", &self.detached_tag)
             .field("for_encryption", &self.for_encryption)
             .finish()
     }
 }
 
+// a plain string unless the tag is detached
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AesOutput {
+    Joined(String),
+    Detached { output: String, tag: String },
+}
+
 #[tauri::command]
 pub async fn generate_iv(
     size: usize,
@@ -70,15 +98,25 @@ pub async fn generate_aes(
 }
 
 #[tauri::command]
-pub async fn crypto_aes(data: AesEncryptoinDto) -> Result<String> {
+pub async fn crypto_aes(data: AesEncryptoinDto) -> Result<AesOutput> {
     info!(
         "aes crypto-> for_encryption: {} mode: {:?} padding: {:?}",
         data.for_encryption, data.mode, data.padding
     );
-    let iv: Option<Vec<u8>> = data.iv.as_ref().and_then(|nonce| {
-        data.iv_encoding
-            .map(|enc| enc.decode(nonce).unwrap_or_default())
-    });
+    let iv: Option<Vec<u8>> = match (data.mode, data.sector) {
+        (EncryptionMode::Xts, Some(sector)) => {
+            Some((sector as u128).to_le_bytes().to_vec())
+        }
+        (EncryptionMode::Xts, None) => {
+            return Err(Error::Unsupported(
+                "xts sector is required".to_string(),
+            ))
+        }
+        _ => data.iv.as_ref().and_then(|nonce| {
+            data.iv_encoding
+                .map(|enc| enc.decode(nonce).unwrap_or_default())
+        }),
+    };
 
     let aad: Option<Vec<u8>> = data.aad.as_ref().and_then(|association| {
         data.aad_encoding
@@ -86,8 +124,28 @@ pub async fn crypto_aes(data: AesEncryptoinDto) -> Result<String> {
     });
     debug!("iv: {:?}, aad: {:?}", iv, aad);
     let key_bytes = data.get_key()?;
-    let plaintext = data.get_input()?;
+    let mut plaintext = data.get_input()?;
     let output_encoding = data.get_output_encoding();
+    let detached = data.detached_tag.unwrap_or_default();
+    if let Some(tag) = &data.tag {
+        let tag = data
+            .tag_encoding
+            .ok_or(Error::Unsupported("tag encoding is required".to_string()))?
+            .decode(tag)?;
+        if data.for_encryption {
+            return Err(Error::Unsupported(
+                "tag is only accepted for decryption".to_string(),
+            ));
+        }
+        let tag_size = detached_tag_size(data.mode, data.tag_length)?;
+        if tag.len() != tag_size {
+            return Err(Error::Unsupported(format!("tag size {}", tag.len())));
+        }
+        plaintext = match data.mode {
+            EncryptionMode::Siv => [tag, plaintext].concat(),
+            _ => [plaintext, tag].concat(),
+        };
+    }
     let output = encrypt_or_decrypt_aes(
         data.mode,
         &plaintext,
@@ -95,11 +153,43 @@ pub async fn crypto_aes(data: AesEncryptoinDto) -> Result<String> {
         iv,
         aad,
         data.padding,
+        data.tag_length,
         data.for_encryption,
     )?;
-    output_encoding.encode(&output)
+    if !(detached && data.for_encryption) {
+        return Ok(AesOutput::Joined(output_encoding.encode(&output)?));
+    }
+    let tag_size = detached_tag_size(data.mode, data.tag_length)?;
+    // the siv tag is the synthetic iv in front of the ciphertext
+    let (output, tag) = match data.mode {
+        EncryptionMode::Siv => {
+            let (tag, output) = output.split_at(tag_size);
+            (output, tag)
+        }
+        _ => output.split_at(output.len() - tag_size),
+    };
+    Ok(AesOutput::Detached {
+        output: output_encoding.encode(output)?,
+        tag: data.tag_encoding.unwrap_or(output_encoding).encode(tag)?,
+    })
 }
 
+fn detached_tag_size(
+    mode: EncryptionMode,
+    tag_length: Option<usize>,
+) -> Result<usize> {
+    match mode {
+        EncryptionMode::Gcm | EncryptionMode::Ccm => {
+            Ok(tag_length.unwrap_or(16))
+        }
+        EncryptionMode::GcmSiv | EncryptionMode::Siv | EncryptionMode::Ocb3 => {
+            Ok(16)
+        }
+        _ => Err(Error::Unsupported(format!("{:?} has no tag", mode))),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn encrypt_or_decrypt_aes(
     mode: EncryptionMode,
     plaintext: &[u8],
@@ -107,9 +197,23 @@ pub(crate) fn encrypt_or_decrypt_aes(
     iv: Option<Vec<u8>>,
     aad: Option<Vec<u8>>,
     padding: AesEncryptionPadding,
+    tag_length: Option<usize>,
     for_encryption: bool,
 ) -> Result<Vec<u8>> {
-    match key.len() {
+    check_mode(mode, iv.as_deref(), padding, tag_length)?;
+    // xts and siv take two keys of the same size
+    let key_size = match mode {
+        EncryptionMode::Xts | EncryptionMode::Siv => key.len() / 2,
+        _ => key.len(),
+    };
+    // rfc 8452 only defines aes-128 and aes-256
+    if mode == EncryptionMode::GcmSiv && key_size == 24 {
+        return Err(Error::Unsupported(format!(
+            "{:?} keysize {}",
+            mode, key_size
+        )));
+    }
+    match key_size {
         16 => encrypt_or_decrypt_aes_inner::<Aes128>(
             mode,
             plaintext,
@@ -117,6 +221,17 @@ pub(crate) fn encrypt_or_decrypt_aes(
             iv,
             aad,
             padding,
+            tag_length,
+            for_encryption,
+        ),
+        24 => encrypt_or_decrypt_aes_inner::<Aes192>(
+            mode,
+            plaintext,
+            key,
+            iv,
+            aad,
+            padding,
+            tag_length,
             for_encryption,
         ),
         32 => encrypt_or_decrypt_aes_inner::<Aes256>(
@@ -126,19 +241,79 @@ pub(crate) fn encrypt_or_decrypt_aes(
             iv,
             aad,
             padding,
+            tag_length,
             for_encryption,
         ),
         _ => Err(Error::Unsupported(format!("keysize {}", key.len()))),
     }
 }
 
-fn encrypt_or_decrypt_aes_inner<C>(
+pub(crate) fn check_mode(
+    mode: EncryptionMode,
+    iv: Option<&[u8]>,
+    padding: AesEncryptionPadding,
+    tag_length: Option<usize>,
+) -> Result<()> {
+    if !matches!(mode, EncryptionMode::Gcm | EncryptionMode::Ccm)
+        && !matches!(tag_length, None | Some(16))
+    {
+        return Err(Error::Unsupported(format!(
+            "{:?} tag length {:?}",
+            mode, tag_length
+        )));
+    }
+    let nonce_size = iv.map(<[u8]>::len);
+    match mode {
+        // aead modes have always ignored the padding, gcm checks its own
+        // nonce and the siv nonce is optional
+        EncryptionMode::Ecb | EncryptionMode::Gcm | EncryptionMode::Siv => {
+            Ok(())
+        }
+        EncryptionMode::GcmSiv if nonce_size == Some(12) => Ok(()),
+        EncryptionMode::Ccm if matches!(nonce_size, Some(7 ..= 13)) => Ok(()),
+        EncryptionMode::Ocb3 if matches!(nonce_size, Some(1 ..= 15)) => Ok(()),
+        EncryptionMode::GcmSiv | EncryptionMode::Ccm | EncryptionMode::Ocb3 => {
+            Err(Error::Unsupported(format!(
+                "{:?} nonce size {:?}",
+                mode, nonce_size
+            )))
+        }
+        EncryptionMode::Cbc => check_iv(mode, iv),
+        EncryptionMode::Ctr
+        | EncryptionMode::Cfb8
+        | EncryptionMode::Cfb128
+        | EncryptionMode::Ofb
+        | EncryptionMode::Xts => {
+            if padding != AesEncryptionPadding::NoPadding {
+                return Err(Error::Unsupported(format!(
+                    "{:?} with {:?}",
+                    mode, padding
+                )));
+            }
+            check_iv(mode, iv)
+        }
+    }
+}
+
+fn check_iv(mode: EncryptionMode, iv: Option<&[u8]>) -> Result<()> {
+    match iv.map(<[u8]>::len) {
+        Some(16) => Ok(()),
+        Some(size) => {
+            Err(Error::Unsupported(format!("{:?} iv size {}", mode, size)))
+        }
+        None => Err(Error::Unsupported(format!("{:?} iv is required", mode))),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn encrypt_or_decrypt_aes_inner<C>(
     mode: EncryptionMode,
     plaintext: &[u8],
     key: &[u8],
     iv: Option<Vec<u8>>,
     aad: Option<Vec<u8>>,
     padding: AesEncryptionPadding,
+    tag_length: Option<usize>,
     for_encryption: bool,
 ) -> Result<Vec<u8>>
 where
@@ -148,6 +323,7 @@ where
         + BlockDecrypt
         + BlockEncrypt
         + KeyInit
+        + Clone
         + BlockSizeUser<BlockSize = typenum::U16>,
 {
     match mode {
@@ -183,28 +359,550 @@ where
                 )
             }
         }
-        EncryptionMode::Gcm => {
-            let nonce = iv.unwrap();
-            let nonce = Nonce::from_slice(&nonce);
-            let mut payload = Vec::from(plaintext);
-            let association = &if let Some(association) = aad {
-                association.to_vec()
+        EncryptionMode::Ctr => {
+            let mut buf = plaintext.to_vec();
+            ctr::Ctr128BE::<C>::new_from_slices(key, &iv.unwrap_or_default())
+                .context("construct aes_ctr_cipher failed")?
+                .apply_keystream(&mut buf);
+            Ok(buf)
+        }
+        EncryptionMode::Cfb8 => {
+            let iv = iv.unwrap_or_default();
+            let mut buf = plaintext.to_vec();
+            if for_encryption {
+                cfb8::Encryptor::<C>::new_from_slices(key, &iv)
+                    .context("construct aes_cfb8_encryptor failed")?
+                    .encrypt(&mut buf);
             } else {
-                vec![]
-            };
+                cfb8::Decryptor::<C>::new_from_slices(key, &iv)
+                    .context("construct aes_cfb8_decryptor failed")?
+                    .decrypt(&mut buf);
+            }
+            Ok(buf)
+        }
+        EncryptionMode::Cfb128 => {
+            let iv = iv.unwrap_or_default();
+            let mut buf = plaintext.to_vec();
+            if for_encryption {
+                cfb_mode::Encryptor::<C>::new_from_slices(key, &iv)
+                    .context("construct aes_cfb_encryptor failed")?
+                    .encrypt(&mut buf);
+            } else {
+                cfb_mode::Decryptor::<C>::new_from_slices(key, &iv)
+                    .context("construct aes_cfb_decryptor failed")?
+                    .decrypt(&mut buf);
+            }
+            Ok(buf)
+        }
+        EncryptionMode::Ofb => {
+            let c = C::new_from_slice(key)
+                .context("construct aes_ofb_cipher failed")?;
+            let mut block =
+                Block::<C>::clone_from_slice(&iv.unwrap_or_default());
+            let mut buf = plaintext.to_vec();
+            for chunk in buf.chunks_mut(16) {
+                c.encrypt_block(&mut block);
+                chunk
+                    .iter_mut()
+                    .zip(block.iter())
+                    .for_each(|(b, k)| *b ^= k);
+            }
+            Ok(buf)
+        }
+        EncryptionMode::Xts => crypt_xts::<C>(
+            key,
+            &iv.unwrap_or_default(),
+            plaintext,
+            for_encryption,
+        ),
+        EncryptionMode::Gcm => {
+            let nonce = iv.ok_or(Error::Unsupported(
+                "gcm nonce is required".to_string(),
+            ))?;
+            crypt_gcm::<C>(
+                key,
+                &nonce,
+                &aad.unwrap_or_default(),
+                plaintext,
+                tag_length.unwrap_or(16),
+                for_encryption,
+            )
+        }
+        EncryptionMode::GcmSiv => crypt_aead::<AesGcmSiv<C>>(
+            key,
+            &iv.unwrap_or_default(),
+            &aad.unwrap_or_default(),
+            plaintext,
+            for_encryption,
+        ),
+        EncryptionMode::Ccm => crypt_ccm::<C>(
+            key,
+            &iv.unwrap_or_default(),
+            &aad.unwrap_or_default(),
+            plaintext,
+            tag_length.unwrap_or(16),
+            for_encryption,
+        ),
+        EncryptionMode::Siv => {
+            crypt_siv::<C>(key, iv, aad, plaintext, for_encryption)
+        }
+        EncryptionMode::Ocb3 => crypt_ocb3::<C>(
+            key,
+            &iv.unwrap_or_default(),
+            &aad.unwrap_or_default(),
+            plaintext,
+            for_encryption,
+        ),
+    }
+}
+
+pub(crate) fn crypt_aead<A>(
+    key: &[u8],
+    nonce: &[u8],
+    association: &[u8],
+    plaintext: &[u8],
+    for_encryption: bool,
+) -> Result<Vec<u8>>
+where
+    A: KeyInit + AeadMutInPlace,
+{
+    if nonce.len() != <A::NonceSize as typenum::Unsigned>::USIZE {
+        return Err(Error::Unsupported(format!("nonce size {}", nonce.len())));
+    }
+    let nonce = aead::Nonce::<A>::from_slice(nonce);
+    let mut payload = Vec::from(plaintext);
+    let mut c =
+        A::new_from_slice(key).context("construct aead_cipher failed")?;
+    if for_encryption {
+        c.encrypt_in_place(nonce, association, &mut payload)
+            .context("aead encrypt failed")?
+    } else {
+        c.decrypt_in_place(nonce, association, &mut payload)
+            .context("aead decrypt failed")?
+    };
+    Ok(payload)
+}
+
+fn crypt_gcm<C>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    input: &[u8],
+    tag_length: usize,
+    enc: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = typenum::U16>,
+{
+    use typenum::{U12, U13, U14, U15, U16};
+    match tag_length {
+        12 => crypt_gcm_nonce::<C, U12>(key, nonce, aad, input, enc),
+        13 => crypt_gcm_nonce::<C, U13>(key, nonce, aad, input, enc),
+        14 => crypt_gcm_nonce::<C, U14>(key, nonce, aad, input, enc),
+        15 => crypt_gcm_nonce::<C, U15>(key, nonce, aad, input, enc),
+        16 => crypt_gcm_nonce::<C, U16>(key, nonce, aad, input, enc),
+        size => Err(Error::Unsupported(format!("gcm tag length {}", size))),
+    }
+}
+
+// nonces other than 12 bytes go through ghash, java accepts any length
+fn crypt_gcm_nonce<C, T>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    input: &[u8],
+    enc: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = typenum::U16>,
+    T: aes_gcm::TagSize,
+{
+    use typenum::{U10, U11, U12, U13, U14, U15, U16, U8, U9};
+    match nonce.len() {
+        8 => crypt_aead::<AesGcm<C, U8, T>>(key, nonce, aad, input, enc),
+        9 => crypt_aead::<AesGcm<C, U9, T>>(key, nonce, aad, input, enc),
+        10 => crypt_aead::<AesGcm<C, U10, T>>(key, nonce, aad, input, enc),
+        11 => crypt_aead::<AesGcm<C, U11, T>>(key, nonce, aad, input, enc),
+        12 => crypt_aead::<AesGcm<C, U12, T>>(key, nonce, aad, input, enc),
+        13 => crypt_aead::<AesGcm<C, U13, T>>(key, nonce, aad, input, enc),
+        14 => crypt_aead::<AesGcm<C, U14, T>>(key, nonce, aad, input, enc),
+        15 => crypt_aead::<AesGcm<C, U15, T>>(key, nonce, aad, input, enc),
+        16 => crypt_aead::<AesGcm<C, U16, T>>(key, nonce, aad, input, enc),
+        size => Err(Error::Unsupported(format!("gcm nonce size {}", size))),
+    }
+}
+
+fn crypt_ccm<C>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    input: &[u8],
+    tag_length: usize,
+    enc: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = typenum::U16>,
+{
+    use typenum::{U10, U12, U14, U16, U4, U6, U8};
+    match tag_length {
+        4 => crypt_ccm_nonce::<C, U4>(key, nonce, aad, input, enc),
+        6 => crypt_ccm_nonce::<C, U6>(key, nonce, aad, input, enc),
+        8 => crypt_ccm_nonce::<C, U8>(key, nonce, aad, input, enc),
+        10 => crypt_ccm_nonce::<C, U10>(key, nonce, aad, input, enc),
+        12 => crypt_ccm_nonce::<C, U12>(key, nonce, aad, input, enc),
+        14 => crypt_ccm_nonce::<C, U14>(key, nonce, aad, input, enc),
+        16 => crypt_ccm_nonce::<C, U16>(key, nonce, aad, input, enc),
+        size => Err(Error::Unsupported(format!("ccm tag length {}", size))),
+    }
+}
+
+fn crypt_ccm_nonce<C, M>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    input: &[u8],
+    enc: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = typenum::U16>,
+    M: ArrayLength<u8> + ccm::TagSize,
+{
+    use typenum::{U10, U11, U12, U13, U7, U8, U9};
+    match nonce.len() {
+        7 => crypt_aead::<Ccm<C, M, U7>>(key, nonce, aad, input, enc),
+        8 => crypt_aead::<Ccm<C, M, U8>>(key, nonce, aad, input, enc),
+        9 => crypt_aead::<Ccm<C, M, U9>>(key, nonce, aad, input, enc),
+        10 => crypt_aead::<Ccm<C, M, U10>>(key, nonce, aad, input, enc),
+        11 => crypt_aead::<Ccm<C, M, U11>>(key, nonce, aad, input, enc),
+        12 => crypt_aead::<Ccm<C, M, U12>>(key, nonce, aad, input, enc),
+        13 => crypt_aead::<Ccm<C, M, U13>>(key, nonce, aad, input, enc),
+        size => Err(Error::Unsupported(format!("ccm nonce size {}", size))),
+    }
+}
 
-            let mut c = AesGcm::<C, typenum::U12>::new_from_slice(key)
-                .context("construct aes_gcm_cipher failed")?;
+// RFC 5297 deterministic aead, key is the cmac key || the ctr key and the
+// output is the synthetic iv || ciphertext. absent aad and nonce are skipped
+// while empty ones still count as s2v components
+fn crypt_siv<C>(
+    key: &[u8],
+    nonce: Option<Vec<u8>>,
+    aad: Option<Vec<u8>>,
+    input: &[u8],
+    for_encryption: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + BlockEncryptMut
+        + KeyInit
+        + Clone
+        + BlockSizeUser<BlockSize = typenum::U16>,
+{
+    let (mac_key, ctr_key) = key.split_at(key.len() / 2);
+    let components =
+        [aad, nonce].into_iter().flatten().collect::<Vec<Vec<u8>>>();
+    let ctr = |siv: &aes::Block, buf: &mut [u8]| -> Result<()> {
+        let mut counter = *siv;
+        counter[8] &= 0x7f;
+        counter[12] &= 0x7f;
+        ctr::Ctr128BE::<C>::new_from_slices(ctr_key, &counter)
+            .context("construct aes_siv_cipher failed")?
+            .apply_keystream(buf);
+        Ok(())
+    };
+    if for_encryption {
+        let siv = s2v::<C>(mac_key, &components, input)?;
+        let mut buf = input.to_vec();
+        ctr(&siv, &mut buf)?;
+        Ok([siv.as_slice(), &buf].concat())
+    } else {
+        if input.len() < 16 {
+            return Err(Error::Unsupported(
+                "siv input is shorter than the synthetic iv".to_string(),
+            ));
+        }
+        let (siv, ciphertext) = input.split_at(16);
+        let siv = aes::Block::clone_from_slice(siv);
+        let mut buf = ciphertext.to_vec();
+        ctr(&siv, &mut buf)?;
+        let expected = s2v::<C>(mac_key, &components, &buf)?;
+        if !bool::from(expected.ct_eq(&siv)) {
+            return Err(Error::Unsupported(
+                "aes siv decrypt failed".to_string(),
+            ));
+        }
+        Ok(buf)
+    }
+}
+
+fn s2v<C>(key: &[u8], components: &[Vec<u8>], last: &[u8]) -> Result<aes::Block>
+where
+    C: BlockCipher
+        + BlockEncryptMut
+        + KeyInit
+        + Clone
+        + BlockSizeUser<BlockSize = typenum::U16>,
+{
+    let mac = <Cmac<C> as Mac>::new_from_slice(key)
+        .context("construct aes_cmac failed")?;
+    let cmac = |data: &[u8]| -> aes::Block {
+        mac.clone().chain_update(data).finalize().into_bytes()
+    };
+    let mut d = cmac(&[0; 16]);
+    for component in components {
+        d = xor_block(&gf_double(&d), &cmac(component));
+    }
+    Ok(if last.len() >= 16 {
+        let mut t = last.to_vec();
+        let offset = last.len() - 16;
+        t[offset ..]
+            .iter_mut()
+            .zip(d.iter())
+            .for_each(|(b, k)| *b ^= k);
+        cmac(&t)
+    } else {
+        cmac(&xor_block(&gf_double(&d), &pad_block(last)))
+    })
+}
+
+// RFC 7253 with a 128 bits tag, output is ciphertext || tag
+fn crypt_ocb3<C>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    input: &[u8],
+    for_encryption: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + BlockDecrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = typenum::U16>,
+{
+    let c =
+        C::new_from_slice(key).context("construct aes_ocb_cipher failed")?;
+    let encrypt = |block: &aes::Block| {
+        let mut block = *block;
+        c.encrypt_block(&mut block);
+        block
+    };
+    let (body, tag) = if for_encryption {
+        (input, None)
+    } else if input.len() >= 16 {
+        let (body, tag) = input.split_at(input.len() - 16);
+        (body, Some(tag))
+    } else {
+        return Err(Error::Unsupported(
+            "ocb input is shorter than the tag".to_string(),
+        ));
+    };
+    let l_star = encrypt(&aes::Block::default());
+    let l_dollar = gf_double(&l_star);
+    let mut l = vec![gf_double(&l_dollar)];
+    let mut l_at = |i: usize| {
+        while l.len() <= i {
+            let next = gf_double(&l[l.len() - 1]);
+            l.push(next);
+        }
+        l[i]
+    };
+
+    // the tag length is 128 bits so its 7 leading bits stay zero
+    let mut nonce_block = aes::Block::default();
+    nonce_block[16 - nonce.len() ..].copy_from_slice(nonce);
+    nonce_block[15 - nonce.len()] |= 1;
+    let bottom = usize::from(nonce_block[15] & 0x3f);
+    nonce_block[15] &= 0xc0;
+    let ktop = encrypt(&nonce_block);
+    let mut stretch = [0; 24];
+    stretch[.. 16].copy_from_slice(&ktop);
+    for i in 0 .. 8 {
+        stretch[16 + i] = ktop[i] ^ ktop[i + 1];
+    }
+    let (shift, bits) = (bottom / 8, bottom % 8);
+    let mut offset = aes::Block::default();
+    for i in 0 .. 16 {
+        offset[i] = stretch[i + shift] << bits;
+        if bits > 0 {
+            offset[i] |= stretch[i + shift + 1] >> (8 - bits);
+        }
+    }
+
+    let mut output = Vec::with_capacity(body.len() + 16);
+    let mut checksum = aes::Block::default();
+    for (i, chunk) in body.chunks(16).enumerate() {
+        if chunk.len() == 16 {
+            offset =
+                xor_block(&offset, &l_at((i + 1).trailing_zeros() as usize));
+            let mut block = xor_block(&offset, chunk);
             if for_encryption {
-                c.encrypt_in_place(nonce, association, &mut payload)
-                    .context("aes gcm encrypt failed")?
+                checksum = xor_block(&checksum, chunk);
+                c.encrypt_block(&mut block);
             } else {
-                c.decrypt_in_place(nonce, association, &mut payload)
-                    .context("aes gcm decrypt failed")?
-            };
-            Ok(payload)
+                c.decrypt_block(&mut block);
+            }
+            block = xor_block(&offset, &block);
+            if !for_encryption {
+                checksum = xor_block(&checksum, &block);
+            }
+            output.extend_from_slice(&block);
+        } else {
+            offset = xor_block(&offset, &l_star);
+            let pad = encrypt(&offset);
+            let partial = chunk
+                .iter()
+                .zip(pad.iter())
+                .map(|(b, k)| b ^ k)
+                .collect::<Vec<u8>>();
+            let plaintext = if for_encryption { chunk } else { &partial };
+            checksum = xor_block(&checksum, &pad_block(plaintext));
+            output.extend_from_slice(&partial);
+        }
+    }
+
+    let mut sum = aes::Block::default();
+    let mut aad_offset = aes::Block::default();
+    for (i, chunk) in aad.chunks(16).enumerate() {
+        let block = if chunk.len() == 16 {
+            aad_offset = xor_block(
+                &aad_offset,
+                &l_at((i + 1).trailing_zeros() as usize),
+            );
+            xor_block(&aad_offset, chunk)
+        } else {
+            aad_offset = xor_block(&aad_offset, &l_star);
+            xor_block(&aad_offset, &pad_block(chunk))
+        };
+        sum = xor_block(&sum, &encrypt(&block));
+    }
+    let full_tag = xor_block(
+        &encrypt(&xor_block(&xor_block(&checksum, &offset), &l_dollar)),
+        &sum,
+    );
+    match tag {
+        None => {
+            output.extend_from_slice(&full_tag);
+            Ok(output)
+        }
+        Some(tag) if bool::from(full_tag.as_slice().ct_eq(tag)) => Ok(output),
+        Some(_) => {
+            Err(Error::Unsupported("aes ocb decrypt failed".to_string()))
+        }
+    }
+}
+
+// multiplies the block by x in GF(2^128), big endian
+fn gf_double(block: &aes::Block) -> aes::Block {
+    let mut next = *block;
+    let carry = block[0] >> 7;
+    for i in 0 .. 15 {
+        next[i] = (block[i] << 1) | (block[i + 1] >> 7);
+    }
+    next[15] = (block[15] << 1) ^ (0x87 * carry);
+    next
+}
+
+fn xor_block(left: &[u8], right: &[u8]) -> aes::Block {
+    let mut block = aes::Block::clone_from_slice(left);
+    block
+        .iter_mut()
+        .zip(right.iter())
+        .for_each(|(b, r)| *b ^= r);
+    block
+}
+
+// appends the 10* padding to a partial block
+fn pad_block(partial: &[u8]) -> aes::Block {
+    let mut block = aes::Block::default();
+    block[.. partial.len()].copy_from_slice(partial);
+    block[partial.len()] = 0x80;
+    block
+}
+
+// IEEE 1619 XTS with ciphertext stealing, key is key1 || key2
+fn crypt_xts<C>(
+    key: &[u8],
+    tweak: &[u8],
+    input: &[u8],
+    for_encryption: bool,
+) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncrypt
+        + BlockDecrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = typenum::U16>,
+{
+    if input.len() < 16 {
+        return Err(Error::Unsupported(
+            "xts input is shorter than a block".to_string(),
+        ));
+    }
+    let (key1, key2) = key.split_at(key.len() / 2);
+    let c =
+        C::new_from_slice(key1).context("construct aes_xts_cipher failed")?;
+    let mut tweak = Block::<C>::clone_from_slice(tweak);
+    C::new_from_slice(key2)
+        .context("construct aes_xts_tweak_cipher failed")?
+        .encrypt_block(&mut tweak);
+    let xex = |block: &mut [u8], tweak: &Block<C>| {
+        let mut buf = Block::<C>::clone_from_slice(block);
+        buf.iter_mut().zip(tweak.iter()).for_each(|(b, t)| *b ^= t);
+        if for_encryption {
+            c.encrypt_block(&mut buf);
+        } else {
+            c.decrypt_block(&mut buf);
         }
+        buf.iter_mut().zip(tweak.iter()).for_each(|(b, t)| *b ^= t);
+        block.copy_from_slice(&buf);
+    };
+    let mut output = input.to_vec();
+    let tail = input.len() % 16;
+    let blocks = input.len() / 16 - usize::from(tail > 0);
+    for block in output[.. blocks * 16].chunks_mut(16) {
+        xex(block, &tweak);
+        tweak = xts_next_tweak(&tweak);
     }
+    if tail > 0 {
+        let (last, partial) = output[blocks * 16 ..].split_at_mut(16);
+        // decryption swaps the tweaks of the last two blocks
+        let (first_tweak, second_tweak) = if for_encryption {
+            (tweak, xts_next_tweak(&tweak))
+        } else {
+            (xts_next_tweak(&tweak), tweak)
+        };
+        xex(last, &first_tweak);
+        let mut stolen = last.to_vec();
+        stolen[.. tail].copy_from_slice(partial);
+        partial.copy_from_slice(&last[.. tail]);
+        xex(&mut stolen, &second_tweak);
+        last.copy_from_slice(&stolen);
+    }
+    Ok(output)
+}
+
+// multiplies the tweak by x in GF(2^128), little endian
+fn xts_next_tweak(tweak: &aes::Block) -> aes::Block {
+    let mut next = *tweak;
+    let carry = tweak[15] >> 7;
+    for i in (1 .. 16).rev() {
+        next[i] = (tweak[i] << 1) | (tweak[i - 1] >> 7);
+    }
+    next[0] = (tweak[0] << 1) ^ (0x87 * carry);
+    next
 }
 
 fn encrypt_aes_inner_in<C>(
@@ -257,7 +955,10 @@ where
 mod test {
     use super::generate_aes;
     use crate::{
-        crypto::aes::{crypto_aes, generate_iv, AesEncryptoinDto},
+        crypto::{
+            aes::{crypto_aes, generate_iv, AesEncryptoinDto, AesOutput},
+            fixture::{hex, PLAINTEXT},
+        },
         enums::{AesEncryptionPadding, EncryptionMode, TextEncoding},
         utils::random_bytes,
     };
@@ -271,7 +972,7 @@ mod test {
             let iv = generate_iv(12, encoding).await.unwrap();
             let aad_bytes = random_bytes(128).unwrap();
             let aad = encoding.encode(&aad_bytes).unwrap();
-            let ciphertext = crypto_aes(AesEncryptoinDto {
+            let AesOutput::Joined(ciphertext) = crypto_aes(AesEncryptoinDto {
                 input: plaintext.to_string(),
                 input_encoding: TextEncoding::Utf8,
                 key: key.to_string(),
@@ -283,12 +984,19 @@ mod test {
                 iv_encoding: Some(encoding),
                 aad: Some(aad.to_string()),
                 aad_encoding: Some(encoding),
+                sector: None,
+                tag_length: None,
+                tag: None,
+                tag_encoding: None,
+                detached_tag: None,
                 for_encryption: true,
             })
             .await
-            .unwrap();
+            .unwrap() else {
+                panic!("tag is not detached")
+            };
             assert_eq!(
-                plaintext,
+                AesOutput::Joined(plaintext.to_string()),
                 crypto_aes(AesEncryptoinDto {
                     input: ciphertext,
                     input_encoding: encoding,
@@ -301,6 +1009,11 @@ mod test {
                     iv_encoding: Some(encoding),
                     aad: Some(aad),
                     aad_encoding: Some(encoding),
+                    sector: None,
+                    tag_length: None,
+                    tag: None,
+                    tag_encoding: None,
+                    detached_tag: None,
                     for_encryption: false
                 })
                 .await
@@ -308,4 +1021,348 @@ mod test {
             )
         }
     }
+
+    #[tokio::test]
+    async fn test_aes_stream_and_xts_modes() {
+        let (key_192, xts_128, xts_256) =
+            (hex(0 .. 24), hex(32 .. 64), hex(32 .. 96));
+        for (mode, key, ciphertext) in [
+            (
+                EncryptionMode::Ctr,
+                &key_192,
+                "c7c65e5feeb78176f625c4db9a95210d9dd23cadd770c52b170784ae712d\
+                 daa2c2d1dfb337428befd67b4e",
+            ),
+            (
+                EncryptionMode::Cfb8,
+                &key_192,
+                "c75539ec71dd431bac24b45d2dea71996e39319f554fcfcacfe50a16716f\
+                 312657f811866b6d13302eb0d6",
+            ),
+            (
+                EncryptionMode::Cfb128,
+                &key_192,
+                "c7c65e5feeb78176f625c4db9a95210d3beff6d14fa6fb10183a31e79e3e\
+                 4ca665325d6263541d20be0490",
+            ),
+            (
+                EncryptionMode::Ofb,
+                &key_192,
+                "c7c65e5feeb78176f625c4db9a95210d874014f83118f9db74454aa12c6f\
+                 d2f90ffe0329e8f319e75e8ba9",
+            ),
+            (
+                EncryptionMode::Xts,
+                &xts_128,
+                "ca5e91dfeb8ecbcf5e1b86189140677864dd582341d3c19a2fa52e584122\
+                 87fe03c9fcf738aa0fff3de76e",
+            ),
+            (
+                EncryptionMode::Xts,
+                &xts_256,
+                "c89b5e8eee9b449bf0f9fc4b069b493b3e47578506bbefda604486be1d11\
+                 d45d30d0dd0383b83468e9b5ab",
+            ),
+        ] {
+            let (iv, sector) = match mode {
+                EncryptionMode::Xts => (None, Some(42)),
+                _ => (Some(hex(16 .. 32)), None),
+            };
+            let dto = AesEncryptoinDto {
+                input: PLAINTEXT.to_string(),
+                input_encoding: TextEncoding::Utf8,
+                key: key.to_string(),
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                mode,
+                padding: AesEncryptionPadding::NoPadding,
+                iv,
+                iv_encoding: Some(TextEncoding::Hex),
+                aad: None,
+                aad_encoding: None,
+                sector,
+                tag_length: None,
+                tag: None,
+                tag_encoding: None,
+                detached_tag: None,
+                for_encryption: true,
+            };
+            assert_eq!(
+                crypto_aes(dto.clone()).await.unwrap(),
+                AesOutput::Joined(ciphertext.to_string())
+            );
+            assert_eq!(
+                crypto_aes(AesEncryptoinDto {
+                    input: ciphertext.to_string(),
+                    input_encoding: TextEncoding::Hex,
+                    output_encoding: TextEncoding::Utf8,
+                    for_encryption: false,
+                    ..dto.clone()
+                })
+                .await
+                .unwrap(),
+                AesOutput::Joined(PLAINTEXT.to_string())
+            );
+            // stream modes and xts never pad
+            assert!(crypto_aes(AesEncryptoinDto {
+                padding: AesEncryptionPadding::Pkcs7Padding,
+                ..dto
+            })
+            .await
+            .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aes_aead_modes() {
+        let (nonce, aad) = (hex(16 .. 28), hex(100 .. 140));
+        for (mode, key, iv, aad, tag_length, plaintext, ciphertext) in [
+            (
+                EncryptionMode::GcmSiv,
+                hex(0 .. 16),
+                Some(nonce.clone()),
+                Some(aad.clone()),
+                None,
+                PLAINTEXT,
+                "c4a41db2c645d1c277f7682228b902187dcc6ffb0c711b343e11234f80\
+                 59cebebf2d91100f9ec9a8c8a577270311d490216ecec79d382748a33efe",
+            ),
+            (
+                EncryptionMode::GcmSiv,
+                hex(0 .. 32),
+                Some(nonce.clone()),
+                Some(aad.clone()),
+                None,
+                PLAINTEXT,
+                "98471b9ba654eae2c7a307a5bd1bf6387118c1ac42d88ec3d885fc404e\
+                 04362f1311435324c0e72d8a1952544fd61b7cde291623212d86baa68b41",
+            ),
+            (
+                EncryptionMode::Ccm,
+                hex(0 .. 24),
+                Some(hex(16 .. 23)),
+                Some(aad.clone()),
+                Some(8),
+                PLAINTEXT,
+                "90870bdd18fca9192c28f3268758304a5e80c596f9f5bbf0ba1f79f270\
+                 6b2031c7c9f50bb08c703357f2e92207e06ddf7c146f",
+            ),
+            (
+                EncryptionMode::Ccm,
+                hex(0 .. 16),
+                Some(hex(16 .. 29)),
+                Some(aad.clone()),
+                Some(16),
+                PLAINTEXT,
+                "28891561c92987b7d0792ae3407002a14cbc2d5915e2f35a4e6d75a5eb\
+                 caa5376d1fbfb240eaddba5b03c4c949022e66bc94fce14a29b688658435",
+            ),
+            (
+                EncryptionMode::Siv,
+                hex(0 .. 32),
+                Some(nonce.clone()),
+                Some(aad.clone()),
+                None,
+                PLAINTEXT,
+                "5bd02e170f3ff367bbd6f84ae1a8999079207e01fcd9feb0ca4eceed9a\
+                 2a66f55686f3b07a9e95d8f7a5c900a029e8064ff1ca0921b5aea3219163",
+            ),
+            (
+                EncryptionMode::Siv,
+                hex(0 .. 64),
+                Some(nonce.clone()),
+                Some(aad.clone()),
+                None,
+                PLAINTEXT,
+                "3daebc31ec10de450b00b7caca6a8bdefcab04e036687665f83ecf08a1\
+                 78a05786bac7c5e4862888a39c755577ed54a569f058f3730b9764e85333",
+            ),
+            (
+                EncryptionMode::Siv,
+                hex(0 .. 32),
+                None,
+                None,
+                None,
+                PLAINTEXT,
+                "cc229e7660083812ecd274b1211671b137aedddd877cea802e9f7a91e9\
+                 ce2cc2d379e0502c9caad0f781e518efbfa65ef61d0cb24c19254e0413e8",
+            ),
+            (
+                EncryptionMode::Siv,
+                hex(0 .. 32),
+                None,
+                Some(String::new()),
+                None,
+                PLAINTEXT,
+                "c5a8a3bded7b7deecf7c5bd7001c9ebeaf9eccf2f91b94c0aa72a98bbb\
+                 00fefbd3f71a888a3b3b0453a0107ebf18753d71d67fb4625cf1bea3495d",
+            ),
+            (
+                EncryptionMode::Ocb3,
+                hex(0 .. 24),
+                Some(nonce.clone()),
+                Some(aad.clone()),
+                None,
+                PLAINTEXT,
+                "c06c59f247c1588940a3bee0fc656d7319d9007fd7f76e6b5ca0efeb8b\
+                 c74bd08ecebc7ea37402b89b470855eebfe5de575b3d8b209bc352c8835a",
+            ),
+            (
+                EncryptionMode::Ocb3,
+                hex(0 .. 16),
+                Some(hex(16 .. 31)),
+                Some(hex(100 .. 116)),
+                None,
+                &PLAINTEXT[.. 32],
+                "31653b0d44cf491912c1ab68006c63b2cb1e6b5e757cd2f9a20def1a9f\
+                 086519421cab1342c70736866be1fbd6c75761",
+            ),
+        ] {
+            let dto = AesEncryptoinDto {
+                input: plaintext.to_string(),
+                input_encoding: TextEncoding::Utf8,
+                key,
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                mode,
+                padding: AesEncryptionPadding::NoPadding,
+                iv,
+                iv_encoding: Some(TextEncoding::Hex),
+                aad,
+                aad_encoding: Some(TextEncoding::Hex),
+                sector: None,
+                tag_length,
+                tag: None,
+                tag_encoding: None,
+                detached_tag: None,
+                for_encryption: true,
+            };
+            assert_eq!(
+                crypto_aes(dto.clone()).await.unwrap(),
+                AesOutput::Joined(ciphertext.to_string())
+            );
+            let dto = AesEncryptoinDto {
+                input: ciphertext.to_string(),
+                input_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Utf8,
+                for_encryption: false,
+                ..dto
+            };
+            assert_eq!(
+                crypto_aes(dto.clone()).await.unwrap(),
+                AesOutput::Joined(plaintext.to_string())
+            );
+            let mut tampered = ciphertext.to_string();
+            tampered.replace_range(.. 2, "00");
+            assert!(crypto_aes(AesEncryptoinDto {
+                input: tampered,
+                ..dto
+            })
+            .await
+            .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aes_detached_tag() {
+        for (mode, key, iv, tag_length, ciphertext, tag) in [
+            // 16 bytes nonce and a truncated tag as written by java
+            (
+                EncryptionMode::Gcm,
+                hex(0 .. 16),
+                Some(hex(16 .. 32)),
+                Some(12),
+                "91f692960cc74debbd38a1f0ce5841f7939258bf0773ca88daa6e8e0263\
+                 153944b414b6b80630d6e88ea8b",
+                "476dc1ff366aa8aa300ee1d2",
+            ),
+            (
+                EncryptionMode::Gcm,
+                hex(0 .. 32),
+                Some(hex(16 .. 24)),
+                None,
+                "8a6413db5c4bd3b8af1fae0de10efed3d0db0efdd878d76fe8d6f3ea188\
+                 2c5207f2ddbb29d6d825a1644c9",
+                "e74a729684793462bea34afb0cb815b4",
+            ),
+            (
+                EncryptionMode::Gcm,
+                hex(0 .. 24),
+                Some(hex(16 .. 29)),
+                Some(14),
+                "b90b1f9c578f329939a9ecedda19da6ddac1a4e9fdac0fc503bd9f9cf73\
+                 8b9bdbdfa57f80fe4f4d7ba89ce",
+                "84bb6daabef79aad6c31588b15ce",
+            ),
+            // the synthetic iv leads the ciphertext
+            (
+                EncryptionMode::Siv,
+                hex(0 .. 32),
+                Some(hex(16 .. 28)),
+                None,
+                "79207e01fcd9feb0ca4eceed9a2a66f55686f3b07a9e95d8f7a5c900a02\
+                 9e8064ff1ca0921b5aea3219163",
+                "5bd02e170f3ff367bbd6f84ae1a89990",
+            ),
+        ] {
+            let dto = AesEncryptoinDto {
+                input: PLAINTEXT.to_string(),
+                input_encoding: TextEncoding::Utf8,
+                key,
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                mode,
+                padding: AesEncryptionPadding::NoPadding,
+                iv,
+                iv_encoding: Some(TextEncoding::Hex),
+                aad: Some(hex(100 .. 140)),
+                aad_encoding: Some(TextEncoding::Hex),
+                sector: None,
+                tag_length,
+                tag: None,
+                tag_encoding: Some(TextEncoding::Hex),
+                detached_tag: Some(true),
+                for_encryption: true,
+            };
+            assert_eq!(
+                crypto_aes(dto.clone()).await.unwrap(),
+                AesOutput::Detached {
+                    output: ciphertext.to_string(),
+                    tag: tag.to_string(),
+                }
+            );
+            let dto = AesEncryptoinDto {
+                input: ciphertext.to_string(),
+                input_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Utf8,
+                tag: Some(tag.to_string()),
+                for_encryption: false,
+                ..dto
+            };
+            assert_eq!(
+                crypto_aes(dto.clone()).await.unwrap(),
+                AesOutput::Joined(PLAINTEXT.to_string())
+            );
+            let joined = match mode {
+                EncryptionMode::Siv => format!("{}{}", tag, ciphertext),
+                _ => format!("{}{}", ciphertext, tag),
+            };
+            assert_eq!(
+                crypto_aes(AesEncryptoinDto {
+                    input: joined,
+                    tag: None,
+                    ..dto.clone()
+                })
+                .await
+                .unwrap(),
+                AesOutput::Joined(PLAINTEXT.to_string())
+            );
+            assert!(crypto_aes(AesEncryptoinDto {
+                tag: Some(tag[2 ..].to_string()),
+                ..dto
+            })
+            .await
+            .is_err());
+        }
+    }
 }
@@ -0,0 +1,152 @@
+use std::fmt::Debug;
+
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::aes::crypt_aead;
+use crate::{
+    add_encryption_trait_impl,
+    crypto::EncryptionDto,
+    enums::{ChaChaVariant, TextEncoding},
+    errors::{Error, Result},
+};
+
+add_encryption_trait_impl!(ChaChaEncryptionDto {
+    variant: ChaChaVariant,
+    iv: Option<String>,
+    iv_encoding: Option<TextEncoding>,
+    aad: Option<String>,
+    aad_encoding: Option<TextEncoding>,
+    for_encryption: bool
+});
+
+impl Debug for ChaChaEncryptionDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaChaEncryptionDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("variant", &self.variant)
+            .field("iv", &self.iv)
+            .field("iv_encoding", &self.iv_encoding)
+            .field("aad", &self.aad)
+            .field("aad_encoding", &self.aad_encoding)
+            .field("for_encryption", &self.for_encryption)
+            .finish()
+    }
+}
+
+#[tauri::command]
+pub async fn crypto_chacha(data: ChaChaEncryptionDto) -> Result<String> {
+    info!("chacha crypto-> {:?}", data);
+    let nonce = match (&data.iv, data.iv_encoding) {
+        (Some(iv), Some(encoding)) => encoding.decode(iv)?,
+        _ => {
+            return Err(Error::Unsupported(
+                "chacha nonce is required".to_string(),
+            ))
+        }
+    };
+    let aad = match (&data.aad, data.aad_encoding) {
+        (Some(aad), Some(encoding)) => encoding.decode(aad)?,
+        _ => Vec::new(),
+    };
+    let output = encrypt_or_decrypt_chacha(
+        data.variant,
+        &data.get_input()?,
+        &data.get_key()?,
+        &nonce,
+        &aad,
+        data.for_encryption,
+    )?;
+    data.get_output_encoding().encode(&output)
+}
+
+pub(crate) fn encrypt_or_decrypt_chacha(
+    variant: ChaChaVariant,
+    input: &[u8],
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    for_encryption: bool,
+) -> Result<Vec<u8>> {
+    match variant {
+        ChaChaVariant::ChaCha20Poly1305 => crypt_aead::<ChaCha20Poly1305>(
+            key,
+            nonce,
+            aad,
+            input,
+            for_encryption,
+        ),
+        ChaChaVariant::XChaCha20Poly1305 => crypt_aead::<XChaCha20Poly1305>(
+            key,
+            nonce,
+            aad,
+            input,
+            for_encryption,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crypto_chacha, ChaChaEncryptionDto};
+    use crate::{
+        crypto::fixture::{hex, PLAINTEXT},
+        enums::{ChaChaVariant, TextEncoding},
+    };
+
+    #[tokio::test]
+    async fn test_chacha_encryption() {
+        for (variant, nonce, ciphertext) in [
+            (
+                ChaChaVariant::ChaCha20Poly1305,
+                hex(16 .. 28),
+                "0aa22027776caf0a70e65111b6dcdb551c098d09eeb7bb04f1b3d87b89\
+                 ce839294efa015b8fec2fc11fa9ceae6e27830272c873483e47e1e41a342",
+            ),
+            (
+                ChaChaVariant::XChaCha20Poly1305,
+                hex(16 .. 40),
+                "709466d625fd48b0fda240bf92e74d2c69e48fcf93184da3956496007f\
+                 5780e1c2b2dc5b69985ef66e24cc6032060e878b1bc4581a46e285e97ae5",
+            ),
+        ] {
+            let dto = ChaChaEncryptionDto {
+                input: PLAINTEXT.to_string(),
+                input_encoding: TextEncoding::Utf8,
+                key: hex(0 .. 32),
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                variant,
+                iv: Some(nonce),
+                iv_encoding: Some(TextEncoding::Hex),
+                aad: Some(hex(100 .. 140)),
+                aad_encoding: Some(TextEncoding::Hex),
+                for_encryption: true,
+            };
+            assert_eq!(crypto_chacha(dto.clone()).await.unwrap(), ciphertext);
+            let dto = ChaChaEncryptionDto {
+                input: ciphertext.to_string(),
+                input_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Utf8,
+                for_encryption: false,
+                ..dto
+            };
+            assert_eq!(crypto_chacha(dto.clone()).await.unwrap(), PLAINTEXT);
+            assert!(crypto_chacha(ChaChaEncryptionDto {
+                aad: None,
+                ..dto.clone()
+            })
+            .await
+            .is_err());
+            assert!(crypto_chacha(ChaChaEncryptionDto {
+                iv: Some(hex(0 .. 16)),
+                ..dto
+            })
+            .await
+            .is_err());
+        }
+    }
+}
@@ -1,12 +1,29 @@
 use std::fmt::Debug;
 
+use aes_gcm::{
+    aead::{consts::U16, AeadInPlace, KeyInit},
+    AesGcm, Nonce, Tag,
+};
 use anyhow::Context;
+use digest::Digest as _;
+use ecdsa::{
+    der::{MaxOverhead, MaxSize},
+    RecoveryId, SignatureSize,
+};
 use elliptic_curve::{
+    generic_array::{typenum::Unsigned, ArrayLength},
+    group::Curve as _,
     sec1::{EncodedPoint, ToEncodedPoint},
-    AffinePoint,
+    AffinePoint, FieldBytesSize, PrimeCurve, PublicKey, SecretKey,
 };
+use k256::Secp256k1;
 use p256::NistP256;
 use serde::{Deserialize, Serialize};
+use signature::{
+    hazmat::{PrehashSigner, PrehashVerifier},
+    Signer, Verifier,
+};
+use subtle::ConstantTimeEq;
 use tracing::{debug, info};
 
 use self::key::{import_ecc_private_key, import_ecc_public_key};
@@ -15,10 +32,11 @@ use crate::{
     add_encryption_trait_impl,
     crypto::{self, EncryptionDto},
     enums::{
-        AesEncryptionPadding, Digest, EccCurveName, EciesEncryptionAlgorithm,
-        Kdf, KeyFormat, Pkcs, TextEncoding,
+        Digest, EccCurveName, EcdsaSignatureFormat, EciesEncryptionAlgorithm,
+        EciesProfile, Kdf, KeyFormat, Pkcs, TextEncoding,
     },
     errors::{Error, Result},
+    utils,
 };
 
 pub mod key;
@@ -28,16 +46,29 @@ add_encryption_trait_impl!(EciesDto {
     pkcs: Pkcs,
     format: KeyFormat,
     kdf: Kdf,
-    kdf_digest: Digest,
+    // defaults to sm3 on sm2 and sha256 elsewhere
+    kdf_digest: Option<Digest>,
     salt: Option<String>,
     salt_encoding: Option<TextEncoding>,
     info: Option<String>,
     info_encoding: Option<TextEncoding>,
     encryption_alg: EciesEncryptionAlgorithm,
+    // kits legacy only, aes key bits (128 or 256) and gcm nonce bytes
+    key_size: Option<usize>,
+    nonce_length: Option<usize>,
+    // wire format, defaults to kits legacy
+    profile: Option<EciesProfile>,
     for_encryption: bool
 });
 
 impl EciesDto {
+    pub fn get_kdf_digest(&self) -> Digest {
+        self.kdf_digest.unwrap_or(match self.curve_name {
+            EccCurveName::SM2 => Digest::Sm3,
+            _ => Digest::Sha256,
+        })
+    }
+
     pub fn get_salt(&self) -> Result<Option<Vec<u8>>> {
         if let Some(s) = self.salt.as_ref() {
             self.salt_encoding
@@ -77,11 +108,46 @@ impl Debug for EciesDto {
             .field("kdf", &self.kdf)
             .field("kdf_digest", &self.kdf_digest)
             .field("encryption_alg", &self.encryption_alg)
+            .field("key_size", &self.key_size)
+            .field("nonce_length", &self.nonce_length)
+            .field("profile", &self.profile)
             .field("for_encryption", &self.for_encryption)
             .finish()
     }
 }
 
+add_encryption_trait_impl!(EcdsaDto {
+    curve_name: EccCurveName,
+    pkcs: Pkcs,
+    format: KeyFormat,
+    // ignored by sm2, which always hashes ZA || M with sm3
+    digest: Digest,
+    // sm2 distinguishing identifier
+    distid: Option<String>,
+    signature_format: EcdsaSignatureFormat,
+    // verification only, encoded with output_encoding
+    signature: Option<String>
+});
+
+impl Debug for EcdsaDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EcdsaDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("curve_name", &self.curve_name)
+            .field("pkcs", &self.pkcs)
+            .field("key_format", &self.format)
+            .field("digest", &self.digest)
+            .field("distid", &self.distid)
+            .field("signature_format", &self.signature_format)
+            .finish()
+    }
+}
+
+// GM/T 0009 default user identifier
+pub(crate) const SM2_DEFAULT_DISTID: &str = "1234567812345678";
+
 #[tauri::command]
 pub async fn ecies(data: EciesDto) -> Result<String> {
     info!("ecies :{:?} ", data);
@@ -90,13 +156,40 @@ pub async fn ecies(data: EciesDto) -> Result<String> {
         EccCurveName::NistP256 => ecies_inner::<NistP256>(data),
         EccCurveName::NistP384 => ecies_inner::<p384::NistP384>(data),
         EccCurveName::NistP521 => ecies_inner::<p521::NistP521>(data),
-        EccCurveName::Secp256k1 => ecies_inner::<k256::Secp256k1>(data),
+        EccCurveName::Secp256k1 => ecies_inner::<Secp256k1>(data),
         EccCurveName::SM2 => ecies_inner::<sm2::Sm2>(data),
     })?;
     output_encoding.encode(&cipher_bytes)
 }
 
 pub fn ecies_inner<C>(data: EciesDto) -> Result<Vec<u8>>
+where
+    C: elliptic_curve::Curve
+        + elliptic_curve::CurveArithmetic
+        + pkcs8::AssociatedOid
+        + elliptic_curve::point::PointCompression,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    match (data.profile.unwrap_or_default(), data.curve_name) {
+        (EciesProfile::KitsLegacy, _) => kits_legacy_ecies::<C>(data),
+        (EciesProfile::Sec1, _) => sec1_ecies::<C>(data),
+        (EciesProfile::Eciesjs, EccCurveName::Secp256k1) => eciesjs(data),
+        (EciesProfile::Sm2C1C3C2, EccCurveName::SM2) => {
+            sm2_encryption(data, true)
+        }
+        (EciesProfile::Sm2C1C2C3, EccCurveName::SM2) => {
+            sm2_encryption(data, false)
+        }
+        (profile, curve_name) => Err(Error::Unsupported(format!(
+            "ecies profile {:?} on {:?}",
+            profile, curve_name
+        ))),
+    }
+}
+
+fn kits_legacy_ecies<C>(data: EciesDto) -> Result<Vec<u8>>
 where
     C: elliptic_curve::Curve
         + elliptic_curve::CurveArithmetic
@@ -111,7 +204,6 @@ where
     let EciesDto {
         pkcs,
         format,
-        kdf,
         encryption_alg,
         for_encryption,
         ..
@@ -119,7 +211,8 @@ where
     let salt = data.get_salt()?;
     let info = data.get_info()?;
     Ok(if for_encryption {
-        let mut result = Vec::new();
+        let envelope = EciesEnvelope::new(&data)?;
+        let mut result = envelope.to_bytes().to_vec();
         let (receiver_public_key_bytes, shared_secret) =
             generate_secret::<C>(&key, format)?;
         result.extend_from_slice(&receiver_public_key_bytes);
@@ -129,68 +222,158 @@ where
             TextEncoding::Base64.encode(&shared_secret)?
         );
 
-        let pkf_key = kdf::kdf_inner_digest(
-            kdf,
-            Digest::Sha256,
-            &shared_secret,
-            salt,
-            info,
-            44,
-        )?;
+        let pkf_key = envelope.derive(&shared_secret, salt, info)?;
         debug!(
             "encryption pkf_key: {}",
             TextEncoding::Base64.encode(&pkf_key)?
         );
 
-        let (secret, iv) = pkf_key.split_at(32);
-        let encrypted = crypto::aes::encrypt_or_decrypt_aes(
-            encryption_alg.as_encryption_mode(),
+        let (secret, iv) = pkf_key.split_at(envelope.key_size);
+        let encrypted = crypto::encrypt_or_decrypt_ecies(
+            encryption_alg,
             &input,
             secret,
-            Some(iv.to_vec()),
-            None,
-            AesEncryptionPadding::NoPadding,
+            iv,
             for_encryption,
         )?;
 
         result.extend_from_slice(&encrypted);
         result
     } else {
+        let (envelope, input) = EciesEnvelope::parse(&data, &input)?;
         let (input, shared_secret) =
-            parse_secret::<C>(&input, &key, pkcs, format)?;
+            parse_secret::<C>(input, &key, pkcs, format)?;
 
         debug!(
             "decryption shared_secret: {}",
             TextEncoding::Base64.encode(&shared_secret)?
         );
 
-        let pkf_key = kdf::kdf_inner_digest(
-            kdf,
-            Digest::Sha256,
-            &shared_secret,
-            salt,
-            info,
-            44,
-        )?;
+        let pkf_key = envelope.derive(&shared_secret, salt, info)?;
         debug!(
             "decryption pkf_key: {}",
             TextEncoding::Base64.encode(&pkf_key)?
         );
 
-        let (secret, iv) = pkf_key.split_at(32);
+        let (secret, iv) = pkf_key.split_at(envelope.key_size);
 
-        crypto::aes::encrypt_or_decrypt_aes(
-            encryption_alg.as_encryption_mode(),
+        crypto::encrypt_or_decrypt_ecies(
+            encryption_alg,
             &input,
             secret,
-            Some(iv.to_vec()),
-            None,
-            AesEncryptionPadding::NoPadding,
+            iv,
             for_encryption,
         )?
     })
 }
 
+// kits legacy envelope header: version || kdf || kdf digest || key size ||
+// nonce size, envelopes starting with the compressed point predate it and
+// always used sha256, a 32 bytes key and a 12 bytes nonce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EciesEnvelope {
+    kdf: Kdf,
+    kdf_digest: Digest,
+    key_size: usize,
+    nonce_size: usize,
+}
+
+impl EciesEnvelope {
+    const SIZE: usize = 5;
+    const VERSION: u8 = 1;
+
+    fn new(data: &EciesDto) -> Result<Self> {
+        let alg = data.encryption_alg;
+        let key_size = data.key_size.unwrap_or(alg.key_sizes()[0]);
+        if !alg.key_sizes().contains(&key_size) {
+            return Err(Error::Unsupported(format!(
+                "ecies {:?} key size {}",
+                alg, key_size
+            )));
+        }
+        let nonce_size = data.nonce_length.unwrap_or(alg.nonce_lengths()[0]);
+        if !alg.nonce_lengths().contains(&nonce_size) {
+            return Err(Error::Unsupported(format!(
+                "ecies {:?} nonce length {}",
+                alg, nonce_size
+            )));
+        }
+        Ok(EciesEnvelope {
+            kdf: data.kdf,
+            kdf_digest: data.get_kdf_digest(),
+            key_size: key_size / 8,
+            nonce_size,
+        })
+    }
+
+    fn to_bytes(self) -> [u8; Self::SIZE] {
+        [
+            Self::VERSION,
+            self.kdf as u8,
+            self.kdf_digest as u8,
+            self.key_size as u8,
+            self.nonce_size as u8,
+        ]
+    }
+
+    fn parse<'a>(data: &EciesDto, input: &'a [u8]) -> Result<(Self, &'a [u8])> {
+        if input.first() != Some(&Self::VERSION) {
+            return Ok((
+                EciesEnvelope {
+                    kdf: data.kdf,
+                    kdf_digest: Digest::Sha256,
+                    key_size: 32,
+                    nonce_size: 12,
+                },
+                input,
+            ));
+        }
+        if input.len() < Self::SIZE {
+            return Err(Error::Unsupported(
+                "ecies envelope header is truncated".to_string(),
+            ));
+        }
+        let (header, input) = input.split_at(Self::SIZE);
+        let envelope = EciesEnvelope {
+            kdf: Kdf::from_repr(header[1])
+                .context(format!("unknown ecies kdf {}", header[1]))?,
+            kdf_digest: Digest::from_repr(header[2])
+                .context(format!("unknown ecies kdf digest {}", header[2]))?,
+            key_size: header[3] as usize,
+            nonce_size: header[4] as usize,
+        };
+        let expected = EciesEnvelope::new(&EciesDto {
+            kdf_digest: data.kdf_digest.or(Some(envelope.kdf_digest)),
+            key_size: data.key_size.or(Some(envelope.key_size * 8)),
+            nonce_length: data.nonce_length.or(Some(envelope.nonce_size)),
+            ..data.clone()
+        })?;
+        if envelope != expected {
+            return Err(Error::Unsupported(format!(
+                "ecies envelope {:?} does not match {:?}",
+                envelope, expected
+            )));
+        }
+        Ok((envelope, input))
+    }
+
+    fn derive(
+        &self,
+        shared_secret: &[u8],
+        salt: Option<Vec<u8>>,
+        info: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>> {
+        kdf::kdf_inner_digest(
+            self.kdf,
+            self.kdf_digest,
+            shared_secret,
+            salt,
+            info,
+            self.key_size + self.nonce_size,
+        )
+    }
+}
+
 fn generate_secret<C>(
     key: &[u8],
     format: KeyFormat,
@@ -254,6 +437,576 @@ where
     Ok((input.to_vec(), shared_secret.raw_secret_bytes().to_vec()))
 }
 
+// SEC1 / ISO 18033-2 ECIES: R || M xor K_enc || HMAC(K_mac, C), where
+// K_enc || K_mac = X9.63-KDF(x(kR), info)
+fn sec1_ecies<C>(data: EciesDto) -> Result<Vec<u8>>
+where
+    C: elliptic_curve::Curve
+        + elliptic_curve::CurveArithmetic
+        + pkcs8::AssociatedOid
+        + elliptic_curve::point::PointCompression,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    let key = data.get_key()?;
+    let input = data.get_input()?;
+    let info = data.get_info()?;
+    let digest = data.get_kdf_digest();
+    let mac_size = digest.as_digest().output_size();
+    let derive = |shared: &EncodedPoint<C>, size: usize| {
+        let z = shared.x().context("shared point is identity")?;
+        kdf::kdf_inner_digest(
+            Kdf::AnsiX963,
+            digest,
+            z,
+            None,
+            info.clone(),
+            size + mac_size,
+        )
+    };
+    if data.for_encryption {
+        let public_key = import_ecc_public_key::<C>(&key, data.format)?;
+        let (ephemeral, shared) = ephemeral_shared_point(&public_key);
+        let keys = derive(&shared, input.len())?;
+        let (enc_key, mac_key) = keys.split_at(input.len());
+        let ciphertext = xor(&input, enc_key);
+        let tag = kdf::hmac_digest(digest, mac_key, &ciphertext)?;
+        Ok([ephemeral.as_bytes(), &ciphertext, &tag].concat())
+    } else {
+        let private_key =
+            import_ecc_private_key::<C>(&key, data.pkcs, data.format)?;
+        let (ephemeral, rest) = split_ephemeral_point::<C>(&input)?;
+        if rest.len() < mac_size {
+            return Err(Error::Unsupported("ecies tag is missing".to_string()));
+        }
+        let (ciphertext, tag) = rest.split_at(rest.len() - mac_size);
+        let keys =
+            derive(&shared_point(&private_key, &ephemeral), ciphertext.len())?;
+        let (enc_key, mac_key) = keys.split_at(ciphertext.len());
+        let expected = kdf::hmac_digest(digest, mac_key, ciphertext)?;
+        if !bool::from(expected.ct_eq(tag)) {
+            return Err(Error::Unsupported("ecies tag mismatch".to_string()));
+        }
+        Ok(xor(ciphertext, enc_key))
+    }
+}
+
+// eciesjs / eth-ecies: R || nonce || tag || AES-256-GCM(M), where the key is
+// HKDF-SHA256(R || kR) with both points uncompressed
+fn eciesjs(data: EciesDto) -> Result<Vec<u8>> {
+    type Aes256Gcm16 = AesGcm<aes::Aes256, U16>;
+
+    let key = data.get_key()?;
+    let input = data.get_input()?;
+    let derive = |ephemeral: &EncodedPoint<Secp256k1>,
+                  shared: &EncodedPoint<Secp256k1>| {
+        let ikm = [ephemeral.as_bytes(), shared.as_bytes()].concat();
+        let key = kdf::kdf_inner_digest(
+            Kdf::HKdf,
+            Digest::Sha256,
+            &ikm,
+            None,
+            None,
+            32,
+        )?;
+        Aes256Gcm16::new_from_slice(&key).context("init eciesjs cipher failed")
+    };
+    if data.for_encryption {
+        let public_key = import_ecc_public_key::<Secp256k1>(&key, data.format)?;
+        let (ephemeral, shared) = ephemeral_shared_point(&public_key);
+        let nonce = utils::random_bytes(16)?;
+        let mut buffer = input;
+        let tag = derive(&ephemeral, &shared)?
+            .encrypt_in_place_detached(
+                Nonce::<U16>::from_slice(&nonce),
+                &[],
+                &mut buffer,
+            )
+            .context("eciesjs encrypt failed")?;
+        Ok([ephemeral.as_bytes(), &nonce, &tag, &buffer].concat())
+    } else {
+        let private_key =
+            import_ecc_private_key::<Secp256k1>(&key, data.pkcs, data.format)?;
+        let (ephemeral, rest) = split_ephemeral_point::<Secp256k1>(&input)?;
+        if rest.len() < 32 {
+            return Err(Error::Unsupported(
+                "eciesjs nonce or tag is missing".to_string(),
+            ));
+        }
+        let (nonce, rest) = rest.split_at(16);
+        let (tag, ciphertext) = rest.split_at(16);
+        let shared = shared_point(&private_key, &ephemeral);
+        let mut buffer = ciphertext.to_vec();
+        derive(&ephemeral.to_encoded_point(false), &shared)?
+            .decrypt_in_place_detached(
+                Nonce::<U16>::from_slice(nonce),
+                &[],
+                &mut buffer,
+                Tag::from_slice(tag),
+            )
+            .context("eciesjs decrypt failed")?;
+        Ok(buffer)
+    }
+}
+
+// GB/T 32918.4: C1 = kG, C2 = M xor KDF(x2 || y2), C3 = SM3(x2 || M || y2)
+fn sm2_encryption(data: EciesDto, c1c3c2: bool) -> Result<Vec<u8>> {
+    let key = data.get_key()?;
+    let input = data.get_input()?;
+    let derive = |shared: &EncodedPoint<sm2::Sm2>, size: usize| {
+        let mut t = vec![0; size];
+        kdf::ansi_x963_into::<sm3::Sm3>(&shared.as_bytes()[1 ..], &[], &mut t);
+        t
+    };
+    let hash = |shared: &EncodedPoint<sm2::Sm2>, message: &[u8]| {
+        let (x2, y2) = shared.as_bytes()[1 ..].split_at(shared.len() / 2);
+        sm3::Sm3::new()
+            .chain_update(x2)
+            .chain_update(message)
+            .chain_update(y2)
+            .finalize()
+            .to_vec()
+    };
+    if data.for_encryption {
+        let public_key = import_ecc_public_key::<sm2::Sm2>(&key, data.format)?;
+        // an all zero key stream must be drawn again with a fresh k
+        let (c1, shared, t) = loop {
+            let (c1, shared) = ephemeral_shared_point(&public_key);
+            let t = derive(&shared, input.len());
+            if input.is_empty() || t.iter().any(|&b| b != 0) {
+                break (c1, shared, t);
+            }
+        };
+        let c2 = xor(&input, &t);
+        let c3 = hash(&shared, &input);
+        Ok(if c1c3c2 {
+            [c1.as_bytes(), &c3, &c2].concat()
+        } else {
+            [c1.as_bytes(), &c2, &c3].concat()
+        })
+    } else {
+        let private_key =
+            import_ecc_private_key::<sm2::Sm2>(&key, data.pkcs, data.format)?;
+        let (c1, rest) = split_ephemeral_point::<sm2::Sm2>(&input)?;
+        if rest.len() < 32 {
+            return Err(Error::Unsupported("sm2 c3 is missing".to_string()));
+        }
+        let (c2, c3) = if c1c3c2 {
+            let (c3, c2) = rest.split_at(32);
+            (c2, c3)
+        } else {
+            rest.split_at(rest.len() - 32)
+        };
+        let shared = shared_point(&private_key, &c1);
+        let t = derive(&shared, c2.len());
+        if !c2.is_empty() && t.iter().all(|&b| b == 0) {
+            return Err(Error::Unsupported(
+                "sm2 kdf output is zero".to_string(),
+            ));
+        }
+        let message = xor(c2, &t);
+        if !bool::from(hash(&shared, &message).ct_eq(c3)) {
+            return Err(Error::Unsupported("sm2 c3 mismatch".to_string()));
+        }
+        Ok(message)
+    }
+}
+
+// uncompressed ephemeral public key and shared point
+fn ephemeral_shared_point<C>(
+    public_key: &PublicKey<C>,
+) -> (EncodedPoint<C>, EncodedPoint<C>)
+where
+    C: elliptic_curve::CurveArithmetic,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    let ephemeral = SecretKey::<C>::random(&mut rand::thread_rng());
+    let shared = shared_point(&ephemeral, public_key);
+    (ephemeral.public_key().to_encoded_point(false), shared)
+}
+
+fn shared_point<C>(
+    secret_key: &SecretKey<C>,
+    public_key: &PublicKey<C>,
+) -> EncodedPoint<C>
+where
+    C: elliptic_curve::CurveArithmetic,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    (public_key.to_projective() * *secret_key.to_nonzero_scalar())
+        .to_affine()
+        .to_encoded_point(false)
+}
+
+fn split_ephemeral_point<C>(input: &[u8]) -> Result<(PublicKey<C>, &[u8])>
+where
+    C: elliptic_curve::CurveArithmetic,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    let size = 1 + 2 * FieldBytesSize::<C>::USIZE;
+    if input.len() < size {
+        return Err(Error::Unsupported(
+            "ephemeral public key is missing".to_string(),
+        ));
+    }
+    let (point, rest) = input.split_at(size);
+    let point = EncodedPoint::<C>::from_bytes(point)
+        .context("informat ephemeral public key")?;
+    let public_key = PublicKey::<C>::try_from(&point)
+        .context("build ephemeral public key failed")?;
+    Ok((public_key, rest))
+}
+
+fn xor(input: &[u8], key_stream: &[u8]) -> Vec<u8> {
+    input.iter().zip(key_stream).map(|(a, b)| a ^ b).collect()
+}
+
+// x coordinate of the shared point, the peer key may also be a bare sec1
+// point and ours a bare scalar
+pub(crate) fn ecdh_inner<C>(
+    private_key: &[u8],
+    public_key: &[u8],
+    pkcs: Pkcs,
+    format: KeyFormat,
+) -> Result<Vec<u8>>
+where
+    C: elliptic_curve::Curve
+        + elliptic_curve::CurveArithmetic
+        + pkcs8::AssociatedOid
+        + elliptic_curve::point::PointCompression,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    let secret_key =
+        match import_ecc_private_key::<C>(private_key, pkcs, format) {
+            Ok(secret_key) => secret_key,
+            Err(_) => SecretKey::<C>::from_slice(private_key)
+                .context("invalid ecdh private key")?,
+        };
+    let public_key = match import_ecc_public_key::<C>(public_key, format) {
+        Ok(public_key) => public_key,
+        Err(_) => PublicKey::<C>::from_sec1_bytes(public_key)
+            .context("invalid ecdh public key")?,
+    };
+    let shared_secret = elliptic_curve::ecdh::diffie_hellman(
+        secret_key.to_nonzero_scalar(),
+        public_key.as_affine(),
+    );
+    Ok(shared_secret.raw_secret_bytes().to_vec())
+}
+
+#[tauri::command]
+pub async fn sign_ecc(data: EcdsaDto) -> Result<String> {
+    info!("ecdsa sign: {:?}", data);
+    let key = data.get_key()?;
+    let input = data.get_input()?;
+    let signature = sign_ecc_inner(&data, &key, &input)?;
+    data.get_output_encoding().encode(&signature)
+}
+
+#[tauri::command]
+pub async fn verify_ecc(data: EcdsaDto) -> Result<bool> {
+    info!("ecdsa verify: {:?}", data);
+    let key = data.get_key()?;
+    let input = data.get_input()?;
+    let signature = data
+        .signature
+        .as_ref()
+        .ok_or(Error::Unsupported("signature is required".to_string()))?;
+    let signature = data.get_output_encoding().decode(signature)?;
+    verify_ecc_inner(&data, &key, &input, &signature)
+}
+
+pub fn sign_ecc_inner(
+    data: &EcdsaDto,
+    key: &[u8],
+    input: &[u8],
+) -> Result<Vec<u8>> {
+    let EcdsaDto {
+        curve_name,
+        pkcs,
+        format,
+        digest,
+        signature_format,
+        ..
+    } = *data;
+    if signature_format == EcdsaSignatureFormat::Recoverable
+        && curve_name != EccCurveName::Secp256k1
+    {
+        return Err(Error::Unsupported(format!(
+            "recoverable signature on {:?}",
+            curve_name
+        )));
+    }
+    let hashed = digest.hash(input);
+    match curve_name {
+        EccCurveName::NistP256 => {
+            let signing_key =
+                p256::ecdsa::SigningKey::from(import_ecc_private_key::<
+                    NistP256,
+                >(
+                    key, pkcs, format
+                )?);
+            ecdsa_sign(&signing_key, &hashed, signature_format)
+        }
+        EccCurveName::NistP384 => {
+            let signing_key =
+                p384::ecdsa::SigningKey::from(import_ecc_private_key::<
+                    p384::NistP384,
+                >(
+                    key, pkcs, format
+                )?);
+            ecdsa_sign(&signing_key, &hashed, signature_format)
+        }
+        EccCurveName::NistP521 => {
+            let signing_key = p521::ecdsa::SigningKey::from_bytes(
+                &import_ecc_private_key::<p521::NistP521>(key, pkcs, format)?
+                    .to_bytes(),
+            )
+            .context("p521 signing key failed")?;
+            ecdsa_sign(&signing_key, &hashed, signature_format)
+        }
+        EccCurveName::Secp256k1 => {
+            let signing_key =
+                k256::ecdsa::SigningKey::from(import_ecc_private_key::<
+                    k256::Secp256k1,
+                >(
+                    key, pkcs, format
+                )?);
+            if signature_format != EcdsaSignatureFormat::Recoverable {
+                return ecdsa_sign(&signing_key, &hashed, signature_format);
+            }
+            let (signature, recovery_id) = signing_key
+                .sign_prehash_recoverable(&hashed)
+                .context("secp256k1 sign failed")?;
+            let mut result = signature.to_vec();
+            result.push(recovery_id.to_byte());
+            Ok(result)
+        }
+        EccCurveName::SM2 => {
+            let secret_key =
+                import_ecc_private_key::<sm2::Sm2>(key, pkcs, format)?;
+            let signing_key = sm2::dsa::SigningKey::new(
+                data.distid.as_deref().unwrap_or(SM2_DEFAULT_DISTID),
+                &secret_key,
+            )
+            .context("sm2 signing key failed")?;
+            let signature: sm2::dsa::Signature =
+                signing_key.try_sign(input).context("sm2 sign failed")?;
+            let signature =
+                ecdsa::Signature::<sm2::Sm2>::from_slice(&signature.to_bytes())
+                    .context("sm2 signature failed")?;
+            encode_signature(signature, signature_format)
+        }
+    }
+}
+
+pub fn verify_ecc_inner(
+    data: &EcdsaDto,
+    key: &[u8],
+    input: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let EcdsaDto {
+        curve_name,
+        pkcs,
+        format,
+        digest,
+        signature_format,
+        ..
+    } = *data;
+    let hashed = digest.hash(input);
+    Ok(match curve_name {
+        EccCurveName::NistP256 => {
+            let verifying_key =
+                p256::ecdsa::VerifyingKey::from(verifying_public_key::<
+                    NistP256,
+                >(
+                    key, pkcs, format
+                )?);
+            ecdsa_verify(&verifying_key, &hashed, signature, signature_format)
+        }
+        EccCurveName::NistP384 => {
+            let verifying_key =
+                p384::ecdsa::VerifyingKey::from(verifying_public_key::<
+                    p384::NistP384,
+                >(
+                    key, pkcs, format
+                )?);
+            ecdsa_verify(&verifying_key, &hashed, signature, signature_format)
+        }
+        EccCurveName::NistP521 => {
+            let verifying_key = p521::ecdsa::VerifyingKey::from_affine(
+                *verifying_public_key::<p521::NistP521>(key, pkcs, format)?
+                    .as_affine(),
+            )
+            .context("p521 verifying key failed")?;
+            ecdsa_verify(&verifying_key, &hashed, signature, signature_format)
+        }
+        EccCurveName::Secp256k1 => {
+            let verifying_key =
+                k256::ecdsa::VerifyingKey::from(verifying_public_key::<
+                    k256::Secp256k1,
+                >(
+                    key, pkcs, format
+                )?);
+            if signature_format != EcdsaSignatureFormat::Recoverable {
+                return Ok(ecdsa_verify(
+                    &verifying_key,
+                    &hashed,
+                    signature,
+                    signature_format,
+                ));
+            }
+            let Some((v, signature)) = signature.split_last() else {
+                return Ok(false);
+            };
+            // accept both 0/1 and ethereum style 27/28 recovery bytes
+            let recovery_id =
+                RecoveryId::from_byte(if *v >= 27 { v - 27 } else { *v });
+            match (k256::ecdsa::Signature::from_slice(signature), recovery_id) {
+                (Ok(signature), Some(recovery_id)) => {
+                    k256::ecdsa::VerifyingKey::recover_from_prehash(
+                        &hashed,
+                        &signature,
+                        recovery_id,
+                    )
+                    .is_ok_and(|recovered| recovered == verifying_key)
+                }
+                _ => false,
+            }
+        }
+        EccCurveName::SM2 => {
+            let verifying_key = sm2::dsa::VerifyingKey::new(
+                data.distid.as_deref().unwrap_or(SM2_DEFAULT_DISTID),
+                verifying_public_key::<sm2::Sm2>(key, pkcs, format)?,
+            )
+            .context("sm2 verifying key failed")?;
+            decode_signature::<sm2::Sm2>(signature, signature_format)
+                .and_then(|signature| {
+                    sm2::dsa::Signature::from_slice(&signature.to_bytes()).ok()
+                })
+                .is_some_and(|signature| {
+                    verifying_key.verify(input, &signature).is_ok()
+                })
+        }
+    })
+}
+
+fn verifying_public_key<C>(
+    key: &[u8],
+    pkcs: Pkcs,
+    format: KeyFormat,
+) -> Result<elliptic_curve::PublicKey<C>>
+where
+    C: elliptic_curve::Curve
+        + elliptic_curve::CurveArithmetic
+        + pkcs8::AssociatedOid,
+    AffinePoint<C>: elliptic_curve::sec1::FromEncodedPoint<C>
+        + elliptic_curve::sec1::ToEncodedPoint<C>,
+    elliptic_curve::FieldBytesSize<C>: elliptic_curve::sec1::ModulusSize,
+{
+    import_ecc_public_key::<C>(key, format).or_else(|_| {
+        import_ecc_private_key::<C>(key, pkcs, format)
+            .map(|secret_key| secret_key.public_key())
+    })
+}
+
+fn ecdsa_sign<C>(
+    signing_key: &impl PrehashSigner<ecdsa::Signature<C>>,
+    hashed: &[u8],
+    signature_format: EcdsaSignatureFormat,
+) -> Result<Vec<u8>>
+where
+    C: PrimeCurve,
+    SignatureSize<C>: ArrayLength<u8>,
+    MaxSize<C>: ArrayLength<u8>,
+    <FieldBytesSize<C> as std::ops::Add>::Output:
+        std::ops::Add<MaxOverhead> + ArrayLength<u8>,
+{
+    let signature = signing_key
+        .sign_prehash(&prehash_field::<C>(hashed))
+        .context("ecdsa sign failed")?;
+    encode_signature(signature, signature_format)
+}
+
+fn ecdsa_verify<C>(
+    verifying_key: &impl PrehashVerifier<ecdsa::Signature<C>>,
+    hashed: &[u8],
+    signature: &[u8],
+    signature_format: EcdsaSignatureFormat,
+) -> bool
+where
+    C: PrimeCurve,
+    SignatureSize<C>: ArrayLength<u8>,
+    MaxSize<C>: ArrayLength<u8>,
+    <FieldBytesSize<C> as std::ops::Add>::Output:
+        std::ops::Add<MaxOverhead> + ArrayLength<u8>,
+{
+    decode_signature::<C>(signature, signature_format).is_some_and(
+        |signature| {
+            verifying_key
+                .verify_prehash(&prehash_field::<C>(hashed), &signature)
+                .is_ok()
+        },
+    )
+}
+
+// ecdsa rejects digests shorter than half the field, such as sha1 on p384 or
+// sha256 on p521, while FIPS 186 and RFC 6979 read any shorter digest as an
+// integer, which left padding with zeros keeps
+fn prehash_field<C: PrimeCurve>(hashed: &[u8]) -> Vec<u8> {
+    let size = FieldBytesSize::<C>::USIZE.saturating_sub(hashed.len());
+    [vec![0; size], hashed.to_vec()].concat()
+}
+
+fn encode_signature<C>(
+    signature: ecdsa::Signature<C>,
+    signature_format: EcdsaSignatureFormat,
+) -> Result<Vec<u8>>
+where
+    C: PrimeCurve,
+    SignatureSize<C>: ArrayLength<u8>,
+    MaxSize<C>: ArrayLength<u8>,
+    <FieldBytesSize<C> as std::ops::Add>::Output:
+        std::ops::Add<MaxOverhead> + ArrayLength<u8>,
+{
+    match signature_format {
+        EcdsaSignatureFormat::Der => Ok(signature.to_der().as_bytes().to_vec()),
+        EcdsaSignatureFormat::Raw => Ok(signature.to_vec()),
+        EcdsaSignatureFormat::Recoverable => Err(Error::Unsupported(
+            "recoverable signature without recovery id".to_string(),
+        )),
+    }
+}
+
+fn decode_signature<C>(
+    signature: &[u8],
+    signature_format: EcdsaSignatureFormat,
+) -> Option<ecdsa::Signature<C>>
+where
+    C: PrimeCurve,
+    SignatureSize<C>: ArrayLength<u8>,
+    MaxSize<C>: ArrayLength<u8>,
+    <FieldBytesSize<C> as std::ops::Add>::Output:
+        std::ops::Add<MaxOverhead> + ArrayLength<u8>,
+{
+    match signature_format {
+        EcdsaSignatureFormat::Der => ecdsa::Signature::from_der(signature),
+        EcdsaSignatureFormat::Raw => ecdsa::Signature::from_slice(signature),
+        EcdsaSignatureFormat::Recoverable => return None,
+    }
+    .ok()
+}
+
 #[cfg(test)]
 mod test {
     use strum::IntoEnumIterator;
@@ -261,11 +1014,15 @@ mod test {
     use tracing_test::traced_test;
 
     use crate::{
-        crypto::ecc::{ecies, key::generate_ecc, EciesDto},
+        crypto::ecc::{
+            ecies, key::generate_ecc, sign_ecc, verify_ecc, EcdsaDto, EciesDto,
+        },
         enums::{
-            Digest, EccCurveName, EciesEncryptionAlgorithm, Kdf, KeyFormat,
-            Pkcs, TextEncoding,
+            Digest, EccCurveName, EcdsaSignatureFormat,
+            EciesEncryptionAlgorithm, EciesProfile, Kdf, KeyFormat, Pkcs,
+            TextEncoding,
         },
+        errors::Error,
         utils::{self},
     };
 
@@ -302,7 +1059,7 @@ mod test {
                                 output_encoding: encoding,
                                 pkcs,
                                 kdf,
-                                kdf_digest,
+                                kdf_digest: Some(kdf_digest),
                                 salt: Some(salt.to_string()),
                                 salt_encoding: Some(TextEncoding::Base64),
                                 info: Some("info".to_string()),
@@ -310,6 +1067,9 @@ mod test {
                                 format,
                                 encryption_alg:
                                     EciesEncryptionAlgorithm::AesGcm,
+                                key_size: None,
+                                nonce_length: None,
+                                profile: None,
                                 for_encryption: true,
                             })
                             .await
@@ -325,7 +1085,7 @@ mod test {
                                     output_encoding: TextEncoding::Utf8,
                                     pkcs,
                                     kdf,
-                                    kdf_digest,
+                                    kdf_digest: Some(kdf_digest),
                                     salt: Some(salt.to_string()),
                                     salt_encoding: Some(TextEncoding::Base64),
                                     info: Some("info".to_string()),
@@ -333,6 +1093,9 @@ mod test {
                                     format,
                                     encryption_alg:
                                         EciesEncryptionAlgorithm::AesGcm,
+                                    key_size: None,
+                                    nonce_length: None,
+                                    profile: None,
                                     for_encryption: false,
                                 })
                                 .await
@@ -345,4 +1108,442 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_ecies_envelope() {
+        let encoding = TextEncoding::Base64;
+        let key = generate_ecc(
+            EccCurveName::NistP256,
+            Pkcs::Pkcs8,
+            KeyFormat::Pem,
+            encoding,
+        )
+        .await
+        .unwrap();
+        let mut dto = EciesDto {
+            curve_name: EccCurveName::NistP256,
+            key: key.1.unwrap(),
+            key_encoding: encoding,
+            input: "plaintext".to_string(),
+            input_encoding: TextEncoding::Utf8,
+            output_encoding: encoding,
+            pkcs: Pkcs::Pkcs8,
+            kdf: Kdf::HKdf,
+            kdf_digest: Some(Digest::Sha384),
+            salt: None,
+            salt_encoding: None,
+            info: None,
+            info_encoding: None,
+            format: KeyFormat::Pem,
+            encryption_alg: EciesEncryptionAlgorithm::AesGcm,
+            key_size: Some(128),
+            nonce_length: Some(16),
+            profile: None,
+            for_encryption: true,
+        };
+        let ciphertext = ecies(dto.clone()).await.unwrap();
+        dto.key = key.0.unwrap();
+        dto.input = ciphertext;
+        dto.input_encoding = encoding;
+        dto.output_encoding = TextEncoding::Utf8;
+        dto.for_encryption = false;
+        assert_eq!(ecies(dto.clone()).await.unwrap(), "plaintext");
+        // version, hkdf, sha384, 16 bytes key and 16 bytes nonce
+        assert_eq!(encoding.decode(&dto.input).unwrap()[.. 5], [
+            1, 0, 2, 16, 16
+        ]);
+        assert_eq!(Kdf::iter().map(|kdf| kdf as u8).collect::<Vec<_>>(), [
+            0, 1, 2, 3, 4
+        ]);
+        assert_eq!(
+            Digest::iter()
+                .map(|digest| digest as u8)
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        // sizes default to the ones recorded in the envelope
+        dto.key_size = None;
+        dto.nonce_length = None;
+        assert_eq!(ecies(dto.clone()).await.unwrap(), "plaintext");
+        for mismatch in [
+            EciesDto {
+                kdf_digest: Some(Digest::Sha256),
+                ..dto.clone()
+            },
+            EciesDto {
+                kdf: Kdf::Concatenation,
+                ..dto.clone()
+            },
+            EciesDto {
+                key_size: Some(256),
+                ..dto.clone()
+            },
+        ] {
+            assert!(ecies(mismatch).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ecies_envelope_sizes() {
+        let encoding = TextEncoding::Base64;
+        let key = generate_ecc(
+            EccCurveName::NistP256,
+            Pkcs::Pkcs8,
+            KeyFormat::Pem,
+            encoding,
+        )
+        .await
+        .unwrap();
+        for (encryption_alg, key_size, nonce_length) in [
+            (EciesEncryptionAlgorithm::ChaCha20Poly1305, 128, 12),
+            (EciesEncryptionAlgorithm::ChaCha20Poly1305, 256, 16),
+            (EciesEncryptionAlgorithm::AesGcmSiv, 256, 16),
+            (EciesEncryptionAlgorithm::AesCcm, 256, 16),
+            (EciesEncryptionAlgorithm::AesOcb3, 128, 16),
+            (EciesEncryptionAlgorithm::AesSiv, 128, 12),
+            (EciesEncryptionAlgorithm::Sm4Gcm, 256, 12),
+            (EciesEncryptionAlgorithm::AesGcm, 192, 12),
+        ] {
+            let result = ecies(EciesDto {
+                curve_name: EccCurveName::NistP256,
+                key: key.1.clone().unwrap(),
+                key_encoding: encoding,
+                input: "plaintext".to_string(),
+                input_encoding: TextEncoding::Utf8,
+                output_encoding: encoding,
+                pkcs: Pkcs::Pkcs8,
+                kdf: Kdf::HKdf,
+                kdf_digest: Some(Digest::Sha256),
+                salt: None,
+                salt_encoding: None,
+                info: None,
+                info_encoding: None,
+                format: KeyFormat::Pem,
+                encryption_alg,
+                key_size: Some(key_size),
+                nonce_length: Some(nonce_length),
+                profile: None,
+                for_encryption: true,
+            })
+            .await;
+            assert!(
+                matches!(result, Err(Error::Unsupported(_))),
+                "{:?} {} {}",
+                encryption_alg,
+                key_size,
+                nonce_length
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ecies_encryption_algorithms() {
+        let encoding = TextEncoding::Base64;
+        let key = generate_ecc(
+            EccCurveName::SM2,
+            Pkcs::Pkcs8,
+            KeyFormat::Pem,
+            encoding,
+        )
+        .await
+        .unwrap();
+        for encryption_alg in EciesEncryptionAlgorithm::iter() {
+            let dto = EciesDto {
+                curve_name: EccCurveName::SM2,
+                key: key.1.clone().unwrap(),
+                key_encoding: encoding,
+                input: "plaintext".to_string(),
+                input_encoding: TextEncoding::Utf8,
+                output_encoding: encoding,
+                pkcs: Pkcs::Pkcs8,
+                kdf: Kdf::HKdf,
+                kdf_digest: None,
+                salt: None,
+                salt_encoding: None,
+                info: None,
+                info_encoding: None,
+                format: KeyFormat::Pem,
+                encryption_alg,
+                key_size: None,
+                nonce_length: None,
+                profile: None,
+                for_encryption: true,
+            };
+            let ciphertext = ecies(dto.clone()).await.unwrap();
+            let dto = EciesDto {
+                key: key.0.clone().unwrap(),
+                input: ciphertext,
+                input_encoding: encoding,
+                output_encoding: TextEncoding::Utf8,
+                for_encryption: false,
+                ..dto
+            };
+            assert_eq!(ecies(dto.clone()).await.unwrap(), "plaintext");
+            // sm2 defaults the kdf digest to sm3
+            assert!(ecies(EciesDto {
+                kdf_digest: Some(Digest::Sha256),
+                ..dto
+            })
+            .await
+            .is_err());
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_ecies_profiles() {
+        let encoding = TextEncoding::Base64;
+        for curve_name in EccCurveName::iter() {
+            let key =
+                generate_ecc(curve_name, Pkcs::Pkcs8, KeyFormat::Der, encoding)
+                    .await
+                    .unwrap();
+            for profile in EciesProfile::iter() {
+                let mut dto = EciesDto {
+                    curve_name,
+                    key: key.1.clone().unwrap(),
+                    key_encoding: encoding,
+                    input: "plaintext".to_string(),
+                    input_encoding: TextEncoding::Utf8,
+                    output_encoding: encoding,
+                    pkcs: Pkcs::Pkcs8,
+                    kdf: Kdf::HKdf,
+                    kdf_digest: Some(Digest::Sha384),
+                    salt: None,
+                    salt_encoding: None,
+                    info: Some("info".to_string()),
+                    info_encoding: Some(TextEncoding::Utf8),
+                    format: KeyFormat::Der,
+                    encryption_alg: EciesEncryptionAlgorithm::AesGcm,
+                    key_size: None,
+                    nonce_length: None,
+                    profile: Some(profile),
+                    for_encryption: true,
+                };
+                let ciphertext = ecies(dto.clone()).await;
+                let supported = match profile {
+                    EciesProfile::Eciesjs => {
+                        curve_name == EccCurveName::Secp256k1
+                    }
+                    EciesProfile::Sm2C1C3C2 | EciesProfile::Sm2C1C2C3 => {
+                        curve_name == EccCurveName::SM2
+                    }
+                    _ => true,
+                };
+                if !supported {
+                    assert!(ciphertext.is_err());
+                    continue;
+                }
+                let ciphertext = encoding.decode(&ciphertext.unwrap()).unwrap();
+                dto.key = key.0.clone().unwrap();
+                dto.input = encoding.encode(&ciphertext).unwrap();
+                dto.input_encoding = encoding;
+                dto.output_encoding = TextEncoding::Utf8;
+                dto.for_encryption = false;
+                assert_eq!(ecies(dto.clone()).await.unwrap(), "plaintext");
+
+                let mut tampered = ciphertext.clone();
+                *tampered.last_mut().unwrap() ^= 1;
+                dto.input = encoding.encode(&tampered).unwrap();
+                assert!(ecies(dto).await.is_err());
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_ecies_reference_vectors() {
+        for (curve_name, profile, key, ciphertext) in [
+            // eciesjs format from the ecies crate, its rust sibling
+            (
+                EccCurveName::Secp256k1,
+                EciesProfile::Eciesjs,
+                "308184020100301006072a8648ce3d020106052b8104000a046d306b\
+                 0201010420d9a62c7e4f1b3b8e8a3fb6a0c4d0e3a5f5f3b6c7d8e9fa\
+                 0b1c2d3e4f5a6b7c8da144034200041938c1b187bdfadcac85a5e459\
+                 eff098a04a0b5ec2ce0d23bf2acb671611111b62ec47626f74ad7c0b\
+                 0a4a7f15681de86a8f177797899583e2b230b4d32745b5",
+                "040e775de4e8cce048df7b710e2c8176a12990419319a24527d01e1d\
+                 3db5ed98cee7c8c7a1bb6a695b25b9bf471f1da396e1cb73ee9b67bb\
+                 c20227cd3bf3e2a7cbaaf82134a2f0c7a232229c4b8efaeb35f29185\
+                 d4c36d378420bdef1c601a44eaf5a7048cd9679bb893",
+            ),
+            // sec1 with the x9.63 kdf and hmac from openssl through python
+            // cryptography, info "kits"
+            (
+                EccCurveName::NistP256,
+                EciesProfile::Sec1,
+                "308187020100301306072a8648ce3d020106082a8648ce3d03010704\
+                 6d306b0201010420c9afa9d845ba75166b5c215767b1d6934e50c3db\
+                 36799b8c5a50eda0a2e4d92ca14403420004587d20347a0b85c57188\
+                 d1697cad04fa938097d367d0b9aae2e7ed33496c55d9d17784d1c52c\
+                 055cae6c8ce8d49c6f4fb12cf5fa7ab536908529a1908310278f",
+                "0413f2f89926e058c4bed00606d10341fd60b15d7778281496262f7d\
+                 995bd53216502066f789ff0101874f0d894c31e10961c3acbc654d51\
+                 8f7f7e149e6af6d55ab976902aa25f51adfa1c69a49c5cf6ddd32831\
+                 d5740cd129c3627b743a2a4b090ea4f2d18b42a9e45e",
+            ),
+            // openssl pkeyutl -encrypt, its asn.1 members laid out in both
+            // orders
+            (
+                EccCurveName::SM2,
+                EciesProfile::Sm2C1C3C2,
+                "308187020100301306072a8648ce3d020106082a811ccf5501822d04\
+                 6d306b02010104205fb6342145e1a5f450f7054899303c8b522b1ef4\
+                 616f56a0bc935c4f39132cd1a1440342000490726fd634046fb1dcfb\
+                 a3c64db940fa078e6473a32d3315d1beb6e7be0ee13d1c3c973a8818\
+                 77b2f482e950c815504650fe6cc17bed5e9e93ecfd23a209207c",
+                "0438945497f733c0076cdfc3721303bc92007c6ba513dbd338523cd5\
+                 684d2bd22df4bdd81ce8ce886c1bd4174e46aa2f533911040e3922f7\
+                 ffbf001210f5f9af8788652d1e3d9c3bd571277f5e11e3bcd4137062\
+                 fc4db3d058c45f078e92ed97d9cc9759ca7675c2529e",
+            ),
+            (
+                EccCurveName::SM2,
+                EciesProfile::Sm2C1C2C3,
+                "308187020100301306072a8648ce3d020106082a811ccf5501822d04\
+                 6d306b02010104205fb6342145e1a5f450f7054899303c8b522b1ef4\
+                 616f56a0bc935c4f39132cd1a1440342000490726fd634046fb1dcfb\
+                 a3c64db940fa078e6473a32d3315d1beb6e7be0ee13d1c3c973a8818\
+                 77b2f482e950c815504650fe6cc17bed5e9e93ecfd23a209207c",
+                "0438945497f733c0076cdfc3721303bc92007c6ba513dbd338523cd5\
+                 684d2bd22df4bdd81ce8ce886c1bd4174e46aa2f533911040e3922f7\
+                 ffbf001210f5f9af87cc9759ca7675c2529e88652d1e3d9c3bd57127\
+                 7f5e11e3bcd4137062fc4db3d058c45f078e92ed97d9",
+            ),
+        ] {
+            let dto = EciesDto {
+                curve_name,
+                key: key.to_string(),
+                key_encoding: TextEncoding::Hex,
+                input: ciphertext.to_string(),
+                input_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Utf8,
+                pkcs: Pkcs::Pkcs8,
+                kdf: Kdf::AnsiX963,
+                kdf_digest: None,
+                salt: None,
+                salt_encoding: None,
+                info: Some("kits".to_string()),
+                info_encoding: Some(TextEncoding::Utf8),
+                format: KeyFormat::Der,
+                encryption_alg: EciesEncryptionAlgorithm::AesGcm,
+                key_size: None,
+                nonce_length: None,
+                profile: Some(profile),
+                for_encryption: false,
+            };
+            assert_eq!(ecies(dto).await.unwrap(), "plaintext", "{:?}", profile);
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_sign_and_verify() {
+        let encoding = TextEncoding::Base64;
+        for curve_name in EccCurveName::iter() {
+            let key =
+                generate_ecc(curve_name, Pkcs::Pkcs8, KeyFormat::Pem, encoding)
+                    .await
+                    .unwrap();
+            for (signature_format, digest) in EcdsaSignatureFormat::iter()
+                .flat_map(|format| {
+                    Digest::iter().map(move |digest| (format, digest))
+                })
+            {
+                let mut dto = EcdsaDto {
+                    curve_name,
+                    key: key.0.clone().unwrap(),
+                    key_encoding: encoding,
+                    input: "plaintext".to_string(),
+                    input_encoding: TextEncoding::Utf8,
+                    output_encoding: encoding,
+                    pkcs: Pkcs::Pkcs8,
+                    format: KeyFormat::Pem,
+                    digest,
+                    distid: Some("ALICE123@YAHOO.COM".to_string()),
+                    signature_format,
+                    signature: None,
+                };
+                let signature = sign_ecc(dto.clone()).await;
+                if signature_format == EcdsaSignatureFormat::Recoverable
+                    && curve_name != EccCurveName::Secp256k1
+                {
+                    assert!(signature.is_err());
+                    continue;
+                }
+                dto.key = key.1.clone().unwrap();
+                dto.signature = Some(signature.unwrap());
+                assert!(
+                    verify_ecc(dto.clone()).await.unwrap(),
+                    "{:?} {:?}",
+                    curve_name,
+                    digest
+                );
+                dto.input = "tampered".to_string();
+                assert!(!verify_ecc(dto).await.unwrap());
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_rfc6979_signatures() {
+        // RFC 6979 appendix A.2.6, P-384 signing "sample"
+        let private_key = "3081b6020100301006072a8648ce3d020106052b8104002204\
+                           819e30819b02010104306b9d3dad2e1b8c1c05b19875b6659f\
+                           4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825\
+                           f872c9ea60d2edf5a16403620004ec3a4e415b4e19a4568618\
+                           029f427fa5da9a8bc4ae92e02e06aae5286b300c64def8f0ea\
+                           9055866064a254515480bc138015d9b72d7d57244ea8ef9ac0\
+                           c621896708a59367f9dfb9f54ca84b3f1c9db1288b231c3ae0\
+                           d4fe7344fd2533264720";
+        let public_key = "3076301006072a8648ce3d020106052b810400220362000\
+                          4ec3a4e415b4e19a4568618029f427fa5da9a8bc4ae92e02e0\
+                          6aae5286b300c64def8f0ea9055866064a254515480bc13801\
+                          5d9b72d7d57244ea8ef9ac0c621896708a59367f9dfb9f54ca\
+                          84b3f1c9db1288b231c3ae0d4fe7344fd2533264720";
+        for (digest, expected) in [
+            // shorter than half the field
+            (
+                Digest::Sha1,
+                "ec748d839243d6fbef4fc5c4859a7dffd7f3abddf72014540c16d733\
+                 09834fa37b9ba002899f6fda3a4a9386790d4eb2a3bcfa947beef473\
+                 2bf247ac17f71676cb31a847b9ff0cbc9c9ed4c1a5b3facf26f49ca0\
+                 31d4857570ccb5ca4424a443",
+            ),
+            (
+                Digest::Sha384,
+                "94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea\
+                 95ad133c81a648152e44acf96e36dd1e80fabe4699ef4aeb15f178ce\
+                 a1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145\
+                 786e679e7b82c71a38628ac8",
+            ),
+        ] {
+            let mut dto = EcdsaDto {
+                curve_name: EccCurveName::NistP384,
+                key: private_key.to_string(),
+                key_encoding: TextEncoding::Hex,
+                input: "sample".to_string(),
+                input_encoding: TextEncoding::Utf8,
+                output_encoding: TextEncoding::Hex,
+                pkcs: Pkcs::Pkcs8,
+                format: KeyFormat::Der,
+                digest,
+                distid: None,
+                signature_format: EcdsaSignatureFormat::Raw,
+                signature: None,
+            };
+            let signature = sign_ecc(dto.clone()).await.unwrap();
+            // the nonce hmac uses the curve digest rather than the message
+            // digest, so only sha384 reproduces the rfc signature
+            if digest == Digest::Sha384 {
+                assert_eq!(signature, expected);
+            }
+            dto.key = public_key.to_string();
+            dto.signature = Some(expected.to_string());
+            assert!(verify_ecc(dto).await.unwrap());
+        }
+    }
 }
@@ -420,7 +420,7 @@ where
     })
 }
 
-fn export_ecc_public_key<C>(
+pub(crate) fn export_ecc_public_key<C>(
     public_key: elliptic_curve::PublicKey<C>,
     encoding: KeyFormat,
 ) -> Result<Vec<u8>>
@@ -0,0 +1,252 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::{ecc::ecdh_inner, edwards::ecdh_edwards, kdf};
+use crate::{
+    add_encryption_trait_impl,
+    crypto::{decode_optional, EncryptionDto},
+    enums::{
+        Digest, EccCurveName, EcdhCurveName, Kdf, KeyFormat, Pkcs, TextEncoding,
+    },
+    errors::{Error, Result},
+};
+
+// input is the peer public key and key is our private key
+add_encryption_trait_impl!(EcdhDto {
+    curve_name: EcdhCurveName,
+    pkcs: Pkcs,
+    format: KeyFormat,
+    // feeds the shared secret into the kdf when present
+    kdf: Option<Kdf>,
+    kdf_digest: Option<Digest>,
+    salt: Option<String>,
+    salt_encoding: Option<TextEncoding>,
+    info: Option<String>,
+    info_encoding: Option<TextEncoding>,
+    // defaults to the shared secret length
+    key_length: Option<usize>
+});
+
+impl Debug for EcdhDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EcdhDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("curve_name", &self.curve_name)
+            .field("pkcs", &self.pkcs)
+            .field("key_format", &self.format)
+            .field("kdf", &self.kdf)
+            .field("kdf_digest", &self.kdf_digest)
+            .field("salt_encoding", &self.salt_encoding)
+            .field("info_encoding", &self.info_encoding)
+            .field("key_length", &self.key_length)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcdhOutput {
+    pub shared_secret: String,
+    pub derived_key: Option<String>,
+}
+
+#[tauri::command]
+pub async fn ecdh(data: EcdhDto) -> Result<EcdhOutput> {
+    info!("ecdh: {:?}", data);
+    let private_key = data.get_key()?;
+    let public_key = data.get_input()?;
+    let EcdhDto { pkcs, format, .. } = data;
+    let shared_secret = match data.curve_name {
+        EcdhCurveName::Ecc(EccCurveName::NistP256) => {
+            ecdh_inner::<p256::NistP256>(
+                &private_key,
+                &public_key,
+                pkcs,
+                format,
+            )
+        }
+        EcdhCurveName::Ecc(EccCurveName::NistP384) => {
+            ecdh_inner::<p384::NistP384>(
+                &private_key,
+                &public_key,
+                pkcs,
+                format,
+            )
+        }
+        EcdhCurveName::Ecc(EccCurveName::NistP521) => {
+            ecdh_inner::<p521::NistP521>(
+                &private_key,
+                &public_key,
+                pkcs,
+                format,
+            )
+        }
+        EcdhCurveName::Ecc(EccCurveName::Secp256k1) => {
+            ecdh_inner::<k256::Secp256k1>(
+                &private_key,
+                &public_key,
+                pkcs,
+                format,
+            )
+        }
+        EcdhCurveName::Ecc(EccCurveName::SM2) => {
+            ecdh_inner::<sm2::Sm2>(&private_key, &public_key, pkcs, format)
+        }
+        EcdhCurveName::Edwards(curve_name) => {
+            ecdh_edwards(curve_name, &private_key, &public_key, format)
+        }
+    }?;
+    let derived_key = match data.kdf {
+        Some(kdf) => {
+            let digest = match (data.kdf_digest, data.curve_name) {
+                (Some(digest), _) => digest,
+                (None, EcdhCurveName::Ecc(EccCurveName::SM2)) => Digest::Sm3,
+                (None, _) => {
+                    return Err(Error::Unsupported(
+                        "kdf digest is required".to_string(),
+                    ))
+                }
+            };
+            let salt = decode_optional(&data.salt, data.salt_encoding, "salt")?;
+            let info = decode_optional(&data.info, data.info_encoding, "info")?;
+            let derived_key = kdf::kdf_inner_digest(
+                kdf,
+                digest,
+                &shared_secret,
+                salt,
+                info,
+                data.key_length.unwrap_or(shared_secret.len()),
+            )?;
+            Some(data.output_encoding.encode(&derived_key)?)
+        }
+        None => None,
+    };
+    Ok(EcdhOutput {
+        shared_secret: data.output_encoding.encode(&shared_secret)?,
+        derived_key,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing_test::traced_test;
+
+    use super::{ecdh, EcdhDto};
+    use crate::{
+        crypto::{ecc::key::generate_ecc, edwards::key::generate_edwards},
+        enums::{
+            Digest, EccCurveName, EcdhCurveName, EdwardsCurveName, Kdf,
+            KeyFormat, Pkcs, TextEncoding,
+        },
+    };
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_ecdh() {
+        let encoding = TextEncoding::Base64;
+        let curves = EccCurveName::iter()
+            .map(EcdhCurveName::Ecc)
+            .chain(EdwardsCurveName::iter().map(EcdhCurveName::Edwards));
+        for curve_name in curves {
+            let generate = || async {
+                match curve_name {
+                    EcdhCurveName::Ecc(curve_name) => generate_ecc(
+                        curve_name,
+                        Pkcs::Pkcs8,
+                        KeyFormat::Pem,
+                        encoding,
+                    )
+                    .await
+                    .unwrap(),
+                    EcdhCurveName::Edwards(curve_name) => {
+                        generate_edwards(curve_name, KeyFormat::Pem, encoding)
+                            .await
+                            .unwrap()
+                    }
+                }
+            };
+            let (alice, bob) = (generate().await, generate().await);
+            let dto = EcdhDto {
+                input: bob.1.unwrap(),
+                input_encoding: encoding,
+                key: alice.0.unwrap(),
+                key_encoding: encoding,
+                output_encoding: TextEncoding::Hex,
+                curve_name,
+                pkcs: Pkcs::Pkcs8,
+                format: KeyFormat::Pem,
+                kdf: Some(Kdf::HKdf),
+                kdf_digest: Some(Digest::Sha256),
+                salt: None,
+                salt_encoding: None,
+                info: Some("info".to_string()),
+                info_encoding: Some(TextEncoding::Utf8),
+                key_length: Some(16),
+            };
+            let alice_output = ecdh(dto.clone()).await.unwrap();
+            let bob_output = ecdh(EcdhDto {
+                input: alice.1.unwrap(),
+                key: bob.0.unwrap(),
+                ..dto
+            })
+            .await
+            .unwrap();
+            assert_eq!(alice_output.shared_secret, bob_output.shared_secret);
+            assert_eq!(alice_output.derived_key, bob_output.derived_key);
+            assert_eq!(alice_output.derived_key.unwrap().len(), 32);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ecdh_raw_keys() {
+        for (curve_name, private_key, public_key, shared_secret) in [
+            // RFC 7748 section 6.1
+            (
+                EcdhCurveName::Edwards(EdwardsCurveName::X25519),
+                "77076d0a7318a57d3c16c17251b26645\
+                 df4c2f87ebc0992ab177fba51db92c2a",
+                "de9edb7d7b7dc1b4d35b61c2ece43537\
+                 3f8343c85b78674dadfc7e146f882b4f",
+                "4a5d9d5ba4ce2de1728e3bf480350f25\
+                 e07e21c947d19e3376f09b3c1e161742",
+            ),
+            // bare scalar and compressed sec1 point
+            (
+                EcdhCurveName::Ecc(EccCurveName::NistP256),
+                "1f2e3d4c5b6a79880123456789abcdef\
+                 fedcba98765432100f1e2d3c4b5a6978",
+                "021a4fb9bf45afaee3c74ffaae8c05bcc7\
+                 1007128fa575678078cdf5aa9eb16af9",
+                "b17f93d7b54bf37dd3f784f147d3447d\
+                 3ca5ec8197ac4b9e2c8f02cf29d58592",
+            ),
+        ] {
+            let output = ecdh(EcdhDto {
+                input: public_key.to_string(),
+                input_encoding: TextEncoding::Hex,
+                key: private_key.to_string(),
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                curve_name,
+                pkcs: Pkcs::Pkcs8,
+                format: KeyFormat::Der,
+                kdf: None,
+                kdf_digest: None,
+                salt: None,
+                salt_encoding: None,
+                info: None,
+                info_encoding: None,
+                key_length: None,
+            })
+            .await
+            .unwrap();
+            assert_eq!(output.shared_secret, shared_secret);
+            assert!(output.derived_key.is_none());
+        }
+    }
+}
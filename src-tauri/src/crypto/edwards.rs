@@ -1,17 +1,27 @@
+use std::fmt::Debug;
+
+use anyhow::Context;
 use base64ct::Encoding;
+use curve25519_dalek::{
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    Scalar,
+};
+use ed25519_dalek::{Signer, Verifier};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use sha2::Digest;
+use tracing::{debug, info};
 
 use crate::{
     add_encryption_trait_impl,
     crypto::{self, kdf::SALT, EncryptionDto},
     enums::{
-        AesEncryptionPadding, EciesEncryptionAlgorithm, EdwardsCurveName,
-        EncryptionMode, KeyFormat, TextEncoding,
+        EciesEncryptionAlgorithm, EddsaVariant, EdwardsCurveName, KeyFormat,
+        TextEncoding,
     },
-    errors::Result,
+    errors::{Error, Result},
 };
 
+mod curve448;
 pub mod key;
 
 add_encryption_trait_impl!(EciesEdwardsDto {
@@ -21,6 +31,282 @@ add_encryption_trait_impl!(EciesEdwardsDto {
     for_encryption: bool
 });
 
+add_encryption_trait_impl!(EddsaDto {
+    curve_name: EdwardsCurveName,
+    format: KeyFormat,
+    variant: EddsaVariant,
+    // utf-8 context of the prehash and context variants, at most 255 bytes
+    context: Option<String>,
+    // reject non-canonical and small order signatures and keys
+    strict: bool,
+    // verification only, encoded with output_encoding
+    signature: Option<String>
+});
+
+impl Debug for EddsaDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EddsaDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("curve_name", &self.curve_name)
+            .field("format", &self.format)
+            .field("variant", &self.variant)
+            .field("context", &self.context)
+            .field("strict", &self.strict)
+            .finish()
+    }
+}
+
+#[tauri::command]
+pub async fn sign_edwards(data: EddsaDto) -> Result<String> {
+    info!("eddsa sign: {:?}", data);
+    let input = data.get_input()?;
+    let key = data.get_key()?;
+    let context = data.context.as_deref().map(str::as_bytes);
+    let signature = match data.curve_name {
+        EdwardsCurveName::Curve25519 => {
+            let signing_key =
+                key::import_curve_25519_private_key(&key, data.format)?;
+            sign_curve_25519(&signing_key, &input, data.variant, context)?
+        }
+        EdwardsCurveName::Curve448 => {
+            let secret = key::import_curve_448_private_key(&key, data.format)?;
+            sign_curve_448(&secret, &input, data.variant, context)?
+        }
+        EdwardsCurveName::X25519 => {
+            return Err(Error::Unsupported(
+                "x25519 keys cannot sign".to_string(),
+            ));
+        }
+    };
+    data.get_output_encoding().encode(&signature)
+}
+
+#[tauri::command]
+pub async fn verify_edwards(data: EddsaDto) -> Result<bool> {
+    info!("eddsa verify: {:?}", data);
+    let input = data.get_input()?;
+    let key = data.get_key()?;
+    let context = data.context.as_deref().map(str::as_bytes);
+    let signature = data
+        .signature
+        .as_ref()
+        .ok_or(Error::Unsupported("signature is required".to_string()))?;
+    let signature = data.get_output_encoding().decode(signature)?;
+    match data.curve_name {
+        EdwardsCurveName::Curve25519 => {
+            let verifying_key =
+                key::import_curve_25519_public_key(&key, data.format).or_else(
+                    |_| {
+                        key::import_curve_25519_private_key(&key, data.format)
+                            .map(|signing_key| signing_key.verifying_key())
+                    },
+                )?;
+            verify_curve_25519(
+                &verifying_key,
+                &input,
+                &signature,
+                data.variant,
+                context,
+                data.strict,
+            )
+        }
+        EdwardsCurveName::Curve448 => {
+            let public_key =
+                key::import_curve_448_public_key(&key, data.format).or_else(
+                    |_| {
+                        key::import_curve_448_private_key(&key, data.format)
+                            .map(|secret| curve448::ed448_public_key(&secret))
+                    },
+                )?;
+            verify_curve_448(
+                &public_key,
+                &input,
+                &signature,
+                data.variant,
+                context,
+                data.strict,
+            )
+        }
+        EdwardsCurveName::X25519 => {
+            Err(Error::Unsupported("x25519 keys cannot verify".to_string()))
+        }
+    }
+}
+
+pub(crate) fn sign_curve_25519(
+    signing_key: &ed25519_dalek::SigningKey,
+    input: &[u8],
+    variant: EddsaVariant,
+    context: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    let context = check_context(variant, context, false)?;
+    Ok(match variant {
+        EddsaVariant::Pure => signing_key
+            .try_sign(input)
+            .context("ed25519 sign failed")?
+            .to_vec(),
+        EddsaVariant::Prehash => signing_key
+            .sign_prehashed(sha2::Sha512::new().chain_update(input), context)
+            .context("ed25519ph sign failed")?
+            .to_vec(),
+        EddsaVariant::Context => {
+            let context = context.unwrap_or_default();
+            let prefix = sha2::Sha512::digest(signing_key.as_bytes());
+            let r = Scalar::from_hash(
+                dom2(context)
+                    .chain_update(&prefix[32 ..])
+                    .chain_update(input),
+            );
+            let big_r = EdwardsPoint::mul_base(&r).compress();
+            let k = Scalar::from_hash(
+                dom2(context)
+                    .chain_update(big_r.as_bytes())
+                    .chain_update(signing_key.verifying_key().as_bytes())
+                    .chain_update(input),
+            );
+            let s = r + k * signing_key.to_scalar();
+            [big_r.to_bytes(), s.to_bytes()].concat()
+        }
+    })
+}
+
+pub(crate) fn verify_curve_25519(
+    verifying_key: &ed25519_dalek::VerifyingKey,
+    input: &[u8],
+    signature: &[u8],
+    variant: EddsaVariant,
+    context: Option<&[u8]>,
+    strict: bool,
+) -> Result<bool> {
+    let context = check_context(variant, context, false)?;
+    let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+        return Ok(false);
+    };
+    Ok(match (variant, strict) {
+        (EddsaVariant::Pure, false) => {
+            verifying_key.verify(input, &signature).is_ok()
+        }
+        (EddsaVariant::Pure, true) => {
+            verifying_key.verify_strict(input, &signature).is_ok()
+        }
+        (EddsaVariant::Prehash, false) => verifying_key
+            .verify_prehashed(
+                sha2::Sha512::new().chain_update(input),
+                context,
+                &signature,
+            )
+            .is_ok(),
+        (EddsaVariant::Prehash, true) => verifying_key
+            .verify_prehashed_strict(
+                sha2::Sha512::new().chain_update(input),
+                context,
+                &signature,
+            )
+            .is_ok(),
+        (EddsaVariant::Context, _) => {
+            let context = context.unwrap_or_default();
+            let big_r = CompressedEdwardsY(*signature.r_bytes());
+            let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(
+                *signature.s_bytes(),
+            ));
+            let points = CompressedEdwardsY(*verifying_key.as_bytes())
+                .decompress()
+                .zip(big_r.decompress());
+            match (points, s) {
+                (Some((a, r)), Some(s))
+                    if !strict
+                        || !(a.is_small_order() || r.is_small_order()) =>
+                {
+                    let k = Scalar::from_hash(
+                        dom2(context)
+                            .chain_update(big_r.as_bytes())
+                            .chain_update(verifying_key.as_bytes())
+                            .chain_update(input),
+                    );
+                    let expected =
+                        EdwardsPoint::vartime_double_scalar_mul_basepoint(
+                            &k, &-a, &s,
+                        );
+                    if strict {
+                        expected == r
+                    } else {
+                        expected.compress() == big_r
+                    }
+                }
+                _ => false,
+            }
+        }
+    })
+}
+
+pub(crate) fn sign_curve_448(
+    secret: &[u8; curve448::SECRET_LENGTH],
+    input: &[u8],
+    variant: EddsaVariant,
+    context: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    let context = check_context(variant, context, true)?.unwrap_or_default();
+    Ok(curve448::ed448_sign(
+        secret,
+        input,
+        context,
+        variant == EddsaVariant::Prehash,
+    )
+    .to_vec())
+}
+
+pub(crate) fn verify_curve_448(
+    public_key: &[u8; curve448::PUBLIC_LENGTH],
+    input: &[u8],
+    signature: &[u8],
+    variant: EddsaVariant,
+    context: Option<&[u8]>,
+    strict: bool,
+) -> Result<bool> {
+    let context = check_context(variant, context, true)?.unwrap_or_default();
+    Ok(curve448::ed448_verify(
+        public_key,
+        input,
+        context,
+        variant == EddsaVariant::Prehash,
+        signature,
+        strict,
+    ))
+}
+
+// pure Ed448 signs under an optional context, pure Ed25519 cannot
+fn check_context(
+    variant: EddsaVariant,
+    context: Option<&[u8]>,
+    pure_context: bool,
+) -> Result<Option<&[u8]>> {
+    let context = context.filter(|context| !context.is_empty());
+    match (variant, context) {
+        (EddsaVariant::Pure, Some(_)) if !pure_context => {
+            Err(Error::Unsupported(
+                "pure eddsa does not take a context".to_string(),
+            ))
+        }
+        (EddsaVariant::Context, None) => Err(Error::Unsupported(
+            "eddsa context variant requires a context".to_string(),
+        )),
+        (_, Some(context)) if context.len() > 255 => Err(Error::Unsupported(
+            "eddsa context must be at most 255 bytes".to_string(),
+        )),
+        _ => Ok(context),
+    }
+}
+
+// RFC 8032 dom2(0, context) prefix of every Ed25519ctx hash
+fn dom2(context: &[u8]) -> sha2::Sha512 {
+    sha2::Sha512::new()
+        .chain_update(b"SigEd25519 no Ed25519 collisions")
+        .chain_update([0, context.len() as u8])
+        .chain_update(context)
+}
+
 #[tauri::command]
 pub async fn ecies_edwards(data: EciesEdwardsDto) -> Result<String> {
     let input = data.get_input()?;
@@ -28,7 +314,17 @@ pub async fn ecies_edwards(data: EciesEdwardsDto) -> Result<String> {
     let output_encoding = data.get_output_encoding();
 
     let output = match data.curve_name {
-        EdwardsCurveName::Curve25519 => curve_25519_ecies(
+        EdwardsCurveName::Curve25519 | EdwardsCurveName::X25519 => {
+            curve_25519_ecies(
+                data.curve_name,
+                &input,
+                &key,
+                data.format,
+                data.encryption_alg,
+                data.for_encryption,
+            )
+        }
+        EdwardsCurveName::Curve448 => curve_448_ecies(
             &input,
             &key,
             data.format,
@@ -40,6 +336,7 @@ pub async fn ecies_edwards(data: EciesEdwardsDto) -> Result<String> {
 }
 
 pub(crate) fn curve_25519_ecies(
+    curve_name: EdwardsCurveName,
     input: &[u8],
     key: &[u8],
     format: KeyFormat,
@@ -47,91 +344,492 @@ pub(crate) fn curve_25519_ecies(
     for_encryption: bool,
 ) -> Result<Vec<u8>> {
     if for_encryption {
-        curve_25519_ecies_encrypt(input, key, format, ea)
+        curve_25519_ecies_encrypt(curve_name, input, key, format, ea)
     } else {
-        curve_25519_ecies_decrypt(input, key, format, ea)
+        curve_25519_ecies_decrypt(curve_name, input, key, format, ea)
     }
 }
 
 fn curve_25519_ecies_encrypt(
+    curve_name: EdwardsCurveName,
     input: &[u8],
     key: &[u8],
     format: KeyFormat,
-    _ea: EciesEncryptionAlgorithm,
+    ea: EciesEncryptionAlgorithm,
 ) -> Result<Vec<u8>> {
     let rng = rand::thread_rng();
     let mut result = Vec::new();
     let receiver_secret_key =
         x25519_dalek::EphemeralSecret::random_from_rng(rng);
-    let verifying_key = key::import_curve_25519_public_key(key, format)?;
-    let public_key =
-        x25519_dalek::PublicKey::from(verifying_key.to_montgomery().to_bytes());
+    let public_key = curve_25519_public_key(curve_name, key, format)?;
     let receiver_public_key =
         x25519_dalek::PublicKey::from(&receiver_secret_key);
     let receiver_public_key_bytes = receiver_public_key.as_bytes();
     result.extend_from_slice(receiver_public_key_bytes);
     let shared_secret = receiver_secret_key.diffie_hellman(&public_key);
-    let pkf_key = pbkdf2::pbkdf2_hmac_array::<sha2::Sha512, 44>(
-        shared_secret.as_bytes(),
-        SALT.as_bytes(),
-        210_000,
-    );
-
-    let (secret, iv) = pkf_key.split_at(32);
+    let (secret, iv) = ecies_key(shared_secret.as_bytes(), ea);
     debug!(
         "decryption shared_secret_bytes: {}",
-        base64ct::Base64::encode_string(secret)
+        base64ct::Base64::encode_string(&secret)
     );
-    let encrypted = crypto::aes::encrypt_or_decrypt_aes(
-        EncryptionMode::Gcm,
-        input,
-        secret,
-        Some(iv.to_vec()),
-        None,
-        AesEncryptionPadding::NoPadding,
-        true,
-    )?;
+    let encrypted =
+        crypto::encrypt_or_decrypt_ecies(ea, input, &secret, &iv, true)?;
     result.extend_from_slice(&encrypted);
     Ok(result)
 }
 
 fn curve_25519_ecies_decrypt(
+    curve_name: EdwardsCurveName,
     input: &[u8],
     key: &[u8],
     format: KeyFormat,
-    _ea: EciesEncryptionAlgorithm,
+    ea: EciesEncryptionAlgorithm,
 ) -> Result<Vec<u8>> {
-    let signing_key = key::import_curve_25519_private_key(key, format)?;
-
-    let verify_key = signing_key.verifying_key();
-    let mont_verify_key = verify_key.to_montgomery().to_bytes();
-
-    let (receiver_secret_bytes, input) = input.split_at(mont_verify_key.len());
+    let private_key = curve_25519_private_key(curve_name, key, format)?;
+    if input.len() < 32 {
+        return Err(Error::Unsupported(
+            "x25519 ciphertext too short".to_string(),
+        ));
+    }
+    let (receiver_secret_bytes, input) = input.split_at(32);
     let mut receiver_secret = [0; 32];
     receiver_secret.copy_from_slice(receiver_secret_bytes);
 
-    let private_key =
-        x25519_dalek::StaticSecret::from(signing_key.to_scalar_bytes());
     let public_key = x25519_dalek::PublicKey::from(receiver_secret);
     let shared_secret = private_key.diffie_hellman(&public_key);
-    let pkf_key = pbkdf2::pbkdf2_hmac_array::<sha2::Sha512, 44>(
-        shared_secret.as_bytes(),
+    let (secret, iv) = ecies_key(shared_secret.as_bytes(), ea);
+    debug!(
+        "decryption shared_secret_bytes: {}",
+        base64ct::Base64::encode_string(&secret)
+    );
+    crypto::encrypt_or_decrypt_ecies(ea, input, &secret, &iv, false)
+}
+
+// pbkdf2 stretched cipher key || 12 bytes nonce, the key is sized for the
+// encryption algorithm
+fn ecies_key(
+    shared_secret: &[u8],
+    ea: EciesEncryptionAlgorithm,
+) -> (Vec<u8>, Vec<u8>) {
+    let key_size = ea.key_sizes()[0] / 8;
+    let mut secret = vec![0; key_size + 12];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha512>(
+        shared_secret,
         SALT.as_bytes(),
         210_000,
+        &mut secret,
     );
+    let iv = secret.split_off(key_size);
+    (secret, iv)
+}
 
-    let (secret, iv) = pkf_key.split_at(32);
-    debug!(
-        "decryption shared_secret_bytes: {}",
-        base64ct::Base64::encode_string(secret)
-    );
-    crypto::aes::encrypt_or_decrypt_aes(
-        EncryptionMode::Gcm,
-        input,
-        secret,
-        Some(iv.to_vec()),
-        None,
-        AesEncryptionPadding::NoPadding,
-        false,
-    )
+// the curve name decides how a key is read, raw 32 bytes keys are x25519
+// keys for x25519 and ed25519 keys converted to montgomery form otherwise
+pub(crate) fn curve_25519_public_key(
+    curve_name: EdwardsCurveName,
+    key: &[u8],
+    format: KeyFormat,
+) -> Result<x25519_dalek::PublicKey> {
+    match curve_name {
+        EdwardsCurveName::X25519 => key::import_x25519_public_key(key, format)
+            .or_else(|_| key::import_x25519_raw_public_key(key)),
+        EdwardsCurveName::Curve25519 => {
+            key::import_curve_25519_public_key(key, format)
+                .or_else(|_| key::import_curve_25519_raw_public_key(key))
+                .map(|verifying_key| {
+                    x25519_dalek::PublicKey::from(
+                        verifying_key.to_montgomery().to_bytes(),
+                    )
+                })
+        }
+        EdwardsCurveName::Curve448 => Err(Error::Unsupported(
+            "curve448 keys are not curve 25519 keys".to_string(),
+        )),
+    }
+}
+
+pub(crate) fn curve_25519_private_key(
+    curve_name: EdwardsCurveName,
+    key: &[u8],
+    format: KeyFormat,
+) -> Result<x25519_dalek::StaticSecret> {
+    match curve_name {
+        EdwardsCurveName::X25519 => key::import_x25519_private_key(key, format)
+            .or_else(|_| key::import_x25519_raw_private_key(key)),
+        EdwardsCurveName::Curve25519 => {
+            key::import_curve_25519_private_key(key, format)
+                .or_else(|_| key::import_curve_25519_raw_private_key(key))
+                .map(|signing_key| {
+                    x25519_dalek::StaticSecret::from(
+                        signing_key.to_scalar_bytes(),
+                    )
+                })
+        }
+        EdwardsCurveName::Curve448 => Err(Error::Unsupported(
+            "curve448 keys are not curve 25519 keys".to_string(),
+        )),
+    }
+}
+
+// x25519 or x448 shared secret, ed25519 and ed448 keys are converted to
+// their montgomery form
+pub(crate) fn ecdh_edwards(
+    curve_name: EdwardsCurveName,
+    private_key: &[u8],
+    public_key: &[u8],
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    match curve_name {
+        EdwardsCurveName::Curve25519 | EdwardsCurveName::X25519 => {
+            let shared_secret =
+                curve_25519_private_key(curve_name, private_key, format)?
+                    .diffie_hellman(&curve_25519_public_key(
+                        curve_name, public_key, format,
+                    )?);
+            if !shared_secret.was_contributory() {
+                return Err(Error::Unsupported(
+                    "x25519 shared secret is zero".to_string(),
+                ));
+            }
+            Ok(shared_secret.as_bytes().to_vec())
+        }
+        EdwardsCurveName::Curve448 => {
+            let secret =
+                match key::import_curve_448_private_key(private_key, format) {
+                    Ok(secret) => curve448::ed448_to_x448_secret(&secret),
+                    Err(_) => private_key.try_into().map_err(|_| {
+                        Error::Unsupported("x448 key length".to_string())
+                    })?,
+                };
+            let public_key =
+                match key::import_curve_448_public_key(public_key, format) {
+                    Ok(public_key) => curve448::ed448_to_x448_public(
+                        &public_key,
+                    )
+                    .ok_or(Error::Unsupported(
+                        "invalid ed448 public key".to_string(),
+                    ))?,
+                    Err(_) => public_key.try_into().map_err(|_| {
+                        Error::Unsupported("x448 key length".to_string())
+                    })?,
+                };
+            curve448::x448(&secret, &public_key)
+                .map(|shared_secret| shared_secret.to_vec())
+                .ok_or(Error::Unsupported(
+                    "x448 shared secret is zero".to_string(),
+                ))
+        }
+    }
+}
+
+pub(crate) fn curve_448_ecies(
+    input: &[u8],
+    key: &[u8],
+    format: KeyFormat,
+    ea: EciesEncryptionAlgorithm,
+    for_encryption: bool,
+) -> Result<Vec<u8>> {
+    if for_encryption {
+        curve_448_ecies_encrypt(input, key, format, ea)
+    } else {
+        curve_448_ecies_decrypt(input, key, format, ea)
+    }
+}
+
+fn curve_448_ecies_encrypt(
+    input: &[u8],
+    key: &[u8],
+    format: KeyFormat,
+    ea: EciesEncryptionAlgorithm,
+) -> Result<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut receiver_secret = [0u8; curve448::X448_LENGTH];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut receiver_secret);
+    let verifying_key = key::import_curve_448_public_key(key, format)?;
+    let public_key = curve448::ed448_to_x448_public(&verifying_key)
+        .ok_or(Error::Unsupported("invalid ed448 public key".to_string()))?;
+    result.extend_from_slice(&curve448::x448_public_key(&receiver_secret));
+    let shared_secret = curve448::x448(&receiver_secret, &public_key)
+        .ok_or(Error::Unsupported("x448 shared secret is zero".to_string()))?;
+    let (secret, iv) = ecies_key(&shared_secret, ea);
+    let encrypted =
+        crypto::encrypt_or_decrypt_ecies(ea, input, &secret, &iv, true)?;
+    result.extend_from_slice(&encrypted);
+    Ok(result)
+}
+
+fn curve_448_ecies_decrypt(
+    input: &[u8],
+    key: &[u8],
+    format: KeyFormat,
+    ea: EciesEncryptionAlgorithm,
+) -> Result<Vec<u8>> {
+    let secret = key::import_curve_448_private_key(key, format)?;
+    if input.len() < curve448::X448_LENGTH {
+        return Err(Error::Unsupported(
+            "x448 ciphertext too short".to_string(),
+        ));
+    }
+    let (receiver_public_bytes, input) = input.split_at(curve448::X448_LENGTH);
+    let mut receiver_public_key = [0u8; curve448::X448_LENGTH];
+    receiver_public_key.copy_from_slice(receiver_public_bytes);
+
+    let private_key = curve448::ed448_to_x448_secret(&secret);
+    let shared_secret = curve448::x448(&private_key, &receiver_public_key)
+        .ok_or(Error::Unsupported("x448 shared secret is zero".to_string()))?;
+    let (secret, iv) = ecies_key(&shared_secret, ea);
+    crypto::encrypt_or_decrypt_ecies(ea, input, &secret, &iv, false)
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing_test::traced_test;
+
+    use crate::{
+        crypto::edwards::{
+            ecies_edwards,
+            key::{
+                export_curve_25519_private_key, export_x25519_private_key,
+                generate_edwards,
+            },
+            sign_curve_25519, sign_edwards, verify_curve_25519, verify_edwards,
+            EciesEdwardsDto, EddsaDto,
+        },
+        enums::{
+            EciesEncryptionAlgorithm, EddsaVariant, EdwardsCurveName,
+            KeyFormat, TextEncoding,
+        },
+    };
+
+    fn signing_key(hex: &str) -> ed25519_dalek::SigningKey {
+        let mut secret = [0u8; 32];
+        secret.copy_from_slice(&TextEncoding::Hex.decode(hex).unwrap());
+        ed25519_dalek::SigningKey::from_bytes(&secret)
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_sign_and_verify_curve_25519() {
+        // RFC 8032 section 7.2 and 7.3
+        for (variant, secret, message, context, expected) in [
+            (
+                EddsaVariant::Context,
+                "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6",
+                "f726936d19c800494e3fdaff20b276a8",
+                Some(b"foo".as_slice()),
+                "55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a\
+                 8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d",
+            ),
+            (
+                EddsaVariant::Prehash,
+                "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+                "616263",
+                None,
+                "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae41\
+                 31f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406",
+            ),
+        ] {
+            let signing_key = signing_key(secret);
+            let message = TextEncoding::Hex.decode(message).unwrap();
+            let signature =
+                sign_curve_25519(&signing_key, &message, variant, context)
+                    .unwrap();
+            assert_eq!(TextEncoding::Hex.encode(&signature).unwrap(), expected);
+            for strict in [false, true] {
+                assert!(verify_curve_25519(
+                    &signing_key.verifying_key(),
+                    &message,
+                    &signature,
+                    variant,
+                    context,
+                    strict,
+                )
+                .unwrap());
+            }
+        }
+
+        let signing_key = signing_key(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        );
+        let signature =
+            sign_curve_25519(&signing_key, b"", EddsaVariant::Pure, None)
+                .unwrap();
+        // RFC 8032 section 7.1 test 1
+        assert_eq!(
+            TextEncoding::Hex.encode(&signature).unwrap(),
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+             5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+        );
+        assert!(!verify_curve_25519(
+            &signing_key.verifying_key(),
+            b"tampered",
+            &signature,
+            EddsaVariant::Pure,
+            None,
+            true,
+        )
+        .unwrap());
+        assert!(sign_curve_25519(
+            &signing_key,
+            b"",
+            EddsaVariant::Context,
+            None
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_edwards_sign_and_ecies() {
+        let encoding = TextEncoding::Base64;
+        for curve_name in EdwardsCurveName::iter() {
+            for format in [KeyFormat::Pem, KeyFormat::Der] {
+                let key = generate_edwards(curve_name, format, encoding)
+                    .await
+                    .unwrap();
+                for variant in EddsaVariant::iter()
+                    .filter(|_| curve_name != EdwardsCurveName::X25519)
+                {
+                    let mut dto = EddsaDto {
+                        curve_name,
+                        key: key.0.clone().unwrap(),
+                        key_encoding: encoding,
+                        input: "release manifest".to_string(),
+                        input_encoding: TextEncoding::Utf8,
+                        output_encoding: encoding,
+                        format,
+                        variant,
+                        context: (variant == EddsaVariant::Context)
+                            .then(|| "kits".to_string()),
+                        strict: true,
+                        signature: None,
+                    };
+                    dto.signature =
+                        Some(sign_edwards(dto.clone()).await.unwrap());
+                    dto.key = key.1.clone().unwrap();
+                    assert!(verify_edwards(dto.clone()).await.unwrap());
+                    dto.input = "tampered".to_string();
+                    assert!(!verify_edwards(dto).await.unwrap());
+                }
+
+                let ciphertext = ecies_edwards(EciesEdwardsDto {
+                    curve_name,
+                    key: key.1.clone().unwrap(),
+                    key_encoding: encoding,
+                    input: "plaintext".to_string(),
+                    input_encoding: TextEncoding::Utf8,
+                    output_encoding: encoding,
+                    format,
+                    encryption_alg: EciesEncryptionAlgorithm::AesGcm,
+                    for_encryption: true,
+                })
+                .await
+                .unwrap();
+                let plaintext = ecies_edwards(EciesEdwardsDto {
+                    curve_name,
+                    key: key.0.clone().unwrap(),
+                    key_encoding: encoding,
+                    input: ciphertext,
+                    input_encoding: encoding,
+                    output_encoding: TextEncoding::Utf8,
+                    format,
+                    encryption_alg: EciesEncryptionAlgorithm::AesGcm,
+                    for_encryption: false,
+                })
+                .await
+                .unwrap();
+                assert_eq!(plaintext, "plaintext");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_raw_x25519_ecies() {
+        for encryption_alg in EciesEncryptionAlgorithm::iter() {
+            let secret =
+                x25519_dalek::StaticSecret::random_from_rng(rand::thread_rng());
+            let public_key = x25519_dalek::PublicKey::from(&secret);
+            let ciphertext = ecies_edwards(EciesEdwardsDto {
+                curve_name: EdwardsCurveName::X25519,
+                key: TextEncoding::Hex.encode(public_key.as_bytes()).unwrap(),
+                key_encoding: TextEncoding::Hex,
+                input: "plaintext".to_string(),
+                input_encoding: TextEncoding::Utf8,
+                output_encoding: TextEncoding::Base64,
+                format: KeyFormat::Der,
+                encryption_alg,
+                for_encryption: true,
+            })
+            .await
+            .unwrap();
+            let private_key =
+                export_x25519_private_key(&secret, KeyFormat::Pem)
+                    .map(String::from_utf8)
+                    .unwrap()
+                    .unwrap();
+            let plaintext = ecies_edwards(EciesEdwardsDto {
+                curve_name: EdwardsCurveName::X25519,
+                key: private_key,
+                key_encoding: TextEncoding::Utf8,
+                input: ciphertext,
+                input_encoding: TextEncoding::Base64,
+                output_encoding: TextEncoding::Utf8,
+                format: KeyFormat::Pem,
+                encryption_alg,
+                for_encryption: false,
+            })
+            .await
+            .unwrap();
+            assert_eq!(plaintext, "plaintext");
+        }
+    }
+    #[tokio::test]
+    async fn test_raw_ed25519_ecies() {
+        let signing_key =
+            ed25519_dalek::SigningKey::generate(&mut rand::thread_rng());
+        let dto = |curve_name, key: &[u8], input: String| EciesEdwardsDto {
+            curve_name,
+            key: TextEncoding::Hex.encode(key).unwrap(),
+            key_encoding: TextEncoding::Hex,
+            input,
+            input_encoding: TextEncoding::Base64,
+            output_encoding: TextEncoding::Base64,
+            format: KeyFormat::Der,
+            encryption_alg: EciesEncryptionAlgorithm::AesGcm,
+            for_encryption: false,
+        };
+        let ciphertext = ecies_edwards(EciesEdwardsDto {
+            input: "plaintext".to_string(),
+            input_encoding: TextEncoding::Utf8,
+            for_encryption: true,
+            ..dto(
+                EdwardsCurveName::Curve25519,
+                signing_key.verifying_key().as_bytes(),
+                String::new(),
+            )
+        })
+        .await
+        .unwrap();
+        let private_key =
+            export_curve_25519_private_key(&signing_key, KeyFormat::Der)
+                .unwrap();
+        for key in [signing_key.as_bytes().as_slice(), &private_key] {
+            let plaintext = ecies_edwards(EciesEdwardsDto {
+                output_encoding: TextEncoding::Utf8,
+                ..dto(EdwardsCurveName::Curve25519, key, ciphertext.clone())
+            })
+            .await
+            .unwrap();
+            assert_eq!(plaintext, "plaintext");
+        }
+        // the seed is not an x25519 scalar
+        assert!(ecies_edwards(dto(
+            EdwardsCurveName::X25519,
+            signing_key.as_bytes(),
+            ciphertext
+        ))
+        .await
+        .is_err());
+    }
 }
@@ -0,0 +1,326 @@
+//! Ed448 (RFC 8032) and X448 (RFC 7748) over the constant time
+//! ed448-goldilocks arithmetic.
+
+use ed448_goldilocks::{
+    curve::{edwards::CompressedEdwardsY, ExtendedPoint, MontgomeryPoint},
+    Scalar,
+};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+pub(crate) const SECRET_LENGTH: usize = 57;
+pub(crate) const PUBLIC_LENGTH: usize = 57;
+pub(crate) const SIGNATURE_LENGTH: usize = 114;
+pub(crate) const X448_LENGTH: usize = 56;
+
+fn shake256<const N: usize>(parts: &[&[u8]]) -> [u8; N] {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut output = [0u8; N];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+// a 114 byte little endian integer reduced modulo the group order
+fn scalar(bytes: &[u8]) -> Scalar {
+    let mut wide = [0u8; SIGNATURE_LENGTH];
+    wide[.. bytes.len()].copy_from_slice(bytes);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+// only canonical encodings round trip, which rejects y >= p, a set unused
+// bit and the sign of a zero x
+fn decode(bytes: &[u8]) -> Option<ExtendedPoint> {
+    let bytes: [u8; PUBLIC_LENGTH] = bytes.try_into().ok()?;
+    let point = CompressedEdwardsY(bytes).decompress()?;
+    (point.compress().0 == bytes).then_some(point)
+}
+
+fn is_small_order(point: &ExtendedPoint) -> bool {
+    point.double().double() == ExtendedPoint::identity()
+}
+
+// RFC 8032 dom4(phflag, context)
+fn dom4(prehash: bool, context: &[u8]) -> Vec<u8> {
+    [
+        b"SigEd448".as_slice(),
+        &[prehash as u8, context.len() as u8],
+        context,
+    ]
+    .concat()
+}
+
+// the clamped secret scalar and the nonce prefix
+fn expand(
+    secret: &[u8; SECRET_LENGTH],
+) -> ([u8; SECRET_LENGTH], [u8; SECRET_LENGTH]) {
+    let hash = shake256::<SIGNATURE_LENGTH>(&[secret]);
+    let mut scalar = [0u8; SECRET_LENGTH];
+    let mut prefix = [0u8; SECRET_LENGTH];
+    scalar.copy_from_slice(&hash[.. SECRET_LENGTH]);
+    prefix.copy_from_slice(&hash[SECRET_LENGTH ..]);
+    scalar[0] &= 0xfc;
+    scalar[SECRET_LENGTH - 1] = 0;
+    scalar[SECRET_LENGTH - 2] |= 0x80;
+    (scalar, prefix)
+}
+
+fn prehashed(message: &[u8], prehash: bool) -> Vec<u8> {
+    if prehash {
+        shake256::<64>(&[message]).to_vec()
+    } else {
+        message.to_vec()
+    }
+}
+
+pub(crate) fn ed448_public_key(
+    secret: &[u8; SECRET_LENGTH],
+) -> [u8; PUBLIC_LENGTH] {
+    let (s, _) = expand(secret);
+    ExtendedPoint::generator()
+        .scalar_mul(&scalar(&s))
+        .compress()
+        .0
+}
+
+pub(crate) fn ed448_sign(
+    secret: &[u8; SECRET_LENGTH],
+    message: &[u8],
+    context: &[u8],
+    prehash: bool,
+) -> [u8; SIGNATURE_LENGTH] {
+    let (s, prefix) = expand(secret);
+    let s = scalar(&s);
+    let public_key = ExtendedPoint::generator().scalar_mul(&s).compress().0;
+    let dom = dom4(prehash, context);
+    let message = prehashed(message, prehash);
+    let r = scalar(&shake256::<SIGNATURE_LENGTH>(&[&dom, &prefix, &message]));
+    let big_r = ExtendedPoint::generator().scalar_mul(&r).compress().0;
+    let k = scalar(&shake256::<SIGNATURE_LENGTH>(&[
+        &dom,
+        &big_r,
+        &public_key,
+        &message,
+    ]));
+    let mut signature = [0u8; SIGNATURE_LENGTH];
+    signature[.. PUBLIC_LENGTH].copy_from_slice(&big_r);
+    signature[PUBLIC_LENGTH ..]
+        .copy_from_slice(&(r + k * s).to_bytes_rfc_8032());
+    signature
+}
+
+pub(crate) fn ed448_verify(
+    public_key: &[u8],
+    message: &[u8],
+    context: &[u8],
+    prehash: bool,
+    signature: &[u8],
+    strict: bool,
+) -> bool {
+    if signature.len() != SIGNATURE_LENGTH {
+        return false;
+    }
+    let (big_r, big_s) = signature.split_at(PUBLIC_LENGTH);
+    let (Some(a), Some(r)) = (decode(public_key), decode(big_r)) else {
+        return false;
+    };
+    let Some(s) = big_s.try_into().ok().and_then(Scalar::from_canonical_bytes)
+    else {
+        return false;
+    };
+    let dom = dom4(prehash, context);
+    let message = prehashed(message, prehash);
+    let k = scalar(&shake256::<SIGNATURE_LENGTH>(&[
+        &dom, big_r, public_key, &message,
+    ]));
+    let lhs = ExtendedPoint::generator().scalar_mul(&s);
+    let rhs = r.add(&a.scalar_mul(&k));
+    if strict {
+        !is_small_order(&a) && !is_small_order(&r) && lhs == rhs
+    } else {
+        lhs.double().double() == rhs.double().double()
+    }
+}
+
+pub(crate) fn x448(
+    scalar: &[u8; X448_LENGTH],
+    u: &[u8; X448_LENGTH],
+) -> Option<[u8; X448_LENGTH]> {
+    let mut k = *scalar;
+    k[0] &= 0xfc;
+    k[X448_LENGTH - 1] |= 0x80;
+    let shared = &MontgomeryPoint(*u) * &Scalar::from_bytes(k);
+    // low order points give the all zero output
+    if shared.as_bytes().iter().all(|byte| *byte == 0) {
+        return None;
+    }
+    Some(shared.0)
+}
+
+pub(crate) fn x448_public_key(scalar: &[u8; X448_LENGTH]) -> [u8; X448_LENGTH] {
+    x448(scalar, MontgomeryPoint::generator().as_bytes())
+        .expect("x448 base point")
+}
+
+// the clamped Ed448 scalar is a valid X448 private key
+pub(crate) fn ed448_to_x448_secret(
+    secret: &[u8; SECRET_LENGTH],
+) -> [u8; X448_LENGTH] {
+    let mut scalar = [0u8; X448_LENGTH];
+    scalar.copy_from_slice(&expand(secret).0[.. X448_LENGTH]);
+    scalar
+}
+
+// RFC 7748 4-isogeny from edwards448 to curve448, u = y^2 / x^2
+pub(crate) fn ed448_to_x448_public(
+    public_key: &[u8],
+) -> Option<[u8; X448_LENGTH]> {
+    let u = decode(public_key)?.to_montgomery();
+    // x = 0 maps to u = 0
+    if u.as_bytes().iter().all(|byte| *byte == 0) {
+        return None;
+    }
+    Some(u.0)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        crypto::edwards::curve448::{
+            ed448_public_key, ed448_sign, ed448_to_x448_public,
+            ed448_to_x448_secret, ed448_verify, x448, x448_public_key,
+        },
+        enums::TextEncoding,
+    };
+
+    fn bytes<const N: usize>(hex: &str) -> [u8; N] {
+        TextEncoding::Hex.decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_ed448() {
+        // RFC 8032 section 7.4, blank and 1 octet with context, and section
+        // 7.5 Ed448ph without and with context
+        for (secret, public_key, message, context, prehash, signature) in [
+            (
+                "6c82a562cb808d10d632be89c8513ebf6c929f34ddfa8c9f63c9960ef6e348a3\
+                 528c8a3fcc2f044e39a3fc5b94492f8f032e7549a20098f95b",
+                "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778\
+                 edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180",
+                "",
+                "",
+                false,
+                "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f\
+                 2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a\
+                 9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4db\
+                 b61149f05a7363268c71d95808ff2e652600",
+            ),
+            (
+                "c4eab05d357007c632f3dbb48489924d552b08fe0c353a0d4a1f00acda2c463a\
+                 fbea67c5e8d2877c5e3bc397a659949ef8021e954e0a12274e",
+                "43ba28f430cdff456ae531545f7ecd0ac834a55d9358c0372bfa0c6c6798c086\
+                 6aea01eb00742802b8438ea4cb82169c235160627b4c3a9480",
+                "03",
+                "666f6f",
+                false,
+                "d4f8f6131770dd46f40867d6fd5d5055de43541f8c5e35abbcd001b32a89f7d2\
+                 151f7647f11d8ca2ae279fb842d607217fce6e042f6815ea000c85741de5c8da\
+                 1144a6a1aba7f96de42505d7a7298524fda538fccbbb754f578c1cad10d54d0d\
+                 5428407e85dcbc98a49155c13764e66c3c00",
+            ),
+            (
+                "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42\
+                 ef7822e0d5104127dc05d6dbefde69e3ab2cec7c867c6e2c49",
+                "259b71c19f83ef77a7abd26524cbdb3161b590a48f7d17de3ee0ba9c52beb743\
+                 c09428a131d6b1b57303d90d8132c276d5ed3d5d01c0f53880",
+                "616263",
+                "",
+                true,
+                "822f6901f7480f3d5f562c592994d9693602875614483256505600bbc281ae38\
+                 1f54d6bce2ea911574932f52a4e6cadd78769375ec3ffd1b801a0d9b3f4030cd\
+                 433964b6457ea39476511214f97469b57dd32dbc560a9a94d00bff07620464a3\
+                 ad203df7dc7ce360c3cd3696d9d9fab90f00",
+            ),
+            (
+                "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42\
+                 ef7822e0d5104127dc05d6dbefde69e3ab2cec7c867c6e2c49",
+                "259b71c19f83ef77a7abd26524cbdb3161b590a48f7d17de3ee0ba9c52beb743\
+                 c09428a131d6b1b57303d90d8132c276d5ed3d5d01c0f53880",
+                "616263",
+                "666f6f",
+                true,
+                "c32299d46ec8ff02b54540982814dce9a05812f81962b649d528095916a2aa48\
+                 1065b1580423ef927ecf0af5888f90da0f6a9a85ad5dc3f280d91224ba9911a3\
+                 653d00e484e2ce232521481c8658df304bb7745a73514cdb9bf3e15784ab7128\
+                 4f8d0704a608c54a6b62d97beb511d132100",
+            ),
+        ] {
+            let secret = bytes::<57>(secret);
+            let message = TextEncoding::Hex.decode(message).unwrap();
+            let context = TextEncoding::Hex.decode(context).unwrap();
+            assert_eq!(
+                TextEncoding::Hex.encode(&ed448_public_key(&secret)).unwrap(),
+                public_key
+            );
+            let computed = ed448_sign(&secret, &message, &context, prehash);
+            assert_eq!(TextEncoding::Hex.encode(&computed).unwrap(), signature);
+            let public_key = ed448_public_key(&secret);
+            for strict in [false, true] {
+                assert!(ed448_verify(
+                    &public_key,
+                    &message,
+                    &context,
+                    prehash,
+                    &computed,
+                    strict
+                ));
+                assert!(!ed448_verify(
+                    &public_key,
+                    b"tampered",
+                    &context,
+                    prehash,
+                    &computed,
+                    strict
+                ));
+                // dom4 separates the pure and prehash modes
+                assert!(!ed448_verify(
+                    &public_key,
+                    &message,
+                    &context,
+                    !prehash,
+                    &computed,
+                    strict
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_x448() {
+        // RFC 7748 section 5.2
+        let scalar = bytes::<56>(
+            "3d262fddf9ec8e88495266fea19a34d28882acef045104d0d1aae121700a779c\
+             984c24f8cdd78fbff44943eba368f54b29259a4f1c600ad3",
+        );
+        let u = bytes::<56>(
+            "06fce640fa3487bfda5f6cf2d5263f8aad88334cbd07437f020f08f9814dc031\
+             ddbdc38c19c6da2583fa5429db94ada18aa7a7fb4ef8a086",
+        );
+        assert_eq!(
+            TextEncoding::Hex.encode(&x448(&scalar, &u).unwrap()).unwrap(),
+            "ce3e4ff95a60dc6697da1db1d85e6afbdf79b50a2412d7546d5f239fe14fbaad\
+             eb445fc66a01b0779d98223961111e21766282f73dd96b6f"
+        );
+
+        // an Ed448 key pair maps onto a matching X448 key pair
+        let secret = [7u8; 57];
+        assert_eq!(
+            ed448_to_x448_public(&ed448_public_key(&secret)).unwrap(),
+            x448_public_key(&ed448_to_x448_secret(&secret))
+        );
+    }
+}
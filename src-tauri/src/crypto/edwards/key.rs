@@ -1,17 +1,29 @@
 use anyhow::Context;
-use pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
-use spki::DecodePublicKey;
+use der::{asn1::OctetStringRef, Decode, Encode};
+use pkcs8::{
+    AlgorithmIdentifierRef, DecodePrivateKey, EncodePrivateKey,
+    EncodePublicKey, ObjectIdentifier, PrivateKeyInfo,
+};
+use spki::{DecodePublicKey, SubjectPublicKeyInfoRef};
 use tracing::info;
 
+use super::curve448;
 use crate::{
     codec::{
         private_bytes_to_pkcs8, private_pkcs8_to_bytes, public_bytes_to_pkcs8,
         public_pkcs8_to_bytes, PkcsDto,
     },
     enums::{EdwardsCurveName, KeyFormat, TextEncoding},
-    errors::Result,
+    errors::{Error, Result},
     utils::KeyTuple,
 };
+
+/// id-X25519 from RFC 8410.
+pub(crate) const X25519_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.101.110");
+/// id-Ed448 from RFC 8410.
+pub(crate) const ED448_OID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.101.113");
 #[tauri::command]
 pub async fn generate_edwards(
     curve_name: EdwardsCurveName,
@@ -20,6 +32,8 @@ pub async fn generate_edwards(
 ) -> Result<KeyTuple> {
     let (private_key, public_key) = match curve_name {
         EdwardsCurveName::Curve25519 => generate_curve_25519_key(format),
+        EdwardsCurveName::Curve448 => generate_curve_448_key(format),
+        EdwardsCurveName::X25519 => generate_x25519_key(format),
     }?;
 
     Ok(KeyTuple::new(
@@ -39,6 +53,8 @@ pub fn derive_edwards(
 
     let public_key = match curve_name {
         EdwardsCurveName::Curve25519 => derive_curve_25519(&input, format),
+        EdwardsCurveName::Curve448 => derive_curve_448(&input, format),
+        EdwardsCurveName::X25519 => derive_x25519(&input, format),
     }?;
 
     encoding.encode(&public_key)
@@ -68,14 +84,33 @@ pub fn transfer_edwards_key(
         .private(if let Some(key) = private_key {
             if !key.trim().is_empty() {
                 let key_bytes = from.encoding.decode(&key)?;
-                let private_bytes = private_bytes_to_pkcs8::<
-                    ed25519_dalek::SigningKey,
-                >(&key_bytes, from.format)
-                .and_then(|key| {
-                    private_pkcs8_to_bytes::<ed25519_dalek::SigningKey>(
-                        key, to.format,
+                let private_bytes = match curve_name {
+                    EdwardsCurveName::Curve25519 => private_bytes_to_pkcs8::<
+                        ed25519_dalek::SigningKey,
+                    >(
+                        &key_bytes, from.format
                     )
-                })?;
+                    .and_then(|key| {
+                        private_pkcs8_to_bytes::<ed25519_dalek::SigningKey>(
+                            key, to.format,
+                        )
+                    }),
+                    EdwardsCurveName::Curve448 => {
+                        import_curve_448_private_key(&key_bytes, from.format)
+                            .and_then(|key| {
+                                export_curve_448_private_key(&key, to.format)
+                            })
+                    }
+                    EdwardsCurveName::X25519 => {
+                        import_x25519_private_key(&key_bytes, from.format)
+                            .or_else(|_| {
+                                import_x25519_raw_private_key(&key_bytes)
+                            })
+                            .and_then(|key| {
+                                export_x25519_private_key(&key, to.format)
+                            })
+                    }
+                }?;
                 Some(to.encoding.encode(&private_bytes)?)
             } else {
                 None
@@ -86,14 +121,33 @@ pub fn transfer_edwards_key(
         .public(if let Some(key) = public_key {
             if !key.trim().is_empty() {
                 let key_bytes = from.encoding.decode(&key)?;
-                let public_bytes = public_bytes_to_pkcs8::<
-                    ed25519_dalek::VerifyingKey,
-                >(&key_bytes, from.format)
-                .and_then(|key| {
-                    public_pkcs8_to_bytes::<ed25519_dalek::VerifyingKey>(
-                        key, to.format,
+                let public_bytes = match curve_name {
+                    EdwardsCurveName::Curve25519 => public_bytes_to_pkcs8::<
+                        ed25519_dalek::VerifyingKey,
+                    >(
+                        &key_bytes, from.format
                     )
-                })?;
+                    .and_then(|key| {
+                        public_pkcs8_to_bytes::<ed25519_dalek::VerifyingKey>(
+                            key, to.format,
+                        )
+                    }),
+                    EdwardsCurveName::Curve448 => {
+                        import_curve_448_public_key(&key_bytes, from.format)
+                            .and_then(|key| {
+                                export_curve_448_public_key(&key, to.format)
+                            })
+                    }
+                    EdwardsCurveName::X25519 => {
+                        import_x25519_public_key(&key_bytes, from.format)
+                            .or_else(|_| {
+                                import_x25519_raw_public_key(&key_bytes)
+                            })
+                            .and_then(|key| {
+                                export_x25519_public_key(&key, to.format)
+                            })
+                    }
+                }?;
                 Some(to.encoding.encode(&public_bytes)?)
             } else {
                 None
@@ -158,6 +212,26 @@ pub(crate) fn import_curve_25519_public_key(
     })
 }
 
+// 32 bytes seed and compressed point as in RFC 8032
+pub(crate) fn import_curve_25519_raw_private_key(
+    input: &[u8],
+) -> Result<ed25519_dalek::SigningKey> {
+    let seed: [u8; 32] = input
+        .try_into()
+        .map_err(|_| Error::Unsupported("ed25519 key length".to_string()))?;
+    Ok(ed25519_dalek::SigningKey::from_bytes(&seed))
+}
+
+pub(crate) fn import_curve_25519_raw_public_key(
+    input: &[u8],
+) -> Result<ed25519_dalek::VerifyingKey> {
+    let key: [u8; 32] = input
+        .try_into()
+        .map_err(|_| Error::Unsupported("ed25519 key length".to_string()))?;
+    Ok(ed25519_dalek::VerifyingKey::from_bytes(&key)
+        .context("invalid ed25519 public key")?)
+}
+
 pub(crate) fn export_curve_25519_private_key(
     secret_key: &ed25519_dalek::SigningKey,
     format: KeyFormat,
@@ -192,3 +266,245 @@ pub(crate) fn export_curve_25519_public_key(
             .to_vec(),
     })
 }
+
+pub(crate) fn generate_x25519_key(
+    format: KeyFormat,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let secret =
+        x25519_dalek::StaticSecret::random_from_rng(rand::thread_rng());
+    let private_key = export_x25519_private_key(&secret, format)?;
+    let public_key = export_x25519_public_key(
+        &x25519_dalek::PublicKey::from(&secret),
+        format,
+    )?;
+    Ok((private_key, public_key))
+}
+
+pub(crate) fn derive_x25519(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    let secret = import_x25519_private_key(input, format)
+        .or_else(|_| import_x25519_raw_private_key(input))?;
+    export_x25519_public_key(&x25519_dalek::PublicKey::from(&secret), format)
+}
+
+// raw keys as used by libsodium, age and wireguard
+pub(crate) fn import_x25519_raw_private_key(
+    input: &[u8],
+) -> Result<x25519_dalek::StaticSecret> {
+    let key: [u8; 32] = input
+        .try_into()
+        .map_err(|_| Error::Unsupported("x25519 key length".to_string()))?;
+    Ok(x25519_dalek::StaticSecret::from(key))
+}
+
+pub(crate) fn import_x25519_raw_public_key(
+    input: &[u8],
+) -> Result<x25519_dalek::PublicKey> {
+    let key: [u8; 32] = input
+        .try_into()
+        .map_err(|_| Error::Unsupported("x25519 key length".to_string()))?;
+    Ok(x25519_dalek::PublicKey::from(key))
+}
+
+pub(crate) fn import_x25519_private_key(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<x25519_dalek::StaticSecret> {
+    let key: [u8; 32] =
+        import_pkcs8_private_key(input, format, X25519_OID, "x25519")?
+            .try_into()
+            .map_err(|_| Error::Unsupported("x25519 key length".to_string()))?;
+    Ok(x25519_dalek::StaticSecret::from(key))
+}
+
+pub(crate) fn import_x25519_public_key(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<x25519_dalek::PublicKey> {
+    let key: [u8; 32] =
+        import_spki_public_key(input, format, X25519_OID, "x25519")?
+            .try_into()
+            .map_err(|_| Error::Unsupported("x25519 key length".to_string()))?;
+    Ok(x25519_dalek::PublicKey::from(key))
+}
+
+pub(crate) fn export_x25519_private_key(
+    secret: &x25519_dalek::StaticSecret,
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    export_pkcs8_private_key(secret.as_bytes(), format, X25519_OID, "x25519")
+}
+
+pub(crate) fn export_x25519_public_key(
+    public_key: &x25519_dalek::PublicKey,
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    export_spki_public_key(public_key.as_bytes(), format, X25519_OID, "x25519")
+}
+
+pub(crate) fn generate_curve_448_key(
+    format: KeyFormat,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut secret = [0u8; curve448::SECRET_LENGTH];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut secret);
+    let private_key = export_curve_448_private_key(&secret, format)?;
+    let public_key = export_curve_448_public_key(
+        &curve448::ed448_public_key(&secret),
+        format,
+    )?;
+    Ok((private_key, public_key))
+}
+
+pub(crate) fn derive_curve_448(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    let secret = import_curve_448_private_key(input, format)?;
+    export_curve_448_public_key(&curve448::ed448_public_key(&secret), format)
+}
+
+pub(crate) fn import_curve_448_private_key(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<[u8; curve448::SECRET_LENGTH]> {
+    import_pkcs8_private_key(input, format, ED448_OID, "ed448")?
+        .try_into()
+        .map_err(|_| Error::Unsupported("ed448 key length".to_string()))
+}
+
+pub(crate) fn import_curve_448_public_key(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<[u8; curve448::PUBLIC_LENGTH]> {
+    import_spki_public_key(input, format, ED448_OID, "ed448")?
+        .try_into()
+        .map_err(|_| Error::Unsupported("ed448 key length".to_string()))
+}
+
+pub(crate) fn export_curve_448_private_key(
+    secret: &[u8; curve448::SECRET_LENGTH],
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    export_pkcs8_private_key(secret, format, ED448_OID, "ed448")
+}
+
+pub(crate) fn export_curve_448_public_key(
+    public_key: &[u8; curve448::PUBLIC_LENGTH],
+    format: KeyFormat,
+) -> Result<Vec<u8>> {
+    export_spki_public_key(public_key, format, ED448_OID, "ed448")
+}
+
+fn import_pkcs8_private_key(
+    input: &[u8],
+    format: KeyFormat,
+    oid: ObjectIdentifier,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let der = pem_or_der(input, format, "PRIVATE KEY")?;
+    let info = PrivateKeyInfo::from_der(&der)
+        .context(format!("informal {} pkcs8 private key", name))?;
+    if info.algorithm.oid != oid {
+        return Err(Error::Unsupported(format!(
+            "{} private key with oid {}",
+            name, info.algorithm.oid
+        )));
+    }
+    Ok(OctetStringRef::from_der(info.private_key)
+        .context(format!("informal {} private key", name))?
+        .as_bytes()
+        .to_vec())
+}
+
+fn import_spki_public_key(
+    input: &[u8],
+    format: KeyFormat,
+    oid: ObjectIdentifier,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let der = pem_or_der(input, format, "PUBLIC KEY")?;
+    let info = SubjectPublicKeyInfoRef::from_der(&der)
+        .context(format!("informal {} public key", name))?;
+    if info.algorithm.oid != oid {
+        return Err(Error::Unsupported(format!(
+            "{} public key with oid {}",
+            name, info.algorithm.oid
+        )));
+    }
+    Ok(info.subject_public_key.raw_bytes().to_vec())
+}
+
+fn export_pkcs8_private_key(
+    key: &[u8],
+    format: KeyFormat,
+    oid: ObjectIdentifier,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let key = OctetStringRef::new(key)
+        .context(format!("{} private key", name))?
+        .to_der()
+        .context(format!("{} private key", name))?;
+    let der = PrivateKeyInfo::new(
+        AlgorithmIdentifierRef {
+            oid,
+            parameters: None,
+        },
+        &key,
+    )
+    .to_der()
+    .context(format!("export {} pkcs8 private key failed", name))?;
+    der_to_format(der, format, "PRIVATE KEY")
+}
+
+fn export_spki_public_key(
+    key: &[u8],
+    format: KeyFormat,
+    oid: ObjectIdentifier,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let der = SubjectPublicKeyInfoRef {
+        algorithm: AlgorithmIdentifierRef {
+            oid,
+            parameters: None,
+        },
+        subject_public_key: der::asn1::BitStringRef::from_bytes(key)
+            .context(format!("{} public key", name))?,
+    }
+    .to_der()
+    .context(format!("export {} public key failed", name))?;
+    der_to_format(der, format, "PUBLIC KEY")
+}
+
+fn pem_or_der(input: &[u8], format: KeyFormat, label: &str) -> Result<Vec<u8>> {
+    Ok(match format {
+        KeyFormat::Pem => {
+            let (pem_label, der) =
+                pem_rfc7468::decode_vec(input).context("invalid pem")?;
+            if pem_label != label {
+                return Err(Error::Unsupported(format!(
+                    "pem label {}",
+                    pem_label
+                )));
+            }
+            der
+        }
+        KeyFormat::Der => input.to_vec(),
+    })
+}
+
+fn der_to_format(
+    der: Vec<u8>,
+    format: KeyFormat,
+    label: &str,
+) -> Result<Vec<u8>> {
+    Ok(match format {
+        KeyFormat::Pem => {
+            pem_rfc7468::encode_string(label, base64ct::LineEnding::LF, &der)
+                .context("encode pem failed")?
+                .into_bytes()
+        }
+        KeyFormat::Der => der,
+    })
+}
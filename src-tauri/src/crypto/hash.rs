@@ -0,0 +1,358 @@
+use std::fmt::Debug;
+
+use anyhow::Context;
+use blake2::{Blake2bVar, Blake2sVar};
+use digest::{
+    Digest as _, DynDigest, ExtendableOutput, Update, VariableOutput,
+};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncReadExt;
+use tracing::info;
+
+use crate::{
+    enums::{ExtendedDigest, HashAlgorithm, TextEncoding},
+    errors::{Error, Result},
+};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+// blake3 and shake outputs are allocated up front
+const MAX_XOF_LENGTH: usize = 64 * 1024;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashDto {
+    pub algorithms: Vec<HashAlgorithm>,
+    pub input: Option<String>,
+    pub input_encoding: Option<TextEncoding>,
+    // streamed in chunks instead of the input when present
+    pub path: Option<String>,
+    // blake2, blake3 and shake output bytes, ignored by fixed size digests
+    pub output_length: Option<usize>,
+    pub output_encoding: TextEncoding,
+}
+
+impl Debug for HashDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashDto")
+            .field("algorithms", &self.algorithms)
+            .field("input", &self.input.as_ref().map(String::len))
+            .field("input_encoding", &self.input_encoding)
+            .field("path", &self.path)
+            .field("output_length", &self.output_length)
+            .field("output_encoding", &self.output_encoding)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashOutput {
+    pub algorithm: HashAlgorithm,
+    pub digest: String,
+    pub insecure: bool,
+}
+
+#[tauri::command]
+pub async fn hash(data: HashDto) -> Result<Vec<HashOutput>> {
+    info!("hash: {:?}", data);
+    if data.algorithms.is_empty() {
+        return Err(Error::Unsupported(
+            "hash algorithm is required".to_string(),
+        ));
+    }
+    let mut states = data
+        .algorithms
+        .iter()
+        .map(|&algorithm| HashState::new(algorithm, data.output_length))
+        .collect::<Result<Vec<HashState>>>()?;
+    match (&data.path, &data.input, data.input_encoding) {
+        (Some(path), ..) => {
+            let mut file = tokio::fs::File::open(path)
+                .await
+                .context(format!("open {} failed", path))?;
+            let mut buffer = vec![0; CHUNK_SIZE];
+            loop {
+                let read = file
+                    .read(&mut buffer)
+                    .await
+                    .context(format!("read {} failed", path))?;
+                if read == 0 {
+                    break;
+                }
+                for state in states.iter_mut() {
+                    state.update(&buffer[.. read]);
+                }
+            }
+        }
+        (None, Some(input), Some(encoding)) => {
+            let input = encoding.decode(input)?;
+            for state in states.iter_mut() {
+                state.update(&input);
+            }
+        }
+        _ => {
+            return Err(Error::Unsupported(
+                "hash input and its encoding or a file path is required"
+                    .to_string(),
+            ))
+        }
+    }
+    data.algorithms
+        .into_iter()
+        .zip(states)
+        .map(|(algorithm, state)| {
+            Ok(HashOutput {
+                algorithm,
+                digest: data.output_encoding.encode(&state.finalize()?)?,
+                insecure: algorithm.is_insecure(),
+            })
+        })
+        .collect()
+}
+
+enum HashState {
+    Fixed(Box<dyn DynDigest + Send + Sync>),
+    Blake2b(Blake2bVar),
+    Blake2s(Blake2sVar),
+    Blake3(Box<blake3::Hasher>, usize),
+    Shake128(sha3::Shake128, usize),
+    Shake256(sha3::Shake256, usize),
+}
+
+impl HashState {
+    fn new(
+        algorithm: HashAlgorithm,
+        output_length: Option<usize>,
+    ) -> Result<Self> {
+        if output_length == Some(0) {
+            return Err(Error::Unsupported(
+                "hash output length must be positive".to_string(),
+            ));
+        }
+        let blake2 = |size: usize| {
+            Error::Unsupported(format!(
+                "{:?} output length {}, expected at most {}",
+                algorithm,
+                output_length.unwrap_or(size),
+                size
+            ))
+        };
+        let xof = |size: usize| match output_length.unwrap_or(size) {
+            length if length > MAX_XOF_LENGTH => {
+                Err(Error::Unsupported(format!(
+                    "{:?} output length {}, expected at most {}",
+                    algorithm, length, MAX_XOF_LENGTH
+                )))
+            }
+            length => Ok(length),
+        };
+        Ok(match algorithm {
+            HashAlgorithm::Digest(digest) => {
+                HashState::Fixed(digest.as_digest())
+            }
+            HashAlgorithm::Extended(extended) => match extended {
+                ExtendedDigest::Md5 => {
+                    HashState::Fixed(Box::new(md5::Md5::new()))
+                }
+                ExtendedDigest::Ripemd160 => {
+                    HashState::Fixed(Box::new(ripemd::Ripemd160::new()))
+                }
+                ExtendedDigest::Blake2b => HashState::Blake2b(
+                    Blake2bVar::new(output_length.unwrap_or(64))
+                        .map_err(|_| blake2(64))?,
+                ),
+                ExtendedDigest::Blake2s => HashState::Blake2s(
+                    Blake2sVar::new(output_length.unwrap_or(32))
+                        .map_err(|_| blake2(32))?,
+                ),
+                ExtendedDigest::Blake3 => {
+                    HashState::Blake3(Box::default(), xof(32)?)
+                }
+                ExtendedDigest::Shake128 => {
+                    HashState::Shake128(Default::default(), xof(32)?)
+                }
+                ExtendedDigest::Shake256 => {
+                    HashState::Shake256(Default::default(), xof(64)?)
+                }
+            },
+        })
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        match self {
+            HashState::Fixed(digest) => digest.update(input),
+            HashState::Blake2b(digest) => Update::update(digest, input),
+            HashState::Blake2s(digest) => Update::update(digest, input),
+            HashState::Blake3(hasher, _) => {
+                hasher.update(input);
+            }
+            HashState::Shake128(xof, _) => Update::update(xof, input),
+            HashState::Shake256(xof, _) => Update::update(xof, input),
+        }
+    }
+
+    fn finalize(self) -> Result<Vec<u8>> {
+        Ok(match self {
+            HashState::Fixed(digest) => digest.finalize().to_vec(),
+            HashState::Blake2b(digest) => {
+                let mut output = vec![0; digest.output_size()];
+                digest
+                    .finalize_variable(&mut output)
+                    .context("blake2b finalize failed")?;
+                output
+            }
+            HashState::Blake2s(digest) => {
+                let mut output = vec![0; digest.output_size()];
+                digest
+                    .finalize_variable(&mut output)
+                    .context("blake2s finalize failed")?;
+                output
+            }
+            HashState::Blake3(hasher, size) => {
+                let mut output = vec![0; size];
+                hasher.finalize_xof().fill(&mut output);
+                output
+            }
+            HashState::Shake128(xof, size) => {
+                let mut output = vec![0; size];
+                xof.finalize_xof_into(&mut output);
+                output
+            }
+            HashState::Shake256(xof, size) => {
+                let mut output = vec![0; size];
+                xof.finalize_xof_into(&mut output);
+                output
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing_test::traced_test;
+
+    use super::{hash, HashDto, CHUNK_SIZE, MAX_XOF_LENGTH};
+    use crate::enums::{Digest, ExtendedDigest, HashAlgorithm, TextEncoding};
+
+    fn dto(algorithms: Vec<HashAlgorithm>, input: &str) -> HashDto {
+        HashDto {
+            algorithms,
+            input: Some(input.to_string()),
+            input_encoding: Some(TextEncoding::Utf8),
+            path: None,
+            output_length: None,
+            output_encoding: TextEncoding::Hex,
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_hash() {
+        let extended = |digest| HashAlgorithm::Extended(digest);
+        for (algorithm, output_length, expected) in [
+            (
+                HashAlgorithm::Digest(Digest::Sha256),
+                None,
+                "ba7816bf8f01cfea414140de5dae2223\
+                 b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                extended(ExtendedDigest::Md5),
+                None,
+                "900150983cd24fb0d6963f7d28e17f72",
+            ),
+            (
+                extended(ExtendedDigest::Ripemd160),
+                None,
+                "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc",
+            ),
+            (
+                extended(ExtendedDigest::Blake2b),
+                None,
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6f\
+                 dbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925a\
+                 b92386edd4009923",
+            ),
+            (
+                extended(ExtendedDigest::Blake2b),
+                Some(32),
+                "bddd813c634239723171ef3fee98579b\
+                 94964e3bb1cb3e427262c8c068d52319",
+            ),
+            (
+                extended(ExtendedDigest::Blake2s),
+                None,
+                "508c5e8c327c14e2e1a72ba34eeb452f\
+                 37458b209ed63a294d999b4c86675982",
+            ),
+            (
+                extended(ExtendedDigest::Blake3),
+                None,
+                "6437b3ac38465133ffb63b75273a8db5\
+                 48c558465d79db03fd359c6cd5bd9d85",
+            ),
+            (
+                extended(ExtendedDigest::Shake128),
+                Some(16),
+                "5881092dd818bf5cf8a3ddb793fbcba7",
+            ),
+            (
+                extended(ExtendedDigest::Shake256),
+                None,
+                "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37\
+                 e78b5739d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4f\
+                 eb06bd8801e751e4",
+            ),
+        ] {
+            let output = hash(HashDto {
+                output_length,
+                ..dto(vec![algorithm], "abc")
+            })
+            .await
+            .unwrap();
+            assert_eq!(output[0].digest, expected, "{:?}", algorithm);
+            assert_eq!(output[0].insecure, algorithm.is_insecure());
+        }
+        assert!(hash(HashDto {
+            output_length: Some(65),
+            ..dto(vec![extended(ExtendedDigest::Blake2b)], "abc")
+        })
+        .await
+        .is_err());
+        assert!(hash(HashDto {
+            output_length: Some(MAX_XOF_LENGTH + 1),
+            ..dto(vec![extended(ExtendedDigest::Shake256)], "abc")
+        })
+        .await
+        .is_err());
+        assert!(HashAlgorithm::Digest(Digest::Sha1).is_insecure());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_hash_file() {
+        let algorithms = Digest::iter()
+            .map(HashAlgorithm::Digest)
+            .chain(ExtendedDigest::iter().map(HashAlgorithm::Extended))
+            .collect::<Vec<_>>();
+        // spans several chunks with a partial one at the end
+        let content = "kits".repeat(CHUNK_SIZE / 3);
+        let path = std::env::temp_dir()
+            .join(format!("kits-hash-{}", std::process::id()));
+        tokio::fs::write(&path, &content).await.unwrap();
+        let from_file = hash(HashDto {
+            input: None,
+            input_encoding: None,
+            path: Some(path.to_string_lossy().to_string()),
+            ..dto(algorithms.clone(), "")
+        })
+        .await;
+        tokio::fs::remove_file(&path).await.unwrap();
+        let from_text = hash(dto(algorithms, &content)).await.unwrap();
+        for (file, text) in from_file.unwrap().iter().zip(from_text) {
+            assert_eq!(file.algorithm, text.algorithm);
+            assert_eq!(file.digest, text.digest);
+        }
+    }
+}
@@ -0,0 +1,1258 @@
+use std::fmt::Debug;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes128Gcm, Aes256Gcm, Nonce,
+};
+use anyhow::Context;
+use chacha20poly1305::ChaCha20Poly1305;
+use elliptic_curve::{
+    sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+    AffinePoint, CurveArithmetic, FieldBytesSize, PublicKey, SecretKey,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::{
+    ecc::key::{import_ecc_private_key, import_ecc_public_key},
+    edwards::{curve_25519_private_key, curve_25519_public_key},
+};
+use crate::{
+    add_encryption_trait_impl,
+    crypto::{decode_optional, EncryptionDto},
+    enums::{
+        EdwardsCurveName, HpkeAead, HpkeKdf, HpkeKem, HpkeMode, KeyFormat,
+        Pkcs, TextEncoding,
+    },
+    errors::{Error, Result},
+    utils::random_bytes,
+};
+
+add_encryption_trait_impl!(HpkeDto {
+    mode: HpkeMode,
+    kem: HpkeKem,
+    kdf: HpkeKdf,
+    aead: HpkeAead,
+    pkcs: Pkcs,
+    format: KeyFormat,
+    info: Option<String>,
+    info_encoding: Option<TextEncoding>,
+    aad: Option<String>,
+    aad_encoding: Option<TextEncoding>,
+    // psk modes only
+    psk: Option<String>,
+    psk_id: Option<String>,
+    psk_encoding: Option<TextEncoding>,
+    // auth modes only, the sender private key when sealing and the sender
+    // public key when opening, encoded with key_encoding
+    sender_key: Option<String>,
+    for_encryption: bool
+});
+
+impl Debug for HpkeDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HpkeDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("mode", &self.mode)
+            .field("kem", &self.kem)
+            .field("kdf", &self.kdf)
+            .field("aead", &self.aead)
+            .field("pkcs", &self.pkcs)
+            .field("key_format", &self.format)
+            .field("info_encoding", &self.info_encoding)
+            .field("aad_encoding", &self.aad_encoding)
+            .field("for_encryption", &self.for_encryption)
+            .finish()
+    }
+}
+
+// single shot seal and open, the output is enc || ciphertext
+#[tauri::command]
+pub async fn hpke(data: HpkeDto) -> Result<String> {
+    info!("hpke: {:?}", data);
+    let key = data.get_key()?;
+    let input = data.get_input()?;
+    let info = decode_optional(&data.info, data.info_encoding, "info")?
+        .unwrap_or_default();
+    let aad = decode_optional(&data.aad, data.aad_encoding, "aad")?
+        .unwrap_or_default();
+    let psk = decode_optional(&data.psk, data.psk_encoding, "psk")?
+        .unwrap_or_default();
+    let psk_id = decode_optional(&data.psk_id, data.psk_encoding, "psk id")?
+        .unwrap_or_default();
+    let sender_key = match data.mode {
+        HpkeMode::Auth | HpkeMode::AuthPsk => Some(
+            data.sender_key
+                .as_ref()
+                .ok_or(Error::Unsupported("sender key is required".to_string()))
+                .and_then(|sender_key| data.key_encoding.decode(sender_key))?,
+        ),
+        _ => None,
+    };
+    let suite = Suite {
+        mode: data.mode,
+        kem: data.kem,
+        kdf: data.kdf,
+        aead: data.aead,
+    };
+    let output = if data.for_encryption {
+        let pk_r = suite.import_public_key(&key, data.format)?;
+        let sk_s = sender_key
+            .map(|sender_key| {
+                suite.import_private_key(&sender_key, data.pkcs, data.format)
+            })
+            .transpose()?;
+        let ikm_e = random_bytes(suite.kem.private_key_size())?;
+        let (enc, ciphertext) = suite.seal(
+            &pk_r,
+            sk_s.as_deref(),
+            &ikm_e,
+            &info,
+            &aad,
+            &psk,
+            &psk_id,
+            &input,
+        )?;
+        [enc, ciphertext].concat()
+    } else {
+        let sk_r = suite.import_private_key(&key, data.pkcs, data.format)?;
+        let pk_s = sender_key
+            .map(|sender_key| suite.import_public_key(&sender_key, data.format))
+            .transpose()?;
+        let size = suite.kem.public_key_size();
+        if input.len() < size {
+            return Err(Error::Unsupported(
+                "hpke encapsulated key is missing".to_string(),
+            ));
+        }
+        let (enc, ciphertext) = input.split_at(size);
+        suite.open(
+            enc,
+            &sk_r,
+            pk_s.as_deref(),
+            &info,
+            &aad,
+            &psk,
+            &psk_id,
+            ciphertext,
+        )?
+    };
+    data.get_output_encoding().encode(&output)
+}
+
+impl HpkeKem {
+    fn kdf(&self) -> HpkeKdf {
+        match self {
+            HpkeKem::DhkemP256 | HpkeKem::DhkemX25519 => HpkeKdf::HkdfSha256,
+            HpkeKem::DhkemP384 => HpkeKdf::HkdfSha384,
+            HpkeKem::DhkemP521 => HpkeKdf::HkdfSha512,
+        }
+    }
+
+    fn secret_size(&self) -> usize {
+        match self {
+            HpkeKem::DhkemP256 | HpkeKem::DhkemX25519 => 32,
+            HpkeKem::DhkemP384 => 48,
+            HpkeKem::DhkemP521 => 64,
+        }
+    }
+
+    fn private_key_size(&self) -> usize {
+        match self {
+            HpkeKem::DhkemP256 | HpkeKem::DhkemX25519 => 32,
+            HpkeKem::DhkemP384 => 48,
+            HpkeKem::DhkemP521 => 66,
+        }
+    }
+
+    fn public_key_size(&self) -> usize {
+        match self {
+            HpkeKem::DhkemP256 => 65,
+            HpkeKem::DhkemP384 => 97,
+            HpkeKem::DhkemP521 => 133,
+            HpkeKem::DhkemX25519 => 32,
+        }
+    }
+
+    fn suite_id(&self) -> Vec<u8> {
+        [b"KEM".as_slice(), &(*self as u16).to_be_bytes()].concat()
+    }
+}
+
+impl HpkeAead {
+    fn key_size(&self) -> usize {
+        match self {
+            HpkeAead::Aes128Gcm => 16,
+            HpkeAead::Aes256Gcm | HpkeAead::ChaCha20Poly1305 => 32,
+        }
+    }
+}
+
+const NONCE_SIZE: usize = 12;
+
+#[derive(Debug, Clone, Copy)]
+struct Suite {
+    mode: HpkeMode,
+    kem: HpkeKem,
+    kdf: HpkeKdf,
+    aead: HpkeAead,
+}
+
+impl Suite {
+    fn suite_id(&self) -> Vec<u8> {
+        [
+            b"HPKE".as_slice(),
+            &(self.kem as u16).to_be_bytes(),
+            &(self.kdf as u16).to_be_bytes(),
+            &(self.aead as u16).to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    fn import_public_key(
+        &self,
+        key: &[u8],
+        format: KeyFormat,
+    ) -> Result<Vec<u8>> {
+        Ok(match self.kem {
+            HpkeKem::DhkemP256 => {
+                let public_key =
+                    import_ecc_public_key::<p256::NistP256>(key, format)?;
+                uncompressed(&public_key)
+            }
+            HpkeKem::DhkemP384 => {
+                let public_key =
+                    import_ecc_public_key::<p384::NistP384>(key, format)?;
+                uncompressed(&public_key)
+            }
+            HpkeKem::DhkemP521 => {
+                let public_key =
+                    import_ecc_public_key::<p521::NistP521>(key, format)?;
+                uncompressed(&public_key)
+            }
+            HpkeKem::DhkemX25519 => {
+                curve_25519_public_key(EdwardsCurveName::X25519, key, format)?
+                    .as_bytes()
+                    .to_vec()
+            }
+        })
+    }
+
+    fn import_private_key(
+        &self,
+        key: &[u8],
+        pkcs: Pkcs,
+        format: KeyFormat,
+    ) -> Result<Vec<u8>> {
+        Ok(match self.kem {
+            HpkeKem::DhkemP256 => {
+                import_ecc_private_key::<p256::NistP256>(key, pkcs, format)?
+                    .to_bytes()
+                    .to_vec()
+            }
+            HpkeKem::DhkemP384 => {
+                import_ecc_private_key::<p384::NistP384>(key, pkcs, format)?
+                    .to_bytes()
+                    .to_vec()
+            }
+            HpkeKem::DhkemP521 => {
+                import_ecc_private_key::<p521::NistP521>(key, pkcs, format)?
+                    .to_bytes()
+                    .to_vec()
+            }
+            HpkeKem::DhkemX25519 => {
+                curve_25519_private_key(EdwardsCurveName::X25519, key, format)?
+                    .to_bytes()
+                    .to_vec()
+            }
+        })
+    }
+
+    fn dh(&self, sk: &[u8], pk: &[u8]) -> Result<Vec<u8>> {
+        match self.kem {
+            HpkeKem::DhkemP256 => nist_dh::<p256::NistP256>(sk, pk),
+            HpkeKem::DhkemP384 => nist_dh::<p384::NistP384>(sk, pk),
+            HpkeKem::DhkemP521 => nist_dh::<p521::NistP521>(sk, pk),
+            HpkeKem::DhkemX25519 => {
+                let shared =
+                    x25519_secret(sk)?.diffie_hellman(&x25519_public_key(pk)?);
+                if !shared.was_contributory() {
+                    return Err(Error::Unsupported(
+                        "x25519 shared secret is zero".to_string(),
+                    ));
+                }
+                Ok(shared.as_bytes().to_vec())
+            }
+        }
+    }
+
+    fn public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        match self.kem {
+            HpkeKem::DhkemP256 => nist_public_key::<p256::NistP256>(sk),
+            HpkeKem::DhkemP384 => nist_public_key::<p384::NistP384>(sk),
+            HpkeKem::DhkemP521 => nist_public_key::<p521::NistP521>(sk),
+            HpkeKem::DhkemX25519 => {
+                Ok(x25519_dalek::PublicKey::from(&x25519_secret(sk)?)
+                    .as_bytes()
+                    .to_vec())
+            }
+        }
+    }
+
+    fn derive_key_pair(&self, ikm: &[u8]) -> Result<Vec<u8>> {
+        let kdf = self.kem.kdf();
+        let suite_id = self.kem.suite_id();
+        let dkp_prk = labeled_extract(kdf, &suite_id, &[], b"dkp_prk", ikm);
+        let size = self.kem.private_key_size();
+        let bitmask = match self.kem {
+            HpkeKem::DhkemX25519 => {
+                return labeled_expand(
+                    kdf,
+                    &suite_id,
+                    &dkp_prk,
+                    b"sk",
+                    &[],
+                    size,
+                )
+            }
+            HpkeKem::DhkemP521 => 0x01,
+            _ => 0xff,
+        };
+        for counter in 0u8 ..= 255 {
+            let mut bytes = labeled_expand(
+                kdf,
+                &suite_id,
+                &dkp_prk,
+                b"candidate",
+                &[counter],
+                size,
+            )?;
+            bytes[0] &= bitmask;
+            // rejects zero and scalars out of the curve order
+            if self.public_key(&bytes).is_ok() {
+                return Ok(bytes);
+            }
+        }
+        Err(Error::Unsupported(
+            "hpke derive key pair failed".to_string(),
+        ))
+    }
+
+    fn extract_and_expand(
+        &self,
+        dh: &[u8],
+        kem_context: &[u8],
+    ) -> Result<Vec<u8>> {
+        let kdf = self.kem.kdf();
+        let suite_id = self.kem.suite_id();
+        let eae_prk = labeled_extract(kdf, &suite_id, &[], b"eae_prk", dh);
+        labeled_expand(
+            kdf,
+            &suite_id,
+            &eae_prk,
+            b"shared_secret",
+            kem_context,
+            self.kem.secret_size(),
+        )
+    }
+
+    // (shared secret, enc), authenticated when the sender key is present
+    fn encap(
+        &self,
+        pk_r: &[u8],
+        sk_s: Option<&[u8]>,
+        ikm_e: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let sk_e = self.derive_key_pair(ikm_e)?;
+        let enc = self.public_key(&sk_e)?;
+        let mut dh = self.dh(&sk_e, pk_r)?;
+        let mut kem_context = [enc.as_slice(), pk_r].concat();
+        if let Some(sk_s) = sk_s {
+            dh.extend(self.dh(sk_s, pk_r)?);
+            kem_context.extend(self.public_key(sk_s)?);
+        }
+        Ok((self.extract_and_expand(&dh, &kem_context)?, enc))
+    }
+
+    fn decap(
+        &self,
+        enc: &[u8],
+        sk_r: &[u8],
+        pk_s: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let mut dh = self.dh(sk_r, enc)?;
+        let mut kem_context = [enc, &self.public_key(sk_r)?].concat();
+        if let Some(pk_s) = pk_s {
+            dh.extend(self.dh(sk_r, pk_s)?);
+            kem_context.extend(pk_s);
+        }
+        self.extract_and_expand(&dh, &kem_context)
+    }
+
+    // (key, base nonce)
+    fn key_schedule(
+        &self,
+        shared_secret: &[u8],
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let with_psk = matches!(self.mode, HpkeMode::Psk | HpkeMode::AuthPsk);
+        if psk.is_empty() != psk_id.is_empty() {
+            return Err(Error::Unsupported(
+                "inconsistent hpke psk inputs".to_string(),
+            ));
+        }
+        if with_psk == psk.is_empty() {
+            return Err(Error::Unsupported(format!(
+                "hpke {:?} mode psk inputs",
+                self.mode
+            )));
+        }
+        // RFC 9180 section 5.1.1
+        if with_psk && psk.len() < 32 {
+            return Err(Error::Unsupported(
+                "hpke psk must be at least 32 bytes".to_string(),
+            ));
+        }
+        let suite_id = self.suite_id();
+        let psk_id_hash =
+            labeled_extract(self.kdf, &suite_id, &[], b"psk_id_hash", psk_id);
+        let info_hash =
+            labeled_extract(self.kdf, &suite_id, &[], b"info_hash", info);
+        let context =
+            [&[self.mode as u8], psk_id_hash.as_slice(), &info_hash].concat();
+        let secret =
+            labeled_extract(self.kdf, &suite_id, shared_secret, b"secret", psk);
+        let key = labeled_expand(
+            self.kdf,
+            &suite_id,
+            &secret,
+            b"key",
+            &context,
+            self.aead.key_size(),
+        )?;
+        let base_nonce = labeled_expand(
+            self.kdf,
+            &suite_id,
+            &secret,
+            b"base_nonce",
+            &context,
+            NONCE_SIZE,
+        )?;
+        Ok((key, base_nonce))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn seal(
+        &self,
+        pk_r: &[u8],
+        sk_s: Option<&[u8]>,
+        ikm_e: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let (shared_secret, enc) = self.encap(pk_r, sk_s, ikm_e)?;
+        let (key, base_nonce) =
+            self.key_schedule(&shared_secret, info, psk, psk_id)?;
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let nonce = Nonce::from_slice(&base_nonce);
+        let ciphertext = match self.aead {
+            HpkeAead::Aes128Gcm => Aes128Gcm::new_from_slice(&key)
+                .context("init hpke aead failed")?
+                .encrypt(nonce, payload),
+            HpkeAead::Aes256Gcm => Aes256Gcm::new_from_slice(&key)
+                .context("init hpke aead failed")?
+                .encrypt(nonce, payload),
+            HpkeAead::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new_from_slice(&key)
+                    .context("init hpke aead failed")?
+                    .encrypt(nonce, payload)
+            }
+        }
+        .context("hpke seal failed")?;
+        Ok((enc, ciphertext))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn open(
+        &self,
+        enc: &[u8],
+        sk_r: &[u8],
+        pk_s: Option<&[u8]>,
+        info: &[u8],
+        aad: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let shared_secret = self.decap(enc, sk_r, pk_s)?;
+        let (key, base_nonce) =
+            self.key_schedule(&shared_secret, info, psk, psk_id)?;
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+        let nonce = Nonce::from_slice(&base_nonce);
+        Ok(match self.aead {
+            HpkeAead::Aes128Gcm => Aes128Gcm::new_from_slice(&key)
+                .context("init hpke aead failed")?
+                .decrypt(nonce, payload),
+            HpkeAead::Aes256Gcm => Aes256Gcm::new_from_slice(&key)
+                .context("init hpke aead failed")?
+                .decrypt(nonce, payload),
+            HpkeAead::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new_from_slice(&key)
+                    .context("init hpke aead failed")?
+                    .decrypt(nonce, payload)
+            }
+        }
+        .context("hpke open failed")?)
+    }
+}
+
+fn labeled_extract(
+    kdf: HpkeKdf,
+    suite_id: &[u8],
+    salt: &[u8],
+    label: &[u8],
+    ikm: &[u8],
+) -> Vec<u8> {
+    let ikm = [b"HPKE-v1".as_slice(), suite_id, label, ikm].concat();
+    match kdf {
+        HpkeKdf::HkdfSha256 => {
+            hkdf::Hkdf::<sha2::Sha256>::extract(Some(salt), &ikm)
+                .0
+                .to_vec()
+        }
+        HpkeKdf::HkdfSha384 => {
+            hkdf::Hkdf::<sha2::Sha384>::extract(Some(salt), &ikm)
+                .0
+                .to_vec()
+        }
+        HpkeKdf::HkdfSha512 => {
+            hkdf::Hkdf::<sha2::Sha512>::extract(Some(salt), &ikm)
+                .0
+                .to_vec()
+        }
+    }
+}
+
+fn labeled_expand(
+    kdf: HpkeKdf,
+    suite_id: &[u8],
+    prk: &[u8],
+    label: &[u8],
+    info: &[u8],
+    size: usize,
+) -> Result<Vec<u8>> {
+    let length = u16::try_from(size).context("hpke expand length")?;
+    let info = [
+        &length.to_be_bytes(),
+        b"HPKE-v1".as_slice(),
+        suite_id,
+        label,
+        info,
+    ]
+    .concat();
+    let mut okm = vec![0; size];
+    match kdf {
+        HpkeKdf::HkdfSha256 => hkdf::Hkdf::<sha2::Sha256>::from_prk(prk)
+            .context("invalid hpke prk")?
+            .expand(&info, &mut okm),
+        HpkeKdf::HkdfSha384 => hkdf::Hkdf::<sha2::Sha384>::from_prk(prk)
+            .context("invalid hpke prk")?
+            .expand(&info, &mut okm),
+        HpkeKdf::HkdfSha512 => hkdf::Hkdf::<sha2::Sha512>::from_prk(prk)
+            .context("invalid hpke prk")?
+            .expand(&info, &mut okm),
+    }
+    .context("hpke expand failed")?;
+    Ok(okm)
+}
+
+fn uncompressed<C>(public_key: &PublicKey<C>) -> Vec<u8>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    public_key.to_encoded_point(false).as_bytes().to_vec()
+}
+
+fn nist_dh<C>(sk: &[u8], pk: &[u8]) -> Result<Vec<u8>>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let sk =
+        SecretKey::<C>::from_slice(sk).context("invalid hpke private key")?;
+    let pk = PublicKey::<C>::from_sec1_bytes(pk)
+        .context("invalid hpke public key")?;
+    let shared = elliptic_curve::ecdh::diffie_hellman(
+        sk.to_nonzero_scalar(),
+        pk.as_affine(),
+    );
+    Ok(shared.raw_secret_bytes().to_vec())
+}
+
+fn nist_public_key<C>(sk: &[u8]) -> Result<Vec<u8>>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let sk =
+        SecretKey::<C>::from_slice(sk).context("invalid hpke private key")?;
+    Ok(uncompressed(&sk.public_key()))
+}
+
+fn x25519_secret(sk: &[u8]) -> Result<x25519_dalek::StaticSecret> {
+    let sk: [u8; 32] = sk
+        .try_into()
+        .map_err(|_| Error::Unsupported("x25519 key length".to_string()))?;
+    Ok(x25519_dalek::StaticSecret::from(sk))
+}
+
+fn x25519_public_key(pk: &[u8]) -> Result<x25519_dalek::PublicKey> {
+    let pk: [u8; 32] = pk
+        .try_into()
+        .map_err(|_| Error::Unsupported("x25519 key length".to_string()))?;
+    Ok(x25519_dalek::PublicKey::from(pk))
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing_test::traced_test;
+
+    use super::{hpke, HpkeDto, Suite};
+    use crate::{
+        crypto::{ecc::key::generate_ecc, edwards::key::generate_edwards},
+        enums::{
+            EccCurveName, EdwardsCurveName, HpkeAead, HpkeKdf, HpkeKem,
+            HpkeMode, KeyFormat, Pkcs, TextEncoding,
+        },
+    };
+
+    fn hex(input: &str) -> Vec<u8> {
+        TextEncoding::Hex.decode(input).unwrap()
+    }
+
+    #[test]
+    fn test_rfc9180_vectors() {
+        // RFC 9180 appendix A.1, A.3 and A.6, sequence number 0: ikmE, pkEm,
+        // ikmR, pkRm, ikmS, pkSm, shared_secret, key, base_nonce, ct
+        let vectors = [
+            (
+                HpkeMode::Base,
+                HpkeKem::DhkemX25519,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "7268600d403fce431561aef583ee1613\
+                     527cff655c1343f29812e66706df3234",
+                    "37fda3567bdbd628e88668c3c8d7e97d\
+                     1d1253b6d4ea6d44c150f741f1bf4431",
+                    "6db9df30aa07dd42ee5e8181afdb977e\
+                     538f5e1fec8a06223f33f7013e525037",
+                    "3948cfe0ad1ddb695d780e59077195da\
+                     6c56506b027329794ab02bca80815c4d",
+                    "",
+                    "",
+                    "fe0e18c9f024ce43799ae393c7e8fe8f\
+                     ce9d218875e8227b0187c04e7d2ea1fc",
+                    "4531685d41d65f03dc48f6b8302c05b0",
+                    "56d890e5accaaf011cff4b7d",
+                    "f938558b5d72f1a23810b4be2ab4f843\
+                     31acc02fc97babc53a52ae8218a355a9\
+                     6d8770ac83d07bea87e13c512a",
+                ],
+            ),
+            (
+                HpkeMode::Psk,
+                HpkeKem::DhkemX25519,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "78628c354e46f3e169bd231be7b2ff1c\
+                     77aa302460a26dbfa15515684c00130b",
+                    "0ad0950d9fb9588e59690b74f1237ecd\
+                     f1d775cd60be2eca57af5a4b0471c91b",
+                    "d4a09d09f575fef425905d2ab396c144\
+                     9141463f698f8efdb7accfaff8995098",
+                    "9fed7e8c17387560e92cc6462a680496\
+                     57246a09bfa8ade7aefe589672016366",
+                    "",
+                    "",
+                    "727699f009ffe3c076315019c6964836\
+                     6b69171439bd7dd0807743bde76986cd",
+                    "15026dba546e3ae05836fc7de5a7bb26",
+                    "9518635eba129d5ce0914555",
+                    "e52c6fed7f758d0cf7145689f21bc1be\
+                     6ec9ea097fef4e959440012f4feb73fb\
+                     611b946199e681f4cfc34db8ea",
+                ],
+            ),
+            (
+                HpkeMode::Auth,
+                HpkeKem::DhkemX25519,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "6e6d8f200ea2fb20c30b003a8b4f433d\
+                     2f4ed4c2658d5bc8ce2fef718059c9f7",
+                    "23fb952571a14a25e3d678140cd0e5eb\
+                     47a0961bb18afcf85896e5453c312e76",
+                    "f1d4a30a4cef8d6d4e3b016e6fd3799e\
+                     a057db4f345472ed302a67ce1c20cdec",
+                    "1632d5c2f71c2b38d0a8fcc359355200\
+                     caa8b1ffdf28618080466c909cb69b2e",
+                    "94b020ce91d73fca4649006c7e7329a6\
+                     7b40c55e9e93cc907d282bbbff386f58",
+                    "8b0c70873dc5aecb7f9ee4e62406a397\
+                     b350e57012be45cf53b7105ae731790b",
+                    "2d6db4cf719dc7293fcbf3fa64690708\
+                     e44e2bebc81f84608677958c0d4448a7",
+                    "b062cb2c4dd4bca0ad7c7a12bbc341e6",
+                    "a1bc314c1942ade7051ffed0",
+                    "5fd92cc9d46dbf8943e72a07e42f363e\
+                     d5f721212cd90bcfd072bfd9f44e06b8\
+                     0fd17824947496e21b680c141b",
+                ],
+            ),
+            (
+                HpkeMode::AuthPsk,
+                HpkeKem::DhkemX25519,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "4303619085a20ebcf18edd22782952b8\
+                     a7161e1dbae6e46e143a52a96127cf84",
+                    "820818d3c23993492cc5623ab437a48a\
+                     0a7ca3e9639c140fe1e33811eb844b7c",
+                    "4b16221f3b269a88e207270b5e1de28c\
+                     b01f847841b344b8314d6a622fe5ee90",
+                    "1d11a3cd247ae48e901939659bd4d79b\
+                     6b959e1f3e7d66663fbc9412dd4e0976",
+                    "62f77dcf5df0dd7eac54eac9f654f426\
+                     d4161ec850cc65c54f8b65d2e0b4e345",
+                    "2bfb2eb18fcad1af0e4f99142a1c474a\
+                     e74e21b9425fc5c589382c69b50cc57e",
+                    "f9d0e870aba28d04709b2680cb818546\
+                     6c6a6ff1d6e9d1091d5bf5e10ce3a577",
+                    "1364ead92c47aa7becfa95203037b19a",
+                    "99d8b5c54669807e9fc70df1",
+                    "a84c64df1e11d8fd11450039d4fe64ff\
+                     0c8a99fca0bd72c2d4c3e0400bc14a40\
+                     f27e45e141a24001697737533e",
+                ],
+            ),
+            (
+                HpkeMode::Base,
+                HpkeKem::DhkemP256,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "4270e54ffd08d79d5928020af4686d8f\
+                     6b7d35dbe470265f1f5aa22816ce860e",
+                    "04a92719c6195d5085104f469a8b9814\
+                     d5838ff72b60501e2c4466e5e67b325a\
+                     c98536d7b61a1af4b78e5b7f951c0900\
+                     be863c403ce65c9bfcb9382657222d18\
+                     c4",
+                    "668b37171f1072f3cf12ea8a236a45df\
+                     23fc13b82af3609ad1e354f6ef817550",
+                    "04fe8c19ce0905191ebc298a92457925\
+                     31f26f0cece2460639e8bc39cb7f706a\
+                     826a779b4cf969b8a0e539c7f62fb3d3\
+                     0ad6aa8f80e30f1d128aafd68a2ce72e\
+                     a0",
+                    "",
+                    "",
+                    "c0d26aeab536609a572b07695d933b58\
+                     9dcf363ff9d93c93adea537aeabb8cb8",
+                    "868c066ef58aae6dc589b6cfdd18f97e",
+                    "4e0bc5018beba4bf004cca59",
+                    "5ad590bb8baa577f8619db35a3631122\
+                     6a896e7342a6d836d8b7bcd2f20b6c7f\
+                     9076ac232e3ab2523f39513434",
+                ],
+            ),
+            (
+                HpkeMode::Psk,
+                HpkeKem::DhkemP256,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "2afa611d8b1a7b321c761b483b6a0535\
+                     79afa4f767450d3ad0f84a39fda587a6",
+                    "04305d35563527bce037773d79a13dea\
+                     bed0e8e7cde61eecee403496959e89e4\
+                     d0ca701726696d1485137ccb5341b3c1\
+                     c7aaee90a4a02449725e744b1193b53b\
+                     5f",
+                    "d42ef874c1913d9568c9405407c805ba\
+                     ddaffd0898a00f1e84e154fa787b2429",
+                    "040d97419ae99f13007a93996648b267\
+                     4e5260a8ebd2b822e84899cd52d87446\
+                     ea394ca76223b76639eccdf00e1967db\
+                     10ade37db4e7db476261fcc8df97c5ff\
+                     d1",
+                    "",
+                    "",
+                    "2e783ad86a1beae03b5749e0f3f5e9bb\
+                     19cb7eb382f2fb2dd64c99f15ae0661b",
+                    "55d9eb9d26911d4c514a990fa8d57048",
+                    "b595dc6b2d7e2ed23af529b1",
+                    "90c4deb5b75318530194e4bb62f890b0\
+                     19b1397bbf9d0d6eb918890e1fb2be1a\
+                     c2603193b60a49c2126b75d0eb",
+                ],
+            ),
+            (
+                HpkeMode::Auth,
+                HpkeKem::DhkemP256,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "798d82a8d9ea19dbc7f2c6dfa54e8a67\
+                     06f7cdc119db0813dacf8440ab37c857",
+                    "042224f3ea800f7ec55c03f29fc9865f\
+                     6ee27004f818fcbdc6dc68932c1e52e1\
+                     5b79e264a98f2c535ef06745f3d30862\
+                     4414153b22c7332bc1e691cb4af4d534\
+                     54",
+                    "7bc93bde8890d1fb55220e7f3b0c107a\
+                     e7e6eda35ca4040bb6651284bf0747ee",
+                    "04423e363e1cd54ce7b7573110ac1213\
+                     99acbc9ed815fae03b72ffbd4c18b018\
+                     36835c5a09513f28fc971b7266cfde2e\
+                     96afe84bb0f266920e82c4f53b36e1a7\
+                     8d",
+                    "874baa0dcf93595a24a45a7f042e0d22\
+                     d368747daaa7e19f80a802af19204ba8",
+                    "04a817a0902bf28e036d66add5d544cc\
+                     3a0457eab150f104285df1e293b5c10e\
+                     ef8651213e43d9cd9086c80b309df22c\
+                     f37609f58c1127f7607e85f210b2804f\
+                     73",
+                    "d4aea336439aadf68f9348880aa35808\
+                     6f1480e7c167b6ef15453ba69b94b44f",
+                    "19aa8472b3fdc530392b0e54ca17c0f5",
+                    "b390052d26b67a5b8a8fcaa4",
+                    "82ffc8c44760db691a07c5627e5fc2c0\
+                     8e7a86979ee79b494a17cc3405446ac2\
+                     bdb8f265db4a099ed3289ffe19",
+                ],
+            ),
+            (
+                HpkeMode::AuthPsk,
+                HpkeKem::DhkemP256,
+                HpkeKdf::HkdfSha256,
+                HpkeAead::Aes128Gcm,
+                [
+                    "3c1fceb477ec954c8d58ef3249e4bb4c\
+                     38241b5925b95f7486e4d9f1d0d35fbb",
+                    "046a1de3fc26a3d43f4e4ba97dbe24f7\
+                     e99181136129c48fbe872d4743e2b131\
+                     357ed4f29a7b317dc22509c7b00991ae\
+                     990bf65f8b236700c82ab7c11a845114\
+                     01",
+                    "abcc2da5b3fa81d8aabd91f7f800a8cc\
+                     f60ec37b1b585a5d1d1ac77f258b6cca",
+                    "04d824d7e897897c172ac8a9e862e4bd\
+                     820133b8d090a9b188b8233a64dfbc5f\
+                     725aa0aa52c8462ab7c9188f1c4872f0\
+                     c99087a867e8a773a13df48a627058e1\
+                     b3",
+                    "6262031f040a9db853edd6f91d227259\
+                     6eabbc78a2ed2bd643f770ecd0f19b82",
+                    "049f158c750e55d8d5ad13ede66cf6e7\
+                     9801634b7acadcad72044eac2ae1d048\
+                     0069133d6488bf73863fa988c4ba8bde\
+                     1c2e948b761274802b4d8012af4f13af\
+                     9e",
+                    "d4c27698391db126f1612d9e91a767f1\
+                     0b9b19aa17e1695549203f0df7d9aebe",
+                    "4d567121d67fae1227d90e11585988fb",
+                    "67c9d05330ca21e5116ecda6",
+                    "b9f36d58d9eb101629a3e5a7b63d2ee4\
+                     af42b3644209ab37e0a272d44365407d\
+                     b8e655c72e4fa46f4ff81b9246",
+                ],
+            ),
+            (
+                HpkeMode::Base,
+                HpkeKem::DhkemP521,
+                HpkeKdf::HkdfSha512,
+                HpkeAead::Aes256Gcm,
+                [
+                    "7f06ab8215105fc46aceeb2e3dc5028b\
+                     44364f960426eb0d8e4026c2f8b5d7e7\
+                     a986688f1591abf5ab753c357a5d6f04\
+                     40414b4ed4ede71317772ac98d9239f7\
+                     0904",
+                    "040138b385ca16bb0d5fa0c0665fbbd7\
+                     e69e3ee29f63991d3e9b5fa740aab890\
+                     0aaeed46ed73a49055758425a0ce3650\
+                     7c54b29cc5b85a5cee6bae0cf1c21f27\
+                     31ece2013dc3fb7c8d21654bb161b463\
+                     962ca19e8c654ff24c94dd2898de1205\
+                     1f1ed0692237fb02b2f8d1dc1c73e9b3\
+                     66b529eb436e98a996ee522aef863dd5\
+                     739d2f29b0",
+                    "2ad954bbe39b7122529f7dde780bff62\
+                     6cd97f850d0784a432784e69d86eccaa\
+                     de43b6c10a8ffdb94bf943c6da479db1\
+                     37914ec835a7e715e36e45e29b587bab\
+                     3bf1",
+                    "0401b45498c1714e2dce167d3caf162e\
+                     45e0642afc7ed435df7902ccae0e84ba\
+                     0f7d373f646b7738bbbdca11ed91bdea\
+                     e3cdcba3301f2457be452f271fa68375\
+                     80e661012af49583a62e48d44bed350c\
+                     7118c0d8dc861c238c72a2bda17f6470\
+                     4f464b57338e7f40b60959480c0e58e6\
+                     559b190d81663ed816e523b6b6a418f6\
+                     6d2451ec64",
+                    "",
+                    "",
+                    "776ab421302f6eff7d7cb5cb1adaea0c\
+                     d50872c71c2d63c30c4f1d5e43653336\
+                     fef33b103c67e7a98add2d3b66e2fda9\
+                     5b5b2a667aa9dac7e59cc1d46d30e818",
+                    "751e346ce8f0ddb2305c8a2a85c70d5c\
+                     f559c53093656be636b9406d4d7d1b70",
+                    "55ff7a7d739c69f44b25447b",
+                    "170f8beddfe949b75ef9c387e201baf4\
+                     132fa7374593dfafa90768788b7b2b20\
+                     0aafcc6d80ea4c795a7c5b841a",
+                ],
+            ),
+            (
+                HpkeMode::Psk,
+                HpkeKem::DhkemP521,
+                HpkeKdf::HkdfSha512,
+                HpkeAead::Aes256Gcm,
+                [
+                    "f3ebfa9a69a924e672114fcd9e06fa95\
+                     59e937f7eccce4181a2b506df53dbe51\
+                     4be12f094bb28e01de19dd345b4f7ede\
+                     5ad7eaa6b9c3019592ec68eaae9a1473\
+                     2ce0",
+                    "040085eff0835cc84351f32471d32aa4\
+                     53cdc1f6418eaaecf1c2824210eb1d48\
+                     d0768b368110fab21407c324b8bb4bec\
+                     63f042cfa4d0868d19b760eb4beba1bf\
+                     f793b30036d2c614d55730bd2a40c718\
+                     f9466faf4d5f8170d22b6df98dfe0c06\
+                     7d02b349ae4a142e0c03418f0a1479ff\
+                     78a3db07ae2c2e89e5840f712c174ba2\
+                     118e90fdcb",
+                    "a2a2458705e278e574f835effecd1823\
+                     2f8a4c459e7550a09d44348ae5d3b1ea\
+                     9d95c51995e657ad6f7cae659f5e1861\
+                     26a471c017f8f5e41da9eba74d4e0473\
+                     e179",
+                    "04006917e049a2be7e1482759fb067dd\
+                     b94e9c4f7f5976f655088dec45246614\
+                     ff924ed3b385fc2986c0ecc39d14f907\
+                     bf837d7306aada59dd5889086125ecd0\
+                     38ead400603394b5d81f89ebfd556a89\
+                     8cc1d6a027e143d199d3db845cb91c52\
+                     89fb26c5ff80832935b0e8dd08d37c61\
+                     85a6f77683347e472d1edb6daa6bd765\
+                     2fea628fae",
+                    "",
+                    "",
+                    "0d52de997fdaa4797720e8b1bebd3df3\
+                     d03c4cf38cc8c1398168d36c3fc76264\
+                     28c9c254dd3f9274450909c64a5b3acb\
+                     e45e2d850a2fd69ac0605fe5c8a057a5",
+                    "f764a5a4b17e5d1ffba6e699d6556049\
+                     7ebaea6eb0b0d9010a6d979e298a39ff",
+                    "479afdf3546ddba3a9841f38",
+                    "de69e9d943a5d0b70be3359a19f317bd\
+                     9aca4a2ebb4332a39bcdfc97d5fe62f3\
+                     a77702f4822c3be531aa7843a1",
+                ],
+            ),
+            (
+                HpkeMode::Auth,
+                HpkeKem::DhkemP521,
+                HpkeKdf::HkdfSha512,
+                HpkeAead::Aes256Gcm,
+                [
+                    "fe1c589c2a05893895a537f38c7cb430\
+                     0b5a7e8fef3d6ccb8f07a498029c61e9\
+                     0262e009dc254c7f6235f9c6b2fd6aef\
+                     f0a714db131b09258c16e217b7bd2aa6\
+                     19b0",
+                    "04017de12ede7f72cb101dab36a11126\
+                     5c97b3654816dcd6183f809d4b3d111f\
+                     e759497f8aefdc5dbb40d3e6d21db15b\
+                     dc60f15f2a420761bcaeef73b891c2b1\
+                     17e9cf01e29320b799bbc86afdc5ea97\
+                     d941ea1c5bd5ebeeac7a784b3bab5247\
+                     46f3e640ec26ee1bd91255f9330d974f\
+                     845084637ee0e6fe9f505c5b87c86a4e\
+                     1a6c3096dd",
+                    "8feea0438481fc0ecd470d6adfcda334\
+                     a759c6b8650452c5a5dd9b2dd2cc9be3\
+                     3d2bb7ee64605fc07ab4664a58bb9a8d\
+                     e80defe510b6c97d2daf85b92cd4bb0a\
+                     66bf",
+                    "04007d419b8834e7513d0e7cc66424a1\
+                     36ec5e11395ab353da324e3586673ee7\
+                     3d53ab34f30a0b42a92d054d0db321b8\
+                     0f6217e655e304f72793767c4231785c\
+                     4a4a6e008f31b93b7a4f2b8cd12e5fe5\
+                     a0523dc71353c66cbdad51c86b9e0bdf\
+                     cd9a45698f2dab1809ab1b0f88f54227\
+                     232c858accc44d9a8d41775ac0263415\
+                     64a2d749f4",
+                    "2f66a68b85ef04822b054ef521838c00\
+                     c64f8b6226935593b69e13a1a2461a4f\
+                     1a74c10c836e87eed150c0db85d4e4f5\
+                     06cbb746149befac6f5c07dc48a615ef\
+                     92db",
+                    "04015cc3636632ea9a3879e43240beae\
+                     5d15a44fba819282fac26a19c989fafd\
+                     d0f330b8521dff7dc393101b018c1e65\
+                     b07be9f5fc9a28a1f450d6a541ee0d76\
+                     221133001e8f0f6a05ab79f9b9bb9ccc\
+                     e142a453d59c5abebb5674839d935a3c\
+                     a1a3fbc328539a60b3bc3c05fed22838\
+                     584a726b9c176796cad0169ba4093332\
+                     cbd2dc3a9f",
+                    "26648fa2a2deb0bfc56349a590fd4cb7\
+                     108a51797b634694fc02061e8d91b357\
+                     6ac736a68bf848fe2a58dfb1956d266e\
+                     68209a4d631e513badf8f4dcfc00f30a",
+                    "01fced239845e53f0ec616e71777883a\
+                     1f9fcab22a50f701bdeee17ad040e44d",
+                    "9752b85fe8c73eda183f9e80",
+                    "0116aeb3a1c405c61b1ce47600b7ecd1\
+                     1d89b9c08c408b7e2d1e00a4d64696d1\
+                     2e6881dc61688209a8207427f9",
+                ],
+            ),
+            (
+                HpkeMode::AuthPsk,
+                HpkeKem::DhkemP521,
+                HpkeKdf::HkdfSha512,
+                HpkeAead::Aes256Gcm,
+                [
+                    "54272797b1fbc128a6967ff1fd606e0c\
+                     67868f7762ce1421439cbc9e90ce1b28\
+                     d566e6c2acbce712e48eebf236696eb6\
+                     80849d6873e9959395b2931975d61d38\
+                     bd6c",
+                    "04000a5096a6e6e002c83517b494bfc2\
+                     e36bfb8632fae8068362852b70d0ff71\
+                     e560b15aff96741ecffb63d8ac3090c3\
+                     769679009ac59a99a1feb4713c5f090f\
+                     c0dbed01ad73c45d29d369e36744e9ed\
+                     37d12f80700c16d816485655169a5dd6\
+                     6e4ddf27f2acffe0f56f7f77ea2b473b\
+                     4bf0518b975d9527009a3d14e5a4957e\
+                     3e8a9074f8",
+                    "3db434a8bc25b27eb0c590dc64997ab1\
+                     378a99f52b2cb5a5a5b2fa540888f6c0\
+                     f09794c654f4468524e040e6b4eca2c9\
+                     dcf229f908b9d318f960cc9e9baa92c5\
+                     eee6",
+                    "0401655b5d3b7cfafaba30851d25edc4\
+                     4c6dd17d99410efbed8591303b4dbeea\
+                     8cb1045d5255f9a60384c3bbd4a3386a\
+                     e6e6fab341dc1f8db0eed5f0ab1aaac6\
+                     d7838e00dadf8a1c2c64b48f89c63372\
+                     1e88369e54104b31368f26e35d04a442\
+                     b0b428510fb23caada686add16492f33\
+                     3b0f7ba74c391d779b788df2c38d7a7f\
+                     4778009d91",
+                    "65d523d9b37e1273eb25ad0527d3a7bd\
+                     33f67208dd1666d9904c6bc04969ae58\
+                     31a8b849e7ff642581f2c3e56be84609\
+                     600d3c6bbdaded3f6989c37d2892b1e9\
+                     78d5",
+                    "040013761e97007293d57de70962876b\
+                     4926f69a52680b4714bee1d4236aa96c\
+                     19b840c57e80b14e91258f0a350e3f7b\
+                     a59f3f091633aede4c7ec4fa8918323a\
+                     a45d5901076dec8eeb22899fda9ab9e1\
+                     960003ff0535f53c02c40f2ae4cdc607\
+                     0a3870b85b4bdd0bb77f1f889e7ee51f\
+                     465a308f08c666ad3407f75dc046b2ff\
+                     5a24dbe2ed",
+                    "9e1d5f62cb38229f57f68948a0fbc126\
+                     4499910cce50ec62cb24188c5b0a9886\
+                     8f3c1cfa8c5baa97b3f24db3cdd30df6\
+                     e04eae83dc4347be8a981066c3b5b945",
+                    "1316ed34bd52374854ed0e5cb0394ca0\
+                     a79b2d8ce7f15d5104f21acdfb594286",
+                    "d9c64ec8deb8a0647fafe8ff",
+                    "942a2a92e0817cf032ce61abccf4f3a7\
+                     c5d21b794ed943227e07b7df2d6dd92c\
+                     9b8a9371949e65cca262448ab7",
+                ],
+            ),
+        ];
+        let info = hex("4f6465206f6e2061204772656369616e2055726e");
+        let psk = hex(
+            "0247fd33b913760fa1fa51e1892d9f307fbe65eb171e8132c2af18555a738b82",
+        );
+        let psk_id = hex("456e6e796e20447572696e206172616e204d6f726961");
+        let aad = b"Count-0";
+        let plaintext = b"Beauty is truth, truth beauty";
+        for (mode, kem, kdf, aead, vector) in vectors {
+            let [ikm_e, pk_e, ikm_r, pk_r, ikm_s, pk_s, ss, key, nonce, ct] =
+                vector.map(hex);
+            let suite = Suite {
+                mode,
+                kem,
+                kdf,
+                aead,
+            };
+            let (psk, psk_id) = match mode {
+                HpkeMode::Psk | HpkeMode::AuthPsk => (&psk[..], &psk_id[..]),
+                _ => (&[][..], &[][..]),
+            };
+            let sk_r = suite.derive_key_pair(&ikm_r).unwrap();
+            assert_eq!(suite.public_key(&sk_r).unwrap(), pk_r);
+            // only the auth modes carry a sender key pair
+            let sk_s = (!ikm_s.is_empty())
+                .then(|| suite.derive_key_pair(&ikm_s).unwrap());
+            if let Some(sk_s) = &sk_s {
+                assert_eq!(suite.public_key(sk_s).unwrap(), pk_s);
+            }
+            let (secret, enc) =
+                suite.encap(&pk_r, sk_s.as_deref(), &ikm_e).unwrap();
+            assert_eq!(enc, pk_e, "{:?} {:?}", kem, mode);
+            assert_eq!(secret, ss, "{:?} {:?}", kem, mode);
+            assert_eq!(
+                suite.key_schedule(&secret, &info, psk, psk_id).unwrap(),
+                (key, nonce)
+            );
+            let (_, sealed) = suite
+                .seal(
+                    &pk_r,
+                    sk_s.as_deref(),
+                    &ikm_e,
+                    &info,
+                    aad,
+                    psk,
+                    psk_id,
+                    plaintext,
+                )
+                .unwrap();
+            assert_eq!(sealed, ct);
+            let opened = suite
+                .open(
+                    &enc,
+                    &sk_r,
+                    sk_s.is_some().then_some(pk_s.as_slice()),
+                    &info,
+                    aad,
+                    psk,
+                    psk_id,
+                    &sealed,
+                )
+                .unwrap();
+            assert_eq!(opened, plaintext);
+            if !psk.is_empty() {
+                assert!(suite
+                    .key_schedule(&secret, &info, &psk[.. 16], psk_id)
+                    .is_err());
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_hpke_seal_and_open() {
+        let encoding = TextEncoding::Base64;
+        for kem in HpkeKem::iter() {
+            let generate = || async {
+                match kem {
+                    HpkeKem::DhkemX25519 => generate_edwards(
+                        EdwardsCurveName::X25519,
+                        KeyFormat::Pem,
+                        encoding,
+                    )
+                    .await
+                    .unwrap(),
+                    _ => generate_ecc(
+                        match kem {
+                            HpkeKem::DhkemP256 => EccCurveName::NistP256,
+                            HpkeKem::DhkemP384 => EccCurveName::NistP384,
+                            _ => EccCurveName::NistP521,
+                        },
+                        Pkcs::Pkcs8,
+                        KeyFormat::Pem,
+                        encoding,
+                    )
+                    .await
+                    .unwrap(),
+                }
+            };
+            let receiver = generate().await;
+            let sender = generate().await;
+            for mode in HpkeMode::iter() {
+                for kdf in HpkeKdf::iter() {
+                    for aead in HpkeAead::iter() {
+                        let with_psk =
+                            matches!(mode, HpkeMode::Psk | HpkeMode::AuthPsk);
+                        let mut dto = HpkeDto {
+                            input: "plaintext".to_string(),
+                            input_encoding: TextEncoding::Utf8,
+                            key: receiver.1.clone().unwrap(),
+                            key_encoding: encoding,
+                            output_encoding: encoding,
+                            mode,
+                            kem,
+                            kdf,
+                            aead,
+                            pkcs: Pkcs::Pkcs8,
+                            format: KeyFormat::Pem,
+                            info: Some("info".to_string()),
+                            info_encoding: Some(TextEncoding::Utf8),
+                            aad: Some("aad".to_string()),
+                            aad_encoding: Some(TextEncoding::Utf8),
+                            psk: with_psk.then(|| {
+                                "0123456789abcdef0123456789abcdef".to_string()
+                            }),
+                            psk_id: with_psk.then(|| "psk id".to_string()),
+                            psk_encoding: Some(TextEncoding::Utf8),
+                            sender_key: sender.0.clone(),
+                            for_encryption: true,
+                        };
+                        let ciphertext = hpke(dto.clone()).await.unwrap();
+                        dto.key = receiver.0.clone().unwrap();
+                        dto.sender_key = sender.1.clone();
+                        dto.input = ciphertext;
+                        dto.input_encoding = encoding;
+                        dto.output_encoding = TextEncoding::Utf8;
+                        dto.for_encryption = false;
+                        assert_eq!(
+                            hpke(dto.clone()).await.unwrap(),
+                            "plaintext"
+                        );
+                        dto.aad = Some("tampered".to_string());
+                        assert!(hpke(dto).await.is_err());
+                    }
+                }
+            }
+        }
+    }
+}
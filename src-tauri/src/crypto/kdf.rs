@@ -8,7 +8,7 @@ use digest::{
     generic_array::typenum::{IsLess, Le, NonZero, U256},
     FixedOutput, FixedOutputReset, HashMarker, OutputSizeUser,
 };
-use hkdf::hmac::Hmac;
+use hkdf::hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 
 use super::EncryptionDto;
@@ -116,6 +116,7 @@ pub(crate) fn kdf_inner_digest(
         Digest::Sha3_512 => {
             kdf_inner::<sha3::Sha3_512>(kdf, input, salt, info, key_size)
         }
+        Digest::Sm3 => kdf_inner::<sm3::Sm3>(kdf, input, salt, info, key_size),
     }
 }
 
@@ -177,9 +178,65 @@ where
                 .context("scrypt failed")?;
             okm
         }
+        Kdf::AnsiX963 => {
+            let info = info.unwrap_or_default();
+            ansi_x963_into::<D>(input, &info, &mut okm);
+            okm
+        }
     })
 }
 
+// SEC1 / ANSI X9.63: Hash(Z || counter || SharedInfo) with counter from 1
+pub(crate) fn ansi_x963_into<D: digest::Digest>(
+    input: &[u8],
+    info: &[u8],
+    okm: &mut [u8],
+) {
+    let blocks = okm.chunks_mut(<D as digest::Digest>::output_size());
+    for (counter, block) in (1u32 ..).zip(blocks) {
+        let hash = D::new()
+            .chain_update(input)
+            .chain_update(counter.to_be_bytes())
+            .chain_update(info)
+            .finalize();
+        block.copy_from_slice(&hash[.. block.len()]);
+    }
+}
+
+pub(crate) fn hmac_digest(
+    digest: Digest,
+    key: &[u8],
+    input: &[u8],
+) -> Result<Vec<u8>> {
+    match digest {
+        Digest::Sha1 => hmac_inner::<sha1::Sha1>(key, input),
+        Digest::Sha256 => hmac_inner::<sha2::Sha256>(key, input),
+        Digest::Sha384 => hmac_inner::<sha2::Sha384>(key, input),
+        Digest::Sha512 => hmac_inner::<sha2::Sha512>(key, input),
+        Digest::Sha3_256 => hmac_inner::<sha3::Sha3_256>(key, input),
+        Digest::Sha3_384 => hmac_inner::<sha3::Sha3_384>(key, input),
+        Digest::Sha3_512 => hmac_inner::<sha3::Sha3_512>(key, input),
+        Digest::Sm3 => hmac_inner::<sm3::Sm3>(key, input),
+    }
+}
+
+fn hmac_inner<D>(key: &[u8], input: &[u8]) -> Result<Vec<u8>>
+where
+    D: CoreProxy,
+    D::Core: HashMarker
+        + FixedOutputCore
+        + BufferKindUser<BufferKind = Eager>
+        + Default
+        + Clone,
+    <D::Core as BlockSizeUser>::BlockSize: IsLess<U256>,
+    Le<<D::Core as BlockSizeUser>::BlockSize, U256>: NonZero,
+{
+    let mut mac =
+        <Hmac<D> as Mac>::new_from_slice(key).context("invalid hmac key")?;
+    mac.update(input);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
@@ -188,7 +245,29 @@ mod tests {
     use tracing::info;
     use tracing_test::traced_test;
 
-    use crate::utils::random_bytes;
+    use super::kdf_inner_digest;
+    use crate::{
+        enums::{Digest, Kdf, TextEncoding},
+        utils::random_bytes,
+    };
+
+    #[test]
+    fn test_ansi_x963_kdf() {
+        let okm = kdf_inner_digest(
+            Kdf::AnsiX963,
+            Digest::Sha256,
+            b"input key material",
+            None,
+            Some(b"info".to_vec()),
+            48,
+        )
+        .unwrap();
+        assert_eq!(
+            TextEncoding::Hex.encode(&okm).unwrap(),
+            "873172bab170cf7fda235768137e3ab865f7a7b8c06662c835e82e9da5681d26\
+             e7e76347f45da8fdba6fb1ee1db1f170"
+        );
+    }
 
     #[test]
     #[traced_test]
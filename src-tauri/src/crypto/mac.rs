@@ -0,0 +1,351 @@
+use std::fmt::Debug;
+
+use aes::cipher::{consts::U16, BlockCipher, BlockEncryptMut, BlockSizeUser};
+use anyhow::Context;
+use cmac::{Cmac, Mac};
+use digest::{ExtendableOutput, Update};
+use poly1305::{universal_hash::KeyInit, Poly1305};
+use serde::{Deserialize, Serialize};
+use sha3::{CShake128, CShake128Core, CShake256, CShake256Core};
+use subtle::ConstantTimeEq;
+use tracing::info;
+
+use super::{aes::encrypt_or_decrypt_aes, kdf};
+use crate::{
+    add_encryption_trait_impl,
+    crypto::EncryptionDto,
+    enums::{
+        AesEncryptionPadding, Digest, EncryptionMode, MacAlgorithm,
+        TextEncoding,
+    },
+    errors::{Error, Result},
+};
+
+add_encryption_trait_impl!(MacDto {
+    algorithm: MacAlgorithm,
+    // hmac only
+    digest: Option<Digest>,
+    // gmac nonce
+    iv: Option<String>,
+    iv_encoding: Option<TextEncoding>,
+    // kmac customization string
+    customization: Option<String>,
+    customization_encoding: Option<TextEncoding>,
+    // kmac output bytes or gmac tag bytes
+    mac_length: Option<usize>,
+    // verification only, encoded with output_encoding
+    mac: Option<String>
+});
+
+impl Debug for MacDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MacDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("algorithm", &self.algorithm)
+            .field("digest", &self.digest)
+            .field("iv_encoding", &self.iv_encoding)
+            .field("customization", &self.customization)
+            .field("customization_encoding", &self.customization_encoding)
+            .field("mac_length", &self.mac_length)
+            .finish()
+    }
+}
+
+#[tauri::command]
+pub async fn generate_mac(data: MacDto) -> Result<String> {
+    info!("generate mac: {:?}", data);
+    let mac = mac_inner(&data)?;
+    data.get_output_encoding().encode(&mac)
+}
+
+#[tauri::command]
+pub async fn verify_mac(data: MacDto) -> Result<bool> {
+    info!("verify mac: {:?}", data);
+    let mac = data
+        .mac
+        .as_ref()
+        .ok_or(Error::Unsupported("mac is required".to_string()))?;
+    let mac = data.get_output_encoding().decode(mac)?;
+    let expected = mac_inner(&data)?;
+    Ok(expected.ct_eq(&mac).into())
+}
+
+fn mac_inner(data: &MacDto) -> Result<Vec<u8>> {
+    let input = data.get_input()?;
+    let key = data.get_key()?;
+    // an empty mac would verify anything
+    if data.mac_length == Some(0) {
+        return Err(Error::Unsupported(
+            "mac length must be positive".to_string(),
+        ));
+    }
+    match data.algorithm {
+        MacAlgorithm::Hmac => {
+            let digest = data.digest.ok_or(Error::Unsupported(
+                "hmac digest is required".to_string(),
+            ))?;
+            kdf::hmac_digest(digest, &key, &input)
+        }
+        MacAlgorithm::AesCmac => match key.len() {
+            16 => cmac::<aes::Aes128>(&key, &input),
+            24 => cmac::<aes::Aes192>(&key, &input),
+            32 => cmac::<aes::Aes256>(&key, &input),
+            size => {
+                Err(Error::Unsupported(format!("aes cmac key size {}", size)))
+            }
+        },
+        MacAlgorithm::AesGmac => {
+            let iv = match (&data.iv, data.iv_encoding) {
+                (Some(iv), Some(encoding)) => encoding.decode(iv)?,
+                _ => {
+                    return Err(Error::Unsupported(
+                        "gmac nonce is required".to_string(),
+                    ))
+                }
+            };
+            // an empty plaintext leaves only the tag
+            encrypt_or_decrypt_aes(
+                EncryptionMode::Gcm,
+                &[],
+                &key,
+                Some(iv),
+                Some(input),
+                AesEncryptionPadding::NoPadding,
+                data.mac_length,
+                true,
+            )
+        }
+        MacAlgorithm::Kmac128 | MacAlgorithm::Kmac256 => {
+            let customization =
+                match (&data.customization, data.customization_encoding) {
+                    (Some(customization), Some(encoding)) => {
+                        encoding.decode(customization)?
+                    }
+                    (Some(_), None) => {
+                        return Err(Error::Unsupported(
+                            "customization encoding is required".to_string(),
+                        ))
+                    }
+                    (None, _) => Vec::new(),
+                };
+            Ok(if data.algorithm == MacAlgorithm::Kmac128 {
+                kmac(
+                    CShake128::from_core(
+                        CShake128Core::new_with_function_name(
+                            b"KMAC",
+                            &customization,
+                        ),
+                    ),
+                    168,
+                    &key,
+                    &input,
+                    data.mac_length.unwrap_or(32),
+                )
+            } else {
+                kmac(
+                    CShake256::from_core(
+                        CShake256Core::new_with_function_name(
+                            b"KMAC",
+                            &customization,
+                        ),
+                    ),
+                    136,
+                    &key,
+                    &input,
+                    data.mac_length.unwrap_or(64),
+                )
+            })
+        }
+        MacAlgorithm::Poly1305 => {
+            let poly1305 = Poly1305::new_from_slice(&key)
+                .context("poly1305 key must be 32 bytes")?;
+            Ok(poly1305.compute_unpadded(&input).to_vec())
+        }
+    }
+}
+
+fn cmac<C>(key: &[u8], input: &[u8]) -> Result<Vec<u8>>
+where
+    C: BlockCipher
+        + BlockEncryptMut
+        + KeyInit
+        + Clone
+        + BlockSizeUser<BlockSize = U16>,
+{
+    let mut mac = <Cmac<C> as Mac>::new_from_slice(key)
+        .context("invalid aes cmac key")?;
+    Mac::update(&mut mac, input);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+// cSHAKE(bytepad(encode_string(K), rate) || X || right_encode(L)) with the
+// function name KMAC
+fn kmac<X: Update + ExtendableOutput>(
+    mut xof: X,
+    rate: usize,
+    key: &[u8],
+    input: &[u8],
+    size: usize,
+) -> Vec<u8> {
+    let mut padded = [left_encode(rate), left_encode(key.len() * 8)].concat();
+    padded.extend_from_slice(key);
+    padded.resize(padded.len().div_ceil(rate) * rate, 0);
+    let mut right_encoded = left_encode(size * 8);
+    right_encoded.rotate_left(1);
+
+    Update::update(&mut xof, &padded);
+    Update::update(&mut xof, input);
+    Update::update(&mut xof, &right_encoded);
+    let mut output = vec![0; size];
+    xof.finalize_xof_into(&mut output);
+    output
+}
+
+// the byte count followed by the big endian value without leading zeros
+fn left_encode(value: usize) -> Vec<u8> {
+    let bytes = (value as u64).to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|&&b| b == 0).count();
+    [&[(8 - skip) as u8], &bytes[skip ..]].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing_test::traced_test;
+
+    use super::{generate_mac, verify_mac, MacDto};
+    use crate::enums::{Digest, MacAlgorithm, TextEncoding};
+
+    fn dto(algorithm: MacAlgorithm, key: &str, input: &str) -> MacDto {
+        MacDto {
+            input: input.to_string(),
+            input_encoding: TextEncoding::Hex,
+            key: key.to_string(),
+            key_encoding: TextEncoding::Hex,
+            output_encoding: TextEncoding::Hex,
+            algorithm,
+            digest: None,
+            iv: None,
+            iv_encoding: None,
+            customization: None,
+            customization_encoding: None,
+            mac_length: None,
+            mac: None,
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_mac() {
+        let kmac_key =
+            "404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f";
+        let gmac_key = "000102030405060708090a0b0c0d0e0f";
+        let gmac = |key: &str| MacDto {
+            iv: Some("000102030405060708090a0b".to_string()),
+            iv_encoding: Some(TextEncoding::Hex),
+            ..dto(MacAlgorithm::AesGmac, key, "6b69747320676d6163")
+        };
+        let tagged = |algorithm| MacDto {
+            customization: Some("My Tagged Application".to_string()),
+            customization_encoding: Some(TextEncoding::Utf8),
+            ..dto(algorithm, kmac_key, "00010203")
+        };
+        for (dto, expected) in [
+            // RFC 4231 test case 2
+            (
+                MacDto {
+                    key_encoding: TextEncoding::Utf8,
+                    input_encoding: TextEncoding::Utf8,
+                    digest: Some(Digest::Sha256),
+                    ..dto(
+                        MacAlgorithm::Hmac,
+                        "Jefe",
+                        "what do ya want for nothing?",
+                    )
+                },
+                "5bdcc146bf60754e6a042426089575c7\
+                 5a003f089d2739839dec58b964ec3843",
+            ),
+            // RFC 4493 example 2
+            (
+                dto(
+                    MacAlgorithm::AesCmac,
+                    "2b7e151628aed2a6abf7158809cf4f3c",
+                    "6bc1bee22e409f96e93d7e117393172a",
+                ),
+                "070a16b46b4d4144f79bdd9dd04a287c",
+            ),
+            (gmac(gmac_key), "8c3815e7357fb0c30421236f21cfe39f"),
+            (
+                gmac(&format!("{}{}", gmac_key, "101112131415161718191a1b1c1d1e1f")),
+                "8b0220351aa3fc8ac9a2e476ae0ea8ac",
+            ),
+            // NIST SP 800-185 KMAC samples 1, 2 and 4
+            (
+                dto(MacAlgorithm::Kmac128, kmac_key, "00010203"),
+                "e5780b0d3ea6f7d3a429c5706aa43a00\
+                 fadbd7d49628839e3187243f456ee14e",
+            ),
+            (
+                tagged(MacAlgorithm::Kmac128),
+                "3b1fba963cd8b0b59e8c1a6d71888b71\
+                 43651af8ba0a7070c0979e2811324aa5",
+            ),
+            (
+                tagged(MacAlgorithm::Kmac256),
+                "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d27\
+                 3a68d2f7f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f2487\
+                 1024d9c27773a8dd",
+            ),
+            // RFC 8439 section 2.5.2
+            (
+                MacDto {
+                    input_encoding: TextEncoding::Utf8,
+                    ..dto(
+                        MacAlgorithm::Poly1305,
+                        "85d6be7857556d337f4452fe42d506a8\
+                         0103808afb0db2fd4abff6af4149f51b",
+                        "Cryptographic Forum Research Group",
+                    )
+                },
+                "a8061dc1305136c6c22b8baf0c0127a9",
+            ),
+        ] {
+            let algorithm = dto.algorithm;
+            let mac = generate_mac(dto.clone()).await.unwrap();
+            assert_eq!(mac, expected, "{:?}", algorithm);
+            assert!(verify_mac(MacDto {
+                mac: Some(mac),
+                ..dto.clone()
+            })
+            .await
+            .unwrap());
+
+            let mut tampered = TextEncoding::Hex.decode(expected).unwrap();
+            tampered[0] ^= 1;
+            assert!(!verify_mac(MacDto {
+                mac: TextEncoding::Hex.encode(&tampered).ok(),
+                ..dto
+            })
+            .await
+            .unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mac_requires_parameters() {
+        for algorithm in MacAlgorithm::iter() {
+            let dto = dto(algorithm, "00", "00");
+            assert!(verify_mac(dto.clone()).await.is_err());
+            // kmac takes keys of any length
+            if !matches!(
+                algorithm,
+                MacAlgorithm::Kmac128 | MacAlgorithm::Kmac256
+            ) {
+                assert!(generate_mac(dto).await.is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt::Debug;
+
+use ::sm2::Sm2;
+use anyhow::Context;
+use digest::Digest as _;
+use elliptic_curve::{
+    ff::PrimeField,
+    sec1::{EncodedPoint, ToEncodedPoint},
+    FieldBytes, PublicKey, Scalar, SecretKey,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::{
+    ecc::{
+        key::{import_ecc_private_key, import_ecc_public_key},
+        SM2_DEFAULT_DISTID,
+    },
+    kdf,
+};
+use crate::{
+    add_encryption_trait_impl,
+    crypto::EncryptionDto,
+    enums::{KeyFormat, Pkcs, TextEncoding},
+    errors::{Error, Result},
+};
+
+// input is the peer static public key and key is our static private key,
+// the ephemeral keys share their encodings
+add_encryption_trait_impl!(Sm2KeyExchangeDto {
+    pkcs: Pkcs,
+    format: KeyFormat,
+    ephemeral_key: String,
+    peer_ephemeral_key: String,
+    // distinguishing identifiers, default to 1234567812345678
+    distid: Option<String>,
+    peer_distid: Option<String>,
+    initiator: bool,
+    // defaults to a 128 bits sm4 key
+    key_length: Option<usize>
+});
+
+impl Debug for Sm2KeyExchangeDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sm2KeyExchangeDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("pkcs", &self.pkcs)
+            .field("key_format", &self.format)
+            .field("distid", &self.distid)
+            .field("peer_distid", &self.peer_distid)
+            .field("initiator", &self.initiator)
+            .field("key_length", &self.key_length)
+            .finish()
+    }
+}
+
+// confirmation is sent to the peer, peer_confirmation is what the peer has
+// to send back
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sm2KeyExchangeOutput {
+    pub shared_key: String,
+    pub confirmation: String,
+    pub peer_confirmation: String,
+}
+
+const SM2_A: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfc,
+];
+const SM2_B: [u8; 32] = [
+    0x28, 0xe9, 0xfa, 0x9e, 0x9d, 0x9f, 0x5e, 0x34, 0x4d, 0x5a, 0x9e, 0x4b,
+    0xcf, 0x65, 0x09, 0xa7, 0xf3, 0x97, 0x89, 0xf5, 0x15, 0xab, 0x8f, 0x92,
+    0xdd, 0xbc, 0xbd, 0x41, 0x4d, 0x94, 0x0e, 0x93,
+];
+const SM2_GX: [u8; 32] = [
+    0x32, 0xc4, 0xae, 0x2c, 0x1f, 0x19, 0x81, 0x19, 0x5f, 0x99, 0x04, 0x46,
+    0x6a, 0x39, 0xc9, 0x94, 0x8f, 0xe3, 0x0b, 0xbf, 0xf2, 0x66, 0x0b, 0xe1,
+    0x71, 0x5a, 0x45, 0x89, 0x33, 0x4c, 0x74, 0xc7,
+];
+const SM2_GY: [u8; 32] = [
+    0xbc, 0x37, 0x36, 0xa2, 0xf4, 0xf6, 0x77, 0x9c, 0x59, 0xbd, 0xce, 0xe3,
+    0x6b, 0x69, 0x21, 0x53, 0xd0, 0xa9, 0x87, 0x7c, 0xc6, 0x2a, 0x47, 0x40,
+    0x02, 0xdf, 0x32, 0xe5, 0x21, 0x39, 0xf0, 0xa0,
+];
+
+#[tauri::command]
+pub async fn sm2_key_exchange(
+    data: Sm2KeyExchangeDto,
+) -> Result<Sm2KeyExchangeOutput> {
+    info!("sm2 key exchange: {:?}", data);
+    let private_key = import_private_key(&data.get_key()?, &data)?;
+    let ephemeral_key = import_private_key(
+        &data.key_encoding.decode(&data.ephemeral_key)?,
+        &data,
+    )?;
+    let public_key = import_public_key(&data.get_input()?, data.format)?;
+    let peer_ephemeral_key = import_public_key(
+        &data.input_encoding.decode(&data.peer_ephemeral_key)?,
+        data.format,
+    )?;
+    let (shared_key, confirmation, peer_confirmation) = key_exchange_inner(
+        &private_key,
+        &ephemeral_key,
+        &public_key,
+        &peer_ephemeral_key,
+        data.distid
+            .as_deref()
+            .unwrap_or(SM2_DEFAULT_DISTID)
+            .as_bytes(),
+        data.peer_distid
+            .as_deref()
+            .unwrap_or(SM2_DEFAULT_DISTID)
+            .as_bytes(),
+        data.initiator,
+        data.key_length.unwrap_or(16),
+    )?;
+    Ok(Sm2KeyExchangeOutput {
+        shared_key: data.output_encoding.encode(&shared_key)?,
+        confirmation: data.output_encoding.encode(&confirmation)?,
+        peer_confirmation: data.output_encoding.encode(&peer_confirmation)?,
+    })
+}
+
+fn import_private_key(
+    input: &[u8],
+    data: &Sm2KeyExchangeDto,
+) -> Result<SecretKey<Sm2>> {
+    match import_ecc_private_key::<Sm2>(input, data.pkcs, data.format) {
+        Ok(secret_key) => Ok(secret_key),
+        Err(_) => Ok(SecretKey::<Sm2>::from_slice(input)
+            .context("invalid sm2 private key")?),
+    }
+}
+
+fn import_public_key(
+    input: &[u8],
+    format: KeyFormat,
+) -> Result<PublicKey<Sm2>> {
+    match import_ecc_public_key::<Sm2>(input, format) {
+        Ok(public_key) => Ok(public_key),
+        Err(_) => Ok(PublicKey::<Sm2>::from_sec1_bytes(input)
+            .context("invalid sm2 public key")?),
+    }
+}
+
+// GB/T 32918.3: U = [t](P + [x̄]R) with t = d + x̄ r, K = KDF(xU || yU || ZA
+// || ZB), SB = H(0x02 || yU || H(xU || ZA || ZB || RA || RB)) and SA the same
+// with 0x03, A being the initiator
+#[allow(clippy::too_many_arguments)]
+fn key_exchange_inner(
+    private_key: &SecretKey<Sm2>,
+    ephemeral_key: &SecretKey<Sm2>,
+    public_key: &PublicKey<Sm2>,
+    peer_ephemeral_key: &PublicKey<Sm2>,
+    distid: &[u8],
+    peer_distid: &[u8],
+    initiator: bool,
+    key_length: usize,
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let ephemeral = ephemeral_key.public_key().to_encoded_point(false);
+    let peer_ephemeral = peer_ephemeral_key.to_encoded_point(false);
+    let t = *private_key.to_nonzero_scalar()
+        + reduced_x(&ephemeral)? * *ephemeral_key.to_nonzero_scalar();
+    let u = ((public_key.to_projective()
+        + peer_ephemeral_key.to_projective() * reduced_x(&peer_ephemeral)?)
+        * t)
+        .to_affine()
+        .to_encoded_point(false);
+    let (xu, yu) = coordinates(&u).context("sm2 shared point is identity")?;
+    let z = z_digest(distid, &private_key.public_key())?;
+    let peer_z = z_digest(peer_distid, public_key)?;
+    let (za, zb, ra, rb) = if initiator {
+        (z, peer_z, ephemeral, peer_ephemeral)
+    } else {
+        (peer_z, z, peer_ephemeral, ephemeral)
+    };
+
+    let mut shared_key = vec![0; key_length];
+    kdf::ansi_x963_into::<sm3::Sm3>(
+        &[xu, yu, &za, &zb].concat(),
+        &[],
+        &mut shared_key,
+    );
+
+    let (x1, y1) = coordinates(&ra).context("invalid sm2 ephemeral key")?;
+    let (x2, y2) = coordinates(&rb).context("invalid sm2 ephemeral key")?;
+    let inner = sm3::Sm3::new()
+        .chain_update(xu)
+        .chain_update(&za)
+        .chain_update(&zb)
+        .chain_update(x1)
+        .chain_update(y1)
+        .chain_update(x2)
+        .chain_update(y2)
+        .finalize();
+    let confirm = |prefix: u8| {
+        sm3::Sm3::new()
+            .chain_update([prefix])
+            .chain_update(yu)
+            .chain_update(inner)
+            .finalize()
+            .to_vec()
+    };
+    let (sb, sa) = (confirm(0x02), confirm(0x03));
+    Ok(if initiator {
+        (shared_key, sa, sb)
+    } else {
+        (shared_key, sb, sa)
+    })
+}
+
+fn coordinates(point: &EncodedPoint<Sm2>) -> Option<(&[u8], &[u8])> {
+    Some((point.x()?.as_slice(), point.y()?.as_slice()))
+}
+
+// x̄ = 2^127 + (x & (2^127 - 1))
+fn reduced_x(point: &EncodedPoint<Sm2>) -> Result<Scalar<Sm2>> {
+    let x = point.x().context("sm2 point is identity")?;
+    let mut bytes = FieldBytes::<Sm2>::default();
+    bytes[16 ..].copy_from_slice(&x[16 ..]);
+    bytes[16] |= 0x80;
+    Ok(Option::from(Scalar::<Sm2>::from_repr(bytes))
+        .context("invalid sm2 point")?)
+}
+
+// ZA = SM3(ENTL || ID || a || b || xG || yG || xA || yA)
+fn z_digest(distid: &[u8], public_key: &PublicKey<Sm2>) -> Result<Vec<u8>> {
+    let entl = u16::try_from(distid.len() * 8).map_err(|_| {
+        Error::Unsupported("sm2 distid is too long".to_string())
+    })?;
+    let point = public_key.to_encoded_point(false);
+    let (x, y) = coordinates(&point).context("sm2 public key is identity")?;
+    Ok(sm3::Sm3::new()
+        .chain_update(entl.to_be_bytes())
+        .chain_update(distid)
+        .chain_update(SM2_A)
+        .chain_update(SM2_B)
+        .chain_update(SM2_GX)
+        .chain_update(SM2_GY)
+        .chain_update(x)
+        .chain_update(y)
+        .finalize()
+        .to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use tracing_test::traced_test;
+
+    use super::{sm2_key_exchange, Sm2KeyExchangeDto};
+    use crate::{
+        crypto::ecc::key::generate_ecc,
+        enums::{EccCurveName, KeyFormat, Pkcs, TextEncoding},
+    };
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_sm2_key_exchange() {
+        let encoding = TextEncoding::Base64;
+        let generate = || async {
+            generate_ecc(
+                EccCurveName::SM2,
+                Pkcs::Pkcs8,
+                KeyFormat::Pem,
+                encoding,
+            )
+            .await
+            .unwrap()
+        };
+        let (alice, alice_ephemeral) = (generate().await, generate().await);
+        let (bob, bob_ephemeral) = (generate().await, generate().await);
+        let dto = Sm2KeyExchangeDto {
+            input: bob.1.clone().unwrap(),
+            input_encoding: encoding,
+            key: alice.0.clone().unwrap(),
+            key_encoding: encoding,
+            output_encoding: TextEncoding::Hex,
+            pkcs: Pkcs::Pkcs8,
+            format: KeyFormat::Pem,
+            ephemeral_key: alice_ephemeral.0.clone().unwrap(),
+            peer_ephemeral_key: bob_ephemeral.1.clone().unwrap(),
+            distid: Some("alice@kits".to_string()),
+            peer_distid: Some("bob@kits".to_string()),
+            initiator: true,
+            key_length: Some(48),
+        };
+        let responder = Sm2KeyExchangeDto {
+            input: alice.1.unwrap(),
+            key: bob.0.unwrap(),
+            ephemeral_key: bob_ephemeral.0.unwrap(),
+            peer_ephemeral_key: alice_ephemeral.1.unwrap(),
+            distid: dto.peer_distid.clone(),
+            peer_distid: dto.distid.clone(),
+            initiator: false,
+            ..dto.clone()
+        };
+        let alice_output = sm2_key_exchange(dto.clone()).await.unwrap();
+        let bob_output = sm2_key_exchange(responder.clone()).await.unwrap();
+        assert_eq!(alice_output.shared_key, bob_output.shared_key);
+        assert_eq!(alice_output.shared_key.len(), 96);
+        assert_eq!(alice_output.confirmation, bob_output.peer_confirmation);
+        assert_eq!(alice_output.peer_confirmation, bob_output.confirmation);
+        assert_ne!(alice_output.confirmation, alice_output.peer_confirmation);
+
+        // both sides must agree on who initiated and on the identifiers
+        let mismatched = [
+            Sm2KeyExchangeDto {
+                initiator: true,
+                ..responder.clone()
+            },
+            Sm2KeyExchangeDto {
+                distid: None,
+                ..responder
+            },
+        ];
+        for dto in mismatched {
+            let output = sm2_key_exchange(dto).await.unwrap();
+            assert_ne!(alice_output.shared_key, output.shared_key);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sm2_key_exchange_vector() {
+        // GM/T 0003.5 key exchange example on the recommended curve with the
+        // default identifiers: dA, rA, PB, RB for the initiator and dB, rB,
+        // PA, RA for the responder
+        let initiator = Sm2KeyExchangeDto {
+            input: "046ae848c57c53c7b1b5fa99eb2286af078ba64c64591b8b566f7357\
+                    d576f16dfbee489d771621a27b36c5c7992062e9cd09a9264386f3fb\
+                    ea54dff69305621c4d"
+                .to_string(),
+            input_encoding: TextEncoding::Hex,
+            key: "81eb26e941bb5af16df116495f906952\
+                  72ae2cd63d6c4ae1678418be48230029"
+                .to_string(),
+            key_encoding: TextEncoding::Hex,
+            output_encoding: TextEncoding::Hex,
+            pkcs: Pkcs::Sec1,
+            format: KeyFormat::Der,
+            ephemeral_key: "d4de15474db74d06491c440d305e0124\
+                            00990f3e390c7e87153c12db2ea60bb3"
+                .to_string(),
+            peer_ephemeral_key: "04acc27688a6f7b706098bc91ff3ad1bff7dc2802c\
+                                 db14ccccdb0a90471f9bd7072fedac0494b2ffc4d6\
+                                 853876c79b8f301c6573ad0aa50f39fc87181e1a1b\
+                                 46fe"
+                .to_string(),
+            distid: None,
+            peer_distid: None,
+            initiator: true,
+            key_length: None,
+        };
+        let responder = Sm2KeyExchangeDto {
+            input: "04160e12897df4edb61dd812feb96748fbd3ccf4ffe26aa6f6db9540\
+                    af49c942324a7dad08bb9a459531694beb20aa489d6649975e1bfcf8\
+                    c4741b78b4b223007f"
+                .to_string(),
+            key: "785129917d45a9ea5437a59356b82338\
+                  eaadda6ceb199088f14ae10defa229b5"
+                .to_string(),
+            ephemeral_key: "7e07124814b309489125eaed10111316\
+                            4ebf0f3458c5bd88335c1f9d596243d6"
+                .to_string(),
+            peer_ephemeral_key: "0464ced1bdbc99d590049b434d0fd73428cf608a5d\
+                                 b8fe5ce07f15026940bae40e376629c7ab21e7db26\
+                                 0922499ddb118f07ce8eaae3e7720afef6a5cc0620\
+                                 70c0"
+                .to_string(),
+            initiator: false,
+            ..initiator.clone()
+        };
+        let (k, sa, sb) = (
+            "6c89347354de2484c60b4ab1fde4c6e5",
+            "18c7894b3816df16cf07b05c5ec0bef5d655d58f779cc1b400a4f3884644db88",
+            "d3a0fe15dee185ceae907a6b595cc32a266ed7b3367e9983a896dc32fa20f8eb",
+        );
+        let output = sm2_key_exchange(initiator).await.unwrap();
+        assert_eq!(
+            (
+                output.shared_key,
+                output.confirmation,
+                output.peer_confirmation
+            ),
+            (k.to_string(), sa.to_string(), sb.to_string())
+        );
+        let output = sm2_key_exchange(responder).await.unwrap();
+        assert_eq!(
+            (
+                output.shared_key,
+                output.confirmation,
+                output.peer_confirmation
+            ),
+            (k.to_string(), sb.to_string(), sa.to_string())
+        );
+    }
+}
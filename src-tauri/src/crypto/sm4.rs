@@ -0,0 +1,205 @@
+use std::fmt::Debug;
+
+use ::sm4::Sm4;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::aes::{check_mode, encrypt_or_decrypt_aes_inner};
+use crate::{
+    add_encryption_trait_impl,
+    crypto::EncryptionDto,
+    enums::{AesEncryptionPadding, EncryptionMode, TextEncoding},
+    errors::{Error, Result},
+};
+
+add_encryption_trait_impl!(Sm4EncryptionDto {
+    mode: EncryptionMode,
+    padding: AesEncryptionPadding,
+    iv: Option<String>,
+    iv_encoding: Option<TextEncoding>,
+    aad: Option<String>,
+    aad_encoding: Option<TextEncoding>,
+    for_encryption: bool
+});
+
+impl Debug for Sm4EncryptionDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sm4EncryptionDto")
+            .field("input_encoding", &self.input_encoding)
+            .field("key_encoding", &self.key_encoding)
+            .field("output_encoding", &self.output_encoding)
+            .field("mode", &self.mode)
+            .field("padding", &self.padding)
+            .field("iv", &self.iv)
+            .field("iv_encoding", &self.iv_encoding)
+            .field("aad", &self.aad)
+            .field("aad_encoding", &self.aad_encoding)
+            .field("for_encryption", &self.for_encryption)
+            .finish()
+    }
+}
+
+#[tauri::command]
+pub async fn crypto_sm4(data: Sm4EncryptionDto) -> Result<String> {
+    info!("sm4 crypto-> {:?}", data);
+    let iv = match (&data.iv, data.iv_encoding) {
+        (Some(iv), Some(encoding)) => Some(encoding.decode(iv)?),
+        _ => None,
+    };
+    let aad = match (&data.aad, data.aad_encoding) {
+        (Some(aad), Some(encoding)) => Some(encoding.decode(aad)?),
+        _ => None,
+    };
+    let output = encrypt_or_decrypt_sm4(
+        data.mode,
+        &data.get_input()?,
+        &data.get_key()?,
+        iv,
+        aad,
+        data.padding,
+        data.for_encryption,
+    )?;
+    data.get_output_encoding().encode(&output)
+}
+
+pub(crate) fn encrypt_or_decrypt_sm4(
+    mode: EncryptionMode,
+    input: &[u8],
+    key: &[u8],
+    iv: Option<Vec<u8>>,
+    aad: Option<Vec<u8>>,
+    padding: AesEncryptionPadding,
+    for_encryption: bool,
+) -> Result<Vec<u8>> {
+    if !matches!(
+        mode,
+        EncryptionMode::Ecb
+            | EncryptionMode::Cbc
+            | EncryptionMode::Ctr
+            | EncryptionMode::Gcm
+    ) {
+        return Err(Error::Unsupported(format!("sm4 {:?}", mode)));
+    }
+    if key.len() != 16 {
+        return Err(Error::Unsupported(format!("sm4 keysize {}", key.len())));
+    }
+    check_mode(mode, iv.as_deref(), padding, None)?;
+    encrypt_or_decrypt_aes_inner::<Sm4>(
+        mode,
+        input,
+        key,
+        iv,
+        aad,
+        padding,
+        None,
+        for_encryption,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crypto_sm4, Sm4EncryptionDto};
+    use crate::{
+        crypto::fixture::{hex, PLAINTEXT},
+        enums::{AesEncryptionPadding, EncryptionMode, TextEncoding},
+    };
+
+    #[tokio::test]
+    async fn test_sm4_encryption() {
+        let key = "0123456789abcdeffedcba9876543210";
+        // GB/T 32907 appendix A
+        assert_eq!(
+            crypto_sm4(Sm4EncryptionDto {
+                input: key.to_string(),
+                input_encoding: TextEncoding::Hex,
+                key: key.to_string(),
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                mode: EncryptionMode::Ecb,
+                padding: AesEncryptionPadding::NoPadding,
+                iv: None,
+                iv_encoding: None,
+                aad: None,
+                aad_encoding: None,
+                for_encryption: true,
+            })
+            .await
+            .unwrap(),
+            "681edf34d206965e86b3e94f536e4246"
+        );
+        for (mode, padding, iv, ciphertext) in [
+            (
+                EncryptionMode::Ecb,
+                AesEncryptionPadding::Pkcs7Padding,
+                None,
+                "088c41beac31615d33c94face62404a66ecf036539a35ddb288c6a82bf\
+                 e937dbe417f17d546fedb288a93c1961d14b8d",
+            ),
+            (
+                EncryptionMode::Cbc,
+                AesEncryptionPadding::Pkcs7Padding,
+                Some(hex(0 .. 16)),
+                "b6556613480f80c2a4c4beadbdc795ced203d6945466924b4faa7bf47b\
+                 fb4034f7697af0aae498a408e856ab10fe99ce",
+            ),
+            (
+                EncryptionMode::Ctr,
+                AesEncryptionPadding::NoPadding,
+                Some(hex(0 .. 16)),
+                "52f0f9414cd301ce41ad95f08edf974a0968756b2ad69171a9b17c93e4\
+                 728d6e74bf728cab558e4e66821e",
+            ),
+            (
+                EncryptionMode::Gcm,
+                AesEncryptionPadding::NoPadding,
+                Some(hex(0 .. 12)),
+                "01497db1c0c4c07c0fa0e1d9b42b0875ee96518d2a24d3cf3594c659f5\
+                 9f330f2160ed364573443563de382d2b8d292daa666e45f8d9b506aaca75",
+            ),
+        ] {
+            let dto = Sm4EncryptionDto {
+                input: PLAINTEXT.to_string(),
+                input_encoding: TextEncoding::Utf8,
+                key: key.to_string(),
+                key_encoding: TextEncoding::Hex,
+                output_encoding: TextEncoding::Hex,
+                mode,
+                padding,
+                iv,
+                iv_encoding: Some(TextEncoding::Hex),
+                aad: Some(hex(100 .. 140)),
+                aad_encoding: Some(TextEncoding::Hex),
+                for_encryption: true,
+            };
+            assert_eq!(crypto_sm4(dto.clone()).await.unwrap(), ciphertext);
+            assert_eq!(
+                crypto_sm4(Sm4EncryptionDto {
+                    input: ciphertext.to_string(),
+                    input_encoding: TextEncoding::Hex,
+                    output_encoding: TextEncoding::Utf8,
+                    for_encryption: false,
+                    ..dto.clone()
+                })
+                .await
+                .unwrap(),
+                PLAINTEXT
+            );
+        }
+        assert!(crypto_sm4(Sm4EncryptionDto {
+            input: PLAINTEXT.to_string(),
+            input_encoding: TextEncoding::Utf8,
+            key: key.to_string(),
+            key_encoding: TextEncoding::Hex,
+            output_encoding: TextEncoding::Hex,
+            mode: EncryptionMode::Xts,
+            padding: AesEncryptionPadding::NoPadding,
+            iv: None,
+            iv_encoding: None,
+            aad: None,
+            aad_encoding: None,
+            for_encryption: true,
+        })
+        .await
+        .is_err());
+    }
+}
@@ -71,6 +71,9 @@ pub enum EccCurveName {
 #[serde(rename_all = "lowercase")]
 pub enum EdwardsCurveName {
     Curve25519,
+    Curve448,
+    // key agreement only, never derived from an Ed25519 key
+    X25519,
 }
 
 #[derive(
@@ -90,6 +93,16 @@ pub enum EncryptionMode {
     Ecb,
     Cbc,
     Gcm,
+    Ctr,
+    Cfb8,
+    Cfb128,
+    Ofb,
+    Xts,
+    #[serde(rename = "GCM-SIV")]
+    GcmSiv,
+    Ccm,
+    Siv,
+    Ocb3,
 }
 
 #[derive(
@@ -144,6 +157,13 @@ pub enum KeyFormat {
     Der,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum EcdhCurveName {
+    Ecc(EccCurveName),
+    Edwards(EdwardsCurveName),
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -159,16 +179,173 @@ pub enum KeyFormat {
 pub enum EciesEncryptionAlgorithm {
     #[serde(rename = "AES-GCM")]
     AesGcm,
+    #[serde(rename = "AES-GCM-SIV")]
+    AesGcmSiv,
+    #[serde(rename = "AES-CCM")]
+    AesCcm,
+    #[serde(rename = "AES-SIV")]
+    AesSiv,
+    #[serde(rename = "AES-OCB3")]
+    AesOcb3,
+    #[serde(rename = "ChaCha20-Poly1305")]
+    ChaCha20Poly1305,
+    #[serde(rename = "SM4-GCM")]
+    Sm4Gcm,
 }
 
 impl EciesEncryptionAlgorithm {
-    pub fn as_encryption_mode(&self) -> EncryptionMode {
+    // none for the stream ciphers
+    pub fn as_encryption_mode(&self) -> Option<EncryptionMode> {
+        match self {
+            EciesEncryptionAlgorithm::AesGcm => Some(EncryptionMode::Gcm),
+            EciesEncryptionAlgorithm::AesGcmSiv => Some(EncryptionMode::GcmSiv),
+            EciesEncryptionAlgorithm::AesCcm => Some(EncryptionMode::Ccm),
+            EciesEncryptionAlgorithm::AesSiv => Some(EncryptionMode::Siv),
+            EciesEncryptionAlgorithm::AesOcb3 => Some(EncryptionMode::Ocb3),
+            EciesEncryptionAlgorithm::ChaCha20Poly1305 => None,
+            EciesEncryptionAlgorithm::Sm4Gcm => Some(EncryptionMode::Gcm),
+        }
+    }
+
+    // key sizes in bits, the first one is the default
+    pub fn key_sizes(&self) -> &'static [usize] {
         match self {
-            EciesEncryptionAlgorithm::AesGcm => EncryptionMode::Gcm,
+            // siv splits the key into the cmac and the ctr halves
+            EciesEncryptionAlgorithm::AesSiv
+            | EciesEncryptionAlgorithm::ChaCha20Poly1305 => &[256],
+            EciesEncryptionAlgorithm::Sm4Gcm => &[128],
+            _ => &[256, 128],
+        }
+    }
+
+    // nonce lengths in bytes, the first one is the default
+    pub fn nonce_lengths(&self) -> &'static [usize] {
+        match self {
+            EciesEncryptionAlgorithm::AesGcm
+            | EciesEncryptionAlgorithm::AesSiv
+            | EciesEncryptionAlgorithm::Sm4Gcm => &[12, 16],
+            _ => &[12],
         }
     }
 }
 
+#[derive(
+    Serialize,
+    Deserialize,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum EciesProfile {
+    #[default]
+    #[serde(rename = "kits-legacy")]
+    KitsLegacy,
+    #[serde(rename = "sec1")]
+    Sec1,
+    #[serde(rename = "eciesjs")]
+    Eciesjs,
+    #[serde(rename = "sm2-c1c3c2")]
+    Sm2C1C3C2,
+    #[serde(rename = "sm2-c1c2c3")]
+    Sm2C1C2C3,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Copy,
+    Clone,
+    Debug,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum HpkeMode {
+    Base = 0x00,
+    Psk = 0x01,
+    Auth = 0x02,
+    AuthPsk = 0x03,
+}
+
+// discriminants are the RFC 9180 registry identifiers
+#[derive(
+    Serialize,
+    Deserialize,
+    Copy,
+    Clone,
+    Debug,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[repr(u16)]
+pub enum HpkeKem {
+    #[serde(rename = "DHKEM(P-256, HKDF-SHA256)")]
+    DhkemP256 = 0x0010,
+    #[serde(rename = "DHKEM(P-384, HKDF-SHA384)")]
+    DhkemP384 = 0x0011,
+    #[serde(rename = "DHKEM(P-521, HKDF-SHA512)")]
+    DhkemP521 = 0x0012,
+    #[serde(rename = "DHKEM(X25519, HKDF-SHA256)")]
+    DhkemX25519 = 0x0020,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Copy,
+    Clone,
+    Debug,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[repr(u16)]
+pub enum HpkeKdf {
+    #[serde(rename = "HKDF-SHA256")]
+    HkdfSha256 = 0x0001,
+    #[serde(rename = "HKDF-SHA384")]
+    HkdfSha384 = 0x0002,
+    #[serde(rename = "HKDF-SHA512")]
+    HkdfSha512 = 0x0003,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Copy,
+    Clone,
+    Debug,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[repr(u16)]
+pub enum HpkeAead {
+    #[serde(rename = "AES-128-GCM")]
+    Aes128Gcm = 0x0001,
+    #[serde(rename = "AES-256-GCM")]
+    Aes256Gcm = 0x0002,
+    #[serde(rename = "ChaCha20Poly1305")]
+    ChaCha20Poly1305 = 0x0003,
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -188,6 +365,120 @@ pub enum RsaEncryptionPadding {
     Oaep,
 }
 
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+)]
+pub enum RsaSignaturePadding {
+    #[serde(rename = "pkcs1-v1_5")]
+    Pkcs1v15,
+    #[serde(rename = "pss")]
+    Pss,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum EddsaVariant {
+    // Ed25519, Ed448
+    Pure,
+    // Ed25519ph, Ed448ph
+    Prehash,
+    // Ed25519ctx, Ed448 with a non-empty context
+    Context,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+)]
+pub enum ChaChaVariant {
+    // RFC 8439, 12 bytes nonce
+    #[serde(rename = "ChaCha20-Poly1305")]
+    ChaCha20Poly1305,
+    // 24 bytes nonce
+    #[serde(rename = "XChaCha20-Poly1305")]
+    XChaCha20Poly1305,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+)]
+pub enum MacAlgorithm {
+    #[serde(rename = "HMAC")]
+    Hmac,
+    #[serde(rename = "AES-CMAC")]
+    AesCmac,
+    // gcm tag over the input as aad
+    #[serde(rename = "AES-GMAC")]
+    AesGmac,
+    // NIST SP 800-185
+    #[serde(rename = "KMAC128")]
+    Kmac128,
+    #[serde(rename = "KMAC256")]
+    Kmac256,
+    // one-time 32 bytes key
+    #[serde(rename = "Poly1305")]
+    Poly1305,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum EcdsaSignatureFormat {
+    // ASN.1 SEQUENCE { r, s }
+    Der,
+    // fixed width r || s
+    Raw,
+    // r || s || v, secp256k1 only
+    Recoverable,
+}
+
 #[derive(
     Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
 )]
@@ -196,6 +487,7 @@ pub enum AesEncryptionPadding {
     NoPadding,
 }
 
+// discriminants are written into ecies envelopes, never renumber them
 #[derive(
     Serialize,
     Deserialize,
@@ -203,20 +495,23 @@ pub enum AesEncryptionPadding {
     Clone,
     Copy,
     EnumIter,
+    FromRepr,
     PartialEq,
     Eq,
     PartialOrd,
     Ord,
 )]
 #[serde(rename_all = "kebab-case")]
+#[repr(u8)]
 pub enum Digest {
-    Sha1,
-    Sha256,
-    Sha384,
-    Sha512,
-    Sha3_256,
-    Sha3_384,
-    Sha3_512,
+    Sha1 = 0,
+    Sha256 = 1,
+    Sha384 = 2,
+    Sha512 = 3,
+    Sha3_256 = 4,
+    Sha3_384 = 5,
+    Sha3_512 = 6,
+    Sm3 = 7,
 }
 
 impl Digest {
@@ -229,10 +524,61 @@ impl Digest {
             Digest::Sha3_256 => Box::new(sha3::Sha3_256::new()),
             Digest::Sha3_384 => Box::new(sha3::Sha3_384::new()),
             Digest::Sha3_512 => Box::new(sha3::Sha3_512::new()),
+            Digest::Sm3 => Box::new(sm3::Sm3::new()),
         }
     }
+
+    pub fn hash(&self, input: &[u8]) -> Vec<u8> {
+        let mut digest = self.as_digest();
+        digest.update(input);
+        digest.finalize().to_vec()
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExtendedDigest {
+    Blake2b,
+    Blake2s,
+    Blake3,
+    Shake128,
+    Shake256,
+    Md5,
+    Ripemd160,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum HashAlgorithm {
+    Digest(Digest),
+    Extended(ExtendedDigest),
+}
+
+impl HashAlgorithm {
+    // kept for legacy checksums only
+    pub fn is_insecure(&self) -> bool {
+        matches!(
+            self,
+            HashAlgorithm::Digest(Digest::Sha1)
+                | HashAlgorithm::Extended(
+                    ExtendedDigest::Md5 | ExtendedDigest::Ripemd160
+                )
+        )
+    }
+}
+
+// ecies envelopes also record these, like the digest ones
 #[derive(
     Serialize,
     Deserialize,
@@ -240,15 +586,18 @@ impl Digest {
     Clone,
     Copy,
     EnumIter,
+    FromRepr,
     PartialEq,
     Eq,
     PartialOrd,
     Ord,
 )]
 #[serde(rename_all = "lowercase")]
+#[repr(u8)]
 pub enum Kdf {
-    HKdf,
-    Concatenation,
-    PbKdf2,
-    Scrypt,
+    HKdf = 0,
+    Concatenation = 1,
+    PbKdf2 = 2,
+    Scrypt = 3,
+    AnsiX963 = 4,
 }
@@ -3,6 +3,8 @@ use std::fmt::Display;
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
+pub mod claims;
+pub mod decode;
 pub mod jwe;
 pub mod jwk;
 pub mod jws;
@@ -76,6 +78,7 @@ pub enum JwkeyAlgorithm {
 
     ES256,
     ES384,
+    #[serde(rename = "ES512")]
     ES521,
     ES256K,
 
@@ -107,6 +110,65 @@ pub enum JwkeyAlgorithm {
     EcdhEsA256kw,
 }
 
+impl JwkeyAlgorithm {
+    pub fn is_signature(self) -> bool {
+        matches!(
+            self,
+            JwkeyAlgorithm::HS256
+                | JwkeyAlgorithm::HS384
+                | JwkeyAlgorithm::HS512
+                | JwkeyAlgorithm::ES256
+                | JwkeyAlgorithm::ES384
+                | JwkeyAlgorithm::ES521
+                | JwkeyAlgorithm::ES256K
+                | JwkeyAlgorithm::RS256
+                | JwkeyAlgorithm::RS384
+                | JwkeyAlgorithm::RS512
+                | JwkeyAlgorithm::PS256
+                | JwkeyAlgorithm::PS384
+                | JwkeyAlgorithm::PS512
+                | JwkeyAlgorithm::EdDSA
+        )
+    }
+
+    pub fn is_content_encryption(self) -> bool {
+        matches!(
+            self,
+            JwkeyAlgorithm::A128GCM
+                | JwkeyAlgorithm::A192GCM
+                | JwkeyAlgorithm::A256GCM
+                | JwkeyAlgorithm::A128cbcHs256
+                | JwkeyAlgorithm::A192cbcHs384
+                | JwkeyAlgorithm::A256cbcHs512
+        )
+    }
+
+    pub fn is_key_management(self) -> bool {
+        !self.is_signature() && !self.is_content_encryption()
+    }
+
+    /// Octets of the symmetric key the algorithm is keyed with, HMAC keys
+    /// match the hash output size.
+    pub fn symmetric_key_length(self) -> Option<usize> {
+        match self {
+            JwkeyAlgorithm::A128GCM
+            | JwkeyAlgorithm::A128GCMKW
+            | JwkeyAlgorithm::A128KW => Some(16),
+            JwkeyAlgorithm::A192GCM
+            | JwkeyAlgorithm::A192GCMKW
+            | JwkeyAlgorithm::A192KW => Some(24),
+            JwkeyAlgorithm::A256GCM
+            | JwkeyAlgorithm::A256GCMKW
+            | JwkeyAlgorithm::A256KW
+            | JwkeyAlgorithm::HS256
+            | JwkeyAlgorithm::A128cbcHs256 => Some(32),
+            JwkeyAlgorithm::HS384 | JwkeyAlgorithm::A192cbcHs384 => Some(48),
+            JwkeyAlgorithm::HS512 | JwkeyAlgorithm::A256cbcHs512 => Some(64),
+            _ => None,
+        }
+    }
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -160,3 +222,22 @@ pub enum JwkeyOperation {
     DeriveKey,
     DeriveBits,
 }
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum JoseSerialization {
+    Compact,
+    General,
+    Flattened,
+}
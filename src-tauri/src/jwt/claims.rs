@@ -0,0 +1,522 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tracing::info;
+
+use super::{
+    jwk::JoseKey,
+    jws::{
+        generate_jws_inner, parse_header, parse_jws, verify_signature,
+        JwsSigner,
+    },
+    JoseSerialization, JwkeyAlgorithm,
+};
+use crate::{
+    errors::{Error, Result},
+    utils::random_id,
+};
+
+/// Registered claims, times are either unix seconds, `now` or relative to
+/// now such as `+1h`, `-30m`, `+7d`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JwtClaims {
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    pub aud: Option<Vec<String>>,
+    pub exp: Option<String>,
+    pub nbf: Option<String>,
+    pub iat: Option<String>,
+    // generated by `utils::random_id` when absent
+    pub jti: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwtDto {
+    pub claims: JwtClaims,
+    // private claims, as a json object
+    pub extra: Option<String>,
+    pub key: JoseKey,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    pub key_id: Option<String>,
+    // extra protected header parameters, as a json object
+    pub header: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwtValidateDto {
+    pub jwt: String,
+    pub key: JoseKey,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    // seconds tolerated on exp, nbf and iat
+    pub leeway: Option<u64>,
+    pub audience: Option<String>,
+    pub issuer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwtCheck {
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwtValidation {
+    pub header: Value,
+    pub claims: Value,
+    pub valid: bool,
+    pub checks: Vec<JwtCheck>,
+}
+
+#[tauri::command]
+pub(crate) fn generate_jwt(data: JwtDto) -> Result<String> {
+    info!("generate jwt, algorithm: {:?}", data.algorithm);
+    let mut claims = match data.extra.as_ref() {
+        Some(extra) => parse_header(extra)?,
+        None => Map::new(),
+    };
+    claims.extend(registered_claims(&data.claims, now()?)?);
+    let mut header = match data.header.as_ref() {
+        Some(header) => parse_header(header)?,
+        None => Map::new(),
+    };
+    header
+        .entry("typ".to_string())
+        .or_insert(Value::String("JWT".to_string()));
+    let signer = JwsSigner {
+        key: data.key,
+        algorithm: data.algorithm,
+        key_id: data.key_id,
+        header: Some(
+            serde_json::to_string(&header).context("jwt header failed")?,
+        ),
+        unprotected: None,
+    };
+    generate_jws_inner(
+        &serde_json::to_vec(&claims).context("jwt claims failed")?,
+        &[signer],
+        JoseSerialization::Compact,
+    )
+}
+
+#[tauri::command]
+pub(crate) fn validate_jwt(data: JwtValidateDto) -> Result<JwtValidation> {
+    info!(
+        "validate jwt, algorithm: {:?}, leeway: {:?}",
+        data.algorithm, data.leeway
+    );
+    let (payload, signatures) = parse_jws(&data.jwt)?;
+    let signature = signatures
+        .first()
+        .ok_or(Error::Unsupported("jwt without signature".to_string()))?;
+    let claims = match serde_json::from_slice::<Value>(&payload)
+        .context("jwt claims is not json")?
+    {
+        Value::Object(claims) => claims,
+        _ => {
+            return Err(Error::Unsupported(
+                "jwt claims is not an object".to_string(),
+            ))
+        }
+    };
+
+    let now = now()?;
+    let leeway =
+        i64::try_from(data.leeway.unwrap_or_default()).unwrap_or(i64::MAX);
+    let mut checks = Vec::new();
+    let verified = verify_signature(signature, &data.key, data.algorithm)?;
+    checks.push(JwtCheck {
+        name: "signature".to_string(),
+        passed: verified,
+        message: if verified {
+            format!("verified with {}", signature.header["alg"])
+        } else {
+            format!("not verified with {}", signature.header["alg"])
+        },
+    });
+    if let Some(exp) = claims.get("exp") {
+        checks.push(match numeric_date(exp) {
+            Some(exp) => JwtCheck {
+                name: "exp".to_string(),
+                passed: now < exp.saturating_add(leeway),
+                message: if now < exp.saturating_add(leeway) {
+                    format!("expires in {}s", exp.saturating_sub(now))
+                } else {
+                    format!("expired {}s ago", now.saturating_sub(exp))
+                },
+            },
+            None => invalid_time("exp", exp),
+        });
+    }
+    if let Some(nbf) = claims.get("nbf") {
+        checks.push(match numeric_date(nbf) {
+            Some(nbf) => JwtCheck {
+                name: "nbf".to_string(),
+                passed: now.saturating_add(leeway) >= nbf,
+                message: if now.saturating_add(leeway) >= nbf {
+                    format!("valid since {}s", now.saturating_sub(nbf))
+                } else {
+                    format!(
+                        "not valid for another {}s",
+                        nbf.saturating_sub(now)
+                    )
+                },
+            },
+            None => invalid_time("nbf", nbf),
+        });
+    }
+    if let Some(iat) = claims.get("iat") {
+        checks.push(match numeric_date(iat) {
+            Some(iat) => JwtCheck {
+                name: "iat".to_string(),
+                passed: iat <= now.saturating_add(leeway),
+                message: if iat <= now.saturating_add(leeway) {
+                    format!("issued {}s ago", now.saturating_sub(iat))
+                } else {
+                    format!("issued {}s in the future", iat.saturating_sub(now))
+                },
+            },
+            None => invalid_time("iat", iat),
+        });
+    }
+    if let Some(audience) = data.audience.as_ref() {
+        let passed = match claims.get("aud") {
+            Some(Value::String(aud)) => aud == audience,
+            Some(Value::Array(aud)) => {
+                aud.iter().any(|aud| aud.as_str() == Some(audience))
+            }
+            _ => false,
+        };
+        checks.push(JwtCheck {
+            name: "aud".to_string(),
+            passed,
+            message: format!(
+                "expected {}, found {}",
+                audience,
+                claims.get("aud").unwrap_or(&Value::Null)
+            ),
+        });
+    }
+    if let Some(issuer) = data.issuer.as_ref() {
+        let passed = claims.get("iss").and_then(|iss| iss.as_str())
+            == Some(issuer.as_str());
+        checks.push(JwtCheck {
+            name: "iss".to_string(),
+            passed,
+            message: format!(
+                "expected {}, found {}",
+                issuer,
+                claims.get("iss").unwrap_or(&Value::Null)
+            ),
+        });
+    }
+
+    Ok(JwtValidation {
+        header: signature.header.clone(),
+        claims: Value::Object(claims),
+        valid: checks.iter().all(|check| check.passed),
+        checks,
+    })
+}
+
+pub(crate) fn registered_claims(
+    claims: &JwtClaims,
+    now: i64,
+) -> Result<Map<String, Value>> {
+    let mut registered = Map::new();
+    if let Some(iss) = claims.iss.as_ref() {
+        registered.insert("iss".to_string(), json!(iss));
+    }
+    if let Some(sub) = claims.sub.as_ref() {
+        registered.insert("sub".to_string(), json!(sub));
+    }
+    match claims.aud.as_deref() {
+        Some([aud]) => {
+            registered.insert("aud".to_string(), json!(aud));
+        }
+        Some(aud) if !aud.is_empty() => {
+            registered.insert("aud".to_string(), json!(aud));
+        }
+        _ => {}
+    }
+    for (name, time) in [
+        ("exp", claims.exp.as_ref()),
+        ("nbf", claims.nbf.as_ref()),
+        ("iat", claims.iat.as_ref()),
+    ] {
+        if let Some(time) = time {
+            registered.insert(name.to_string(), json!(parse_time(time, now)?));
+        }
+    }
+    let jti = match claims.jti.as_ref() {
+        Some(jti) => jti.clone(),
+        None => random_id()?,
+    };
+    registered.insert("jti".to_string(), json!(jti));
+    Ok(registered)
+}
+
+/// Parses a unix timestamp, `now` or a signed offset from now with an
+/// optional `s`, `m`, `h`, `d` or `w` unit.
+pub(crate) fn parse_time(input: &str, now: i64) -> Result<i64> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("now") {
+        return Ok(now);
+    }
+    let (sign, offset) = if let Some(offset) = input.strip_prefix('+') {
+        (1, offset)
+    } else if let Some(offset) = input.strip_prefix('-') {
+        (-1, offset)
+    } else {
+        return Ok(input
+            .parse::<i64>()
+            .context(format!("invalid time {}", input))?);
+    };
+    let (value, unit) = match offset.char_indices().last() {
+        Some((index, unit)) if unit.is_ascii_alphabetic() => {
+            (&offset[.. index], unit)
+        }
+        _ => (offset, 's'),
+    };
+    let value = value
+        .parse::<i64>()
+        .context(format!("invalid time {}", input))?;
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return Err(Error::Unsupported(format!("time unit {}", unit))),
+    };
+    value
+        .checked_mul(sign * seconds)
+        .and_then(|offset| now.checked_add(offset))
+        .ok_or(Error::Unsupported(format!(
+            "time {} is out of range",
+            input
+        )))
+}
+
+pub(crate) fn now() -> Result<i64> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("system time before unix epoch")?
+        .as_secs() as i64)
+}
+
+// RFC 7519 section 2, a NumericDate may carry fractional seconds
+pub(crate) fn numeric_date(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| {
+        value
+            .as_f64()
+            .filter(|time| time.is_finite())
+            .map(|time| time.floor() as i64)
+    })
+}
+
+fn invalid_time(name: &str, value: &Value) -> JwtCheck {
+    JwtCheck {
+        name: name.to_string(),
+        passed: false,
+        message: format!("{} is not a numeric date", value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use tracing_test::traced_test;
+
+    use crate::{
+        enums::TextEncoding,
+        jwt::{
+            claims::{
+                generate_jwt, now, numeric_date, parse_time, validate_jwt,
+                JwtClaims, JwtDto, JwtValidateDto,
+            },
+            jwk::{generate_jwk, JoseKey, JwkGenerate},
+            JwkeyAlgorithm, JwkeyType,
+        },
+    };
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("now", 1000).unwrap(), 1000);
+        assert_eq!(parse_time("1700000000", 1000).unwrap(), 1700000000);
+        assert_eq!(parse_time("+1h", 1000).unwrap(), 4600);
+        assert_eq!(parse_time("-30m", 1000).unwrap(), -800);
+        assert_eq!(parse_time("+90", 1000).unwrap(), 1090);
+        assert_eq!(parse_time("+1w", 0).unwrap(), 604800);
+        assert!(parse_time("+1y", 0).is_err());
+        assert!(parse_time("tomorrow", 0).is_err());
+        assert!(parse_time("+9999999999999999w", 0).is_err());
+        assert!(parse_time("-1s", i64::MIN).is_err());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_generate_and_validate_jwt() {
+        let key = JoseKey::Jwk {
+            key: generate_jwk(JwkGenerate {
+                key_id: Some("kits".to_string()),
+                key_type: JwkeyType::EcDSA,
+                algorithm: Some(JwkeyAlgorithm::ES256),
+                usage: None,
+                operations: None,
+                bits: None,
+                curve_name: None,
+                thumbprint: None,
+            })
+            .await
+            .unwrap(),
+        };
+        let claims = JwtClaims {
+            iss: Some("kits".to_string()),
+            sub: Some("subject".to_string()),
+            aud: Some(vec!["app".to_string(), "api".to_string()]),
+            exp: Some("+1h".to_string()),
+            nbf: Some("now".to_string()),
+            iat: Some("now".to_string()),
+            jti: None,
+        };
+        let jwt = generate_jwt(JwtDto {
+            claims: claims.clone(),
+            extra: Some(r#"{"scope":"openid"}"#.to_string()),
+            key: key.clone(),
+            algorithm: None,
+            key_id: None,
+            header: None,
+        })
+        .unwrap();
+        let validation = validate_jwt(JwtValidateDto {
+            jwt: jwt.clone(),
+            key: key.clone(),
+            algorithm: Some(JwkeyAlgorithm::ES256),
+            leeway: Some(60),
+            audience: Some("api".to_string()),
+            issuer: Some("kits".to_string()),
+        })
+        .unwrap();
+        assert!(validation.valid);
+        assert_eq!(validation.checks.len(), 6);
+        assert_eq!(validation.header["typ"], "JWT");
+        assert_eq!(validation.header["kid"], "kits");
+        assert_eq!(validation.claims["scope"], "openid");
+        assert!(validation.claims["jti"].is_string());
+        assert_eq!(
+            validation.claims["exp"].as_i64().unwrap()
+                - validation.claims["iat"].as_i64().unwrap(),
+            3600
+        );
+
+        let validation = validate_jwt(JwtValidateDto {
+            jwt,
+            key: key.clone(),
+            algorithm: None,
+            leeway: None,
+            audience: Some("web".to_string()),
+            issuer: Some("other".to_string()),
+        })
+        .unwrap();
+        assert!(!validation.valid);
+        for check in validation.checks {
+            assert_eq!(
+                check.passed,
+                !["aud", "iss"].contains(&check.name.as_str())
+            );
+        }
+
+        let expired = generate_jwt(JwtDto {
+            claims: JwtClaims {
+                exp: Some((now().unwrap() - 30).to_string()),
+                ..Default::default()
+            },
+            extra: None,
+            key: JoseKey::Secret {
+                key: "secret".to_string(),
+                encoding: TextEncoding::Utf8,
+            },
+            algorithm: Some(JwkeyAlgorithm::HS256),
+            key_id: None,
+            header: None,
+        })
+        .unwrap();
+        for (leeway, valid) in [(None, false), (Some(60), true)] {
+            let validation = validate_jwt(JwtValidateDto {
+                jwt: expired.clone(),
+                key: JoseKey::Secret {
+                    key: "secret".to_string(),
+                    encoding: TextEncoding::Utf8,
+                },
+                algorithm: None,
+                leeway,
+                audience: None,
+                issuer: None,
+            })
+            .unwrap();
+            assert_eq!(validation.valid, valid);
+        }
+
+        // an hs256 jwt checked with an ecdsa key is invalid, not an error
+        let validation = validate_jwt(JwtValidateDto {
+            jwt: expired,
+            key,
+            algorithm: None,
+            leeway: Some(60),
+            audience: None,
+            issuer: None,
+        })
+        .unwrap();
+        assert!(!validation.valid);
+        assert!(!validation.checks[0].passed);
+        assert_eq!(validation.checks[0].name, "signature");
+    }
+
+    #[test]
+    fn test_validate_numeric_dates() {
+        assert_eq!(numeric_date(&json!(1700000000)), Some(1700000000));
+        assert_eq!(numeric_date(&json!(1700000000.5)), Some(1700000000));
+        assert_eq!(numeric_date(&json!(-0.5)), Some(-1));
+        assert_eq!(numeric_date(&json!("1700000000")), None);
+
+        let secret = JoseKey::Secret {
+            key: "secret".to_string(),
+            encoding: TextEncoding::Utf8,
+        };
+        let now = now().unwrap();
+        // exp must be strictly after now, RFC 7519 section 4.1.4
+        for (extra, valid) in [
+            (json!({ "exp": now }), false),
+            (json!({ "exp": now as f64 + 0.5 }), false),
+            (json!({ "exp": now as f64 + 3600.5 }), true),
+            (json!({ "nbf": now as f64 - 0.5, "iat": now as f64 }), true),
+            (json!({ "nbf": now as f64 + 3600.5 }), false),
+            (json!({ "exp": "tomorrow" }), false),
+        ] {
+            let jwt = generate_jwt(JwtDto {
+                claims: JwtClaims::default(),
+                extra: Some(extra.to_string()),
+                key: secret.clone(),
+                algorithm: Some(JwkeyAlgorithm::HS256),
+                key_id: None,
+                header: None,
+            })
+            .unwrap();
+            let validation = validate_jwt(JwtValidateDto {
+                jwt,
+                key: secret.clone(),
+                algorithm: None,
+                leeway: None,
+                audience: None,
+                issuer: None,
+            })
+            .unwrap();
+            assert_eq!(validation.valid, valid, "{}", extra);
+        }
+    }
+}
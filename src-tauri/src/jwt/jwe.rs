@@ -1,6 +1,1084 @@
-use crate::errors::Result;
+use anyhow::Context;
+use elliptic_curve::{
+    sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+    AffinePoint, CurveArithmetic, FieldBytesSize,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use subtle::ConstantTimeEq;
+use tracing::info;
+
+use super::{
+    jwk::{jwk_to_ecc_public_key, jwk_to_x25519_public_key, JoseKey},
+    jws::{decode_header, hmac_sign, parse_header},
+    JoseSerialization, JwkeyAlgorithm,
+};
+use crate::{
+    codec::{base64_decode, base64_encode},
+    crypto::{aes::encrypt_or_decrypt_aes, rsa},
+    enums::{
+        AesEncryptionPadding, Digest, EncryptionMode, RsaEncryptionPadding,
+        TextEncoding,
+    },
+    errors::{Error, Result},
+    utils::random_bytes,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JweRecipient {
+    pub key: JoseKey,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    pub key_id: Option<String>,
+    // extra per-recipient header parameters, as a json object
+    pub header: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JweDto {
+    pub payload: String,
+    pub payload_encoding: TextEncoding,
+    pub encryption: JwkeyAlgorithm,
+    pub recipients: Vec<JweRecipient>,
+    // extra protected header parameters, as a json object
+    pub header: Option<String>,
+    // additional authenticated data, ignored by the compact serialization
+    pub aad: Option<String>,
+    pub aad_encoding: Option<TextEncoding>,
+    pub serialization: JoseSerialization,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JweDecryptDto {
+    pub jwe: String,
+    pub key: JoseKey,
+    pub output_encoding: TextEncoding,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JweDecryption {
+    pub header: Value,
+    pub payload: String,
+}
 
 #[tauri::command]
-pub(crate) fn generate_jwe() -> Result<String> {
-    Ok("".to_string())
+pub(crate) fn generate_jwe(data: JweDto) -> Result<String> {
+    info!(
+        "generate jwe, enc: {:?}, serialization: {:?}, recipients: {}",
+        data.encryption,
+        data.serialization,
+        data.recipients.len()
+    );
+    let payload = data.payload_encoding.decode(&data.payload)?;
+    let aad = match (data.aad.as_ref(), data.aad_encoding) {
+        (Some(aad), Some(encoding)) => Some(encoding.decode(aad)?),
+        _ => None,
+    };
+    let header = match data.header.as_ref() {
+        Some(header) => parse_header(header)?,
+        None => Map::new(),
+    };
+    generate_jwe_inner(
+        &payload,
+        data.encryption,
+        &data.recipients,
+        header,
+        aad,
+        data.serialization,
+    )
+}
+
+#[tauri::command]
+pub(crate) fn decrypt_jwe(data: JweDecryptDto) -> Result<JweDecryption> {
+    info!("decrypt jwe: {}", data.jwe.len());
+    let (header, payload) = decrypt_jwe_inner(&data.jwe, &data.key)?;
+    Ok(JweDecryption {
+        header,
+        payload: data.output_encoding.encode(&payload)?,
+    })
+}
+
+pub(crate) fn generate_jwe_inner(
+    payload: &[u8],
+    enc: JwkeyAlgorithm,
+    recipients: &[JweRecipient],
+    mut protected: Map<String, Value>,
+    aad: Option<Vec<u8>>,
+    serialization: JoseSerialization,
+) -> Result<String> {
+    if !enc.is_content_encryption() {
+        return Err(Error::Unsupported(format!("jwe enc {:?}", enc)));
+    }
+    if recipients.is_empty() {
+        return Err(Error::Unsupported("jwe without recipient".to_string()));
+    }
+    if serialization != JoseSerialization::General && recipients.len() > 1 {
+        return Err(Error::Unsupported(format!(
+            "{:?} jwe with multiple recipients",
+            serialization
+        )));
+    }
+    protected.insert("enc".to_string(), json!(enc));
+
+    let mut headers = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let alg = recipient_algorithm(recipient)?;
+        let mut header = match recipient.header.as_ref() {
+            Some(header) => parse_header(header)?,
+            None => Map::new(),
+        };
+        header.insert("alg".to_string(), json!(alg));
+        let kid = recipient
+            .key_id
+            .clone()
+            .or(recipient.key.jwk()?.and_then(|jwk| {
+                jwk["kid"].as_str().map(|kid| kid.to_string())
+            }));
+        if let Some(kid) = kid {
+            header.insert("kid".to_string(), Value::String(kid));
+        }
+        headers.push((alg, header));
+    }
+
+    let direct = headers.iter().any(|(alg, _)| {
+        matches!(alg, JwkeyAlgorithm::Dir | JwkeyAlgorithm::EcdhEs)
+    });
+    if direct && headers.len() > 1 {
+        return Err(Error::Unsupported(
+            "direct key agreement with multiple recipients".to_string(),
+        ));
+    }
+
+    let cek_len = content_key_length(enc)?;
+    let mut cek = random_bytes(cek_len)?;
+    let mut encrypted_keys = Vec::with_capacity(recipients.len());
+    for (recipient, (alg, header)) in recipients.iter().zip(headers.iter_mut())
+    {
+        let agreement = protected
+            .iter()
+            .chain(header.iter())
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Map<String, Value>>();
+        let (encrypted_key, params) = match alg {
+            JwkeyAlgorithm::Dir => {
+                cek = recipient.key.secret()?;
+                if cek.len() != cek_len {
+                    return Err(Error::Unsupported(format!(
+                        "dir key length {} for {:?}",
+                        cek.len(),
+                        enc
+                    )));
+                }
+                (vec![], Map::new())
+            }
+            JwkeyAlgorithm::EcdhEs => {
+                let (derived, params) = ecdh_es_encrypt(
+                    &recipient.key,
+                    &agreement,
+                    &algorithm_name(enc)?,
+                    cek_len,
+                )?;
+                cek = derived;
+                (vec![], params)
+            }
+            _ => encrypt_key(*alg, &recipient.key, &cek, &agreement)?,
+        };
+        header.extend(params);
+        encrypted_keys.push(encrypted_key);
+    }
+
+    let compact = serialization != JoseSerialization::General;
+    if compact {
+        let (_, header) = headers.remove(0);
+        protected.extend(header);
+    }
+    let encoded_protected = base64_encode(
+        &serde_json::to_vec(&protected).context("jwe header failed")?,
+        true,
+        true,
+    )?;
+    let aad = match (serialization, aad) {
+        (JoseSerialization::Compact, _) | (_, None) => None,
+        (_, Some(aad)) => Some(base64_encode(&aad, true, true)?),
+    };
+    let authenticated = match aad.as_ref() {
+        Some(aad) => format!("{}.{}", encoded_protected, aad),
+        None => encoded_protected.clone(),
+    };
+
+    let iv = random_bytes(content_iv_length(enc))?;
+    let (ciphertext, tag) =
+        encrypt_content(enc, &cek, &iv, authenticated.as_bytes(), payload)?;
+
+    let iv = base64_encode(&iv, true, true)?;
+    let ciphertext = base64_encode(&ciphertext, true, true)?;
+    let tag = base64_encode(&tag, true, true)?;
+
+    Ok(match serialization {
+        JoseSerialization::Compact => format!(
+            "{}.{}.{}.{}.{}",
+            encoded_protected,
+            base64_encode(&encrypted_keys[0], true, true)?,
+            iv,
+            ciphertext,
+            tag
+        ),
+        JoseSerialization::Flattened => {
+            let mut jwe = json!({
+                "protected": encoded_protected,
+                "iv": iv,
+                "ciphertext": ciphertext,
+                "tag": tag,
+            });
+            if !encrypted_keys[0].is_empty() {
+                jwe["encrypted_key"] = Value::String(base64_encode(
+                    &encrypted_keys[0],
+                    true,
+                    true,
+                )?);
+            }
+            if let Some(aad) = aad {
+                jwe["aad"] = Value::String(aad);
+            }
+            serde_json::to_string_pretty(&jwe).context("jwe to json failed")?
+        }
+        JoseSerialization::General => {
+            let mut entries = Vec::with_capacity(headers.len());
+            for ((_, header), encrypted_key) in
+                headers.into_iter().zip(encrypted_keys.iter())
+            {
+                let mut entry = json!({ "header": header });
+                if !encrypted_key.is_empty() {
+                    entry["encrypted_key"] = Value::String(base64_encode(
+                        encrypted_key,
+                        true,
+                        true,
+                    )?);
+                }
+                entries.push(entry);
+            }
+            let mut jwe = json!({
+                "protected": encoded_protected,
+                "recipients": entries,
+                "iv": iv,
+                "ciphertext": ciphertext,
+                "tag": tag,
+            });
+            if let Some(aad) = aad {
+                jwe["aad"] = Value::String(aad);
+            }
+            serde_json::to_string_pretty(&jwe).context("jwe to json failed")?
+        }
+    })
+}
+
+pub(crate) struct JweParts {
+    pub protected: Map<String, Value>,
+    pub recipients: Vec<(Map<String, Value>, Vec<u8>)>,
+    pub authenticated: Vec<u8>,
+    pub iv: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+pub(crate) fn parse_jwe(input: &str) -> Result<JweParts> {
+    let input = input.trim();
+    if input.starts_with('{') {
+        let jwe: Value =
+            serde_json::from_str(input).context("invalid jwe json")?;
+        let encoded_protected = jwe["protected"].as_str().unwrap_or_default();
+        let protected = if encoded_protected.is_empty() {
+            Map::new()
+        } else {
+            decode_header(encoded_protected)?
+        };
+        let shared =
+            jwe["unprotected"].as_object().cloned().unwrap_or_default();
+        let entries = match jwe["recipients"].as_array() {
+            Some(recipients) => recipients.clone(),
+            None => vec![jwe.clone()],
+        };
+        let mut recipients = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let mut header = protected.clone();
+            header.extend(shared.clone());
+            if let Some(unprotected) = entry["header"].as_object() {
+                header.extend(unprotected.clone());
+            }
+            let encrypted_key = base64_decode(
+                entry["encrypted_key"].as_str().unwrap_or_default(),
+                true,
+                true,
+            )?;
+            recipients.push((header, encrypted_key));
+        }
+        let authenticated = match jwe["aad"].as_str() {
+            Some(aad) => format!("{}.{}", encoded_protected, aad),
+            None => encoded_protected.to_string(),
+        };
+        let segment = |name: &str| {
+            base64_decode(jwe[name].as_str().unwrap_or_default(), true, true)
+        };
+        Ok(JweParts {
+            protected,
+            recipients,
+            authenticated: authenticated.into_bytes(),
+            iv: segment("iv")?,
+            ciphertext: segment("ciphertext")?,
+            tag: segment("tag")?,
+        })
+    } else {
+        let parts = input.split('.').collect::<Vec<&str>>();
+        if parts.len() != 5 {
+            return Err(Error::Unsupported(format!(
+                "compact jwe with {} parts",
+                parts.len()
+            )));
+        }
+        let protected = decode_header(parts[0])?;
+        Ok(JweParts {
+            recipients: vec![(
+                protected.clone(),
+                base64_decode(parts[1], true, true)?,
+            )],
+            protected,
+            authenticated: parts[0].as_bytes().to_vec(),
+            iv: base64_decode(parts[2], true, true)?,
+            ciphertext: base64_decode(parts[3], true, true)?,
+            tag: base64_decode(parts[4], true, true)?,
+        })
+    }
+}
+
+pub(crate) fn decrypt_jwe_inner(
+    input: &str,
+    key: &JoseKey,
+) -> Result<(Value, Vec<u8>)> {
+    let parts = parse_jwe(input)?;
+    let mut failure = None;
+    for (header, encrypted_key) in parts.recipients.iter() {
+        let decrypted = header_algorithm(header, "alg").and_then(|alg| {
+            let enc = header_algorithm(header, "enc")?;
+            let cek = decrypt_key(alg, enc, key, encrypted_key, header)?;
+            let mut sealed = parts.ciphertext.clone();
+            sealed.extend_from_slice(&parts.tag);
+            decrypt_content(enc, &cek, &parts.iv, &parts.authenticated, &sealed)
+        });
+        match decrypted {
+            Ok(payload) => {
+                return Ok((Value::Object(parts.protected), payload));
+            }
+            Err(err) => failure = Some(err),
+        }
+    }
+    Err(failure
+        .unwrap_or(Error::Unsupported("jwe without recipient".to_string())))
+}
+
+fn recipient_algorithm(recipient: &JweRecipient) -> Result<JwkeyAlgorithm> {
+    let alg = match recipient.algorithm {
+        Some(alg) => alg,
+        None => recipient
+            .key
+            .jwk()?
+            .and_then(|jwk| {
+                serde_json::from_value::<JwkeyAlgorithm>(jwk["alg"].clone())
+                    .ok()
+            })
+            .ok_or(Error::Unsupported(
+                "jwe algorithm is required".to_string(),
+            ))?,
+    };
+    if !alg.is_key_management() {
+        return Err(Error::Unsupported(format!("jwe alg {:?}", alg)));
+    }
+    Ok(alg)
+}
+
+fn header_algorithm(
+    header: &Map<String, Value>,
+    name: &str,
+) -> Result<JwkeyAlgorithm> {
+    let value = header
+        .get(name)
+        .ok_or(Error::Unsupported(format!("jwe header without `{}`", name)))?;
+    Ok(serde_json::from_value::<JwkeyAlgorithm>(value.clone())
+        .context(format!("unsupported {} {}", name, value))?)
+}
+
+fn algorithm_name(alg: JwkeyAlgorithm) -> Result<String> {
+    Ok(serde_json::to_value(alg)
+        .context("algorithm name failed")?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+fn header_bytes(header: &Map<String, Value>, name: &str) -> Result<Vec<u8>> {
+    match header.get(name).and_then(|value| value.as_str()) {
+        Some(value) => base64_decode(value, true, true),
+        None => Ok(vec![]),
+    }
+}
+
+pub(crate) fn content_key_length(enc: JwkeyAlgorithm) -> Result<usize> {
+    match enc.symmetric_key_length() {
+        Some(length) if enc.is_content_encryption() => Ok(length),
+        _ => Err(Error::Unsupported(format!("jwe enc {:?}", enc))),
+    }
+}
+
+fn content_iv_length(enc: JwkeyAlgorithm) -> usize {
+    match enc {
+        JwkeyAlgorithm::A128cbcHs256
+        | JwkeyAlgorithm::A192cbcHs384
+        | JwkeyAlgorithm::A256cbcHs512 => 16,
+        _ => 12,
+    }
+}
+
+pub(crate) fn wrap_key_length(alg: JwkeyAlgorithm) -> Result<usize> {
+    Ok(match alg {
+        JwkeyAlgorithm::A128KW
+        | JwkeyAlgorithm::A128GCMKW
+        | JwkeyAlgorithm::EcdhEsA128kw => 16,
+        JwkeyAlgorithm::A192KW
+        | JwkeyAlgorithm::A192GCMKW
+        | JwkeyAlgorithm::EcdhEsA192kw => 24,
+        JwkeyAlgorithm::A256KW
+        | JwkeyAlgorithm::A256GCMKW
+        | JwkeyAlgorithm::EcdhEsA256kw => 32,
+        _ => return Err(Error::Unsupported(format!("jwe alg {:?}", alg))),
+    })
+}
+
+fn encrypt_content(
+    enc: JwkeyAlgorithm,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    payload: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    if cek.len() != content_key_length(enc)? {
+        return Err(Error::Unsupported(format!(
+            "cek length {} for {:?}",
+            cek.len(),
+            enc
+        )));
+    }
+    match enc {
+        JwkeyAlgorithm::A128GCM
+        | JwkeyAlgorithm::A192GCM
+        | JwkeyAlgorithm::A256GCM => {
+            let mut sealed = encrypt_or_decrypt_aes(
+                EncryptionMode::Gcm,
+                payload,
+                cek,
+                Some(iv.to_vec()),
+                Some(aad.to_vec()),
+                AesEncryptionPadding::NoPadding,
+                None,
+                true,
+            )?;
+            let tag = sealed.split_off(sealed.len() - 16);
+            Ok((sealed, tag))
+        }
+        _ => {
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            let ciphertext = encrypt_or_decrypt_aes(
+                EncryptionMode::Cbc,
+                payload,
+                enc_key,
+                Some(iv.to_vec()),
+                None,
+                AesEncryptionPadding::Pkcs7Padding,
+                None,
+                true,
+            )?;
+            let tag = cbc_hmac_tag(enc, mac_key, aad, iv, &ciphertext)?;
+            Ok((ciphertext, tag))
+        }
+    }
+}
+
+fn decrypt_content(
+    enc: JwkeyAlgorithm,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>> {
+    if cek.len() != content_key_length(enc)? {
+        return Err(Error::Unsupported(format!(
+            "cek length {} for {:?}",
+            cek.len(),
+            enc
+        )));
+    }
+    match enc {
+        JwkeyAlgorithm::A128GCM
+        | JwkeyAlgorithm::A192GCM
+        | JwkeyAlgorithm::A256GCM => encrypt_or_decrypt_aes(
+            EncryptionMode::Gcm,
+            sealed,
+            cek,
+            Some(iv.to_vec()),
+            Some(aad.to_vec()),
+            AesEncryptionPadding::NoPadding,
+            None,
+            false,
+        ),
+        _ => {
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            if sealed.len() < mac_key.len() {
+                return Err(Error::Unsupported("jwe tag length".to_string()));
+            }
+            let (ciphertext, tag) =
+                sealed.split_at(sealed.len() - mac_key.len());
+            let expected = cbc_hmac_tag(enc, mac_key, aad, iv, ciphertext)?;
+            if !bool::from(expected.ct_eq(tag)) {
+                return Err(Error::Unsupported(
+                    "jwe authentication tag mismatch".to_string(),
+                ));
+            }
+            encrypt_or_decrypt_aes(
+                EncryptionMode::Cbc,
+                ciphertext,
+                enc_key,
+                Some(iv.to_vec()),
+                None,
+                AesEncryptionPadding::Pkcs7Padding,
+                None,
+                false,
+            )
+        }
+    }
+}
+
+fn cbc_hmac_tag(
+    enc: JwkeyAlgorithm,
+    mac_key: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>> {
+    let mut input =
+        Vec::with_capacity(aad.len() + iv.len() + ciphertext.len() + 8);
+    input.extend_from_slice(aad);
+    input.extend_from_slice(iv);
+    input.extend_from_slice(ciphertext);
+    input.extend_from_slice(&((aad.len() as u64) * 8).to_be_bytes());
+    let mac = match enc {
+        JwkeyAlgorithm::A128cbcHs256 => {
+            hmac_sign::<sha2::Sha256>(mac_key, &input)
+        }
+        JwkeyAlgorithm::A192cbcHs384 => {
+            hmac_sign::<sha2::Sha384>(mac_key, &input)
+        }
+        JwkeyAlgorithm::A256cbcHs512 => {
+            hmac_sign::<sha2::Sha512>(mac_key, &input)
+        }
+        _ => Err(Error::Unsupported(format!("jwe enc {:?}", enc))),
+    }?;
+    Ok(mac[.. mac_key.len()].to_vec())
+}
+
+fn encrypt_key(
+    alg: JwkeyAlgorithm,
+    key: &JoseKey,
+    cek: &[u8],
+    header: &Map<String, Value>,
+) -> Result<(Vec<u8>, Map<String, Value>)> {
+    let mut params = Map::new();
+    let encrypted_key = match alg {
+        JwkeyAlgorithm::A128KW
+        | JwkeyAlgorithm::A192KW
+        | JwkeyAlgorithm::A256KW => aes_key_wrap(alg, &key.secret()?, cek)?,
+        JwkeyAlgorithm::A128GCMKW
+        | JwkeyAlgorithm::A192GCMKW
+        | JwkeyAlgorithm::A256GCMKW => {
+            let kek = key.secret()?;
+            if kek.len() != wrap_key_length(alg)? {
+                return Err(Error::Unsupported(format!(
+                    "key length {} for {:?}",
+                    kek.len(),
+                    alg
+                )));
+            }
+            let iv = random_bytes(12)?;
+            let mut sealed = encrypt_or_decrypt_aes(
+                EncryptionMode::Gcm,
+                cek,
+                &kek,
+                Some(iv.clone()),
+                None,
+                AesEncryptionPadding::NoPadding,
+                None,
+                true,
+            )?;
+            let tag = sealed.split_off(sealed.len() - 16);
+            params.insert(
+                "iv".to_string(),
+                Value::String(base64_encode(&iv, true, true)?),
+            );
+            params.insert(
+                "tag".to_string(),
+                Value::String(base64_encode(&tag, true, true)?),
+            );
+            sealed
+        }
+        JwkeyAlgorithm::Rsa1_5
+        | JwkeyAlgorithm::RsaOaep
+        | JwkeyAlgorithm::RsaOaep256
+        | JwkeyAlgorithm::RsaOaep384
+        | JwkeyAlgorithm::RsaOaep521 => {
+            let (padding, digest) = rsa_padding(alg);
+            rsa::encrypt_rsa_inner(
+                key.rsa_public_key()?,
+                cek,
+                padding,
+                digest,
+                digest,
+            )?
+        }
+        JwkeyAlgorithm::EcdhEsA128kw
+        | JwkeyAlgorithm::EcdhEsA192kw
+        | JwkeyAlgorithm::EcdhEsA256kw => {
+            let (kek, agreement) = ecdh_es_encrypt(
+                key,
+                header,
+                &algorithm_name(alg)?,
+                wrap_key_length(alg)?,
+            )?;
+            params.extend(agreement);
+            aes_key_wrap(alg, &kek, cek)?
+        }
+        _ => return Err(Error::Unsupported(format!("jwe alg {:?}", alg))),
+    };
+    Ok((encrypted_key, params))
+}
+
+fn decrypt_key(
+    alg: JwkeyAlgorithm,
+    enc: JwkeyAlgorithm,
+    key: &JoseKey,
+    encrypted_key: &[u8],
+    header: &Map<String, Value>,
+) -> Result<Vec<u8>> {
+    match alg {
+        JwkeyAlgorithm::Dir => key.secret(),
+        JwkeyAlgorithm::A128KW
+        | JwkeyAlgorithm::A192KW
+        | JwkeyAlgorithm::A256KW => {
+            aes_key_unwrap(alg, &key.secret()?, encrypted_key)
+        }
+        JwkeyAlgorithm::A128GCMKW
+        | JwkeyAlgorithm::A192GCMKW
+        | JwkeyAlgorithm::A256GCMKW => {
+            let kek = key.secret()?;
+            if kek.len() != wrap_key_length(alg)? {
+                return Err(Error::Unsupported(format!(
+                    "key length {} for {:?}",
+                    kek.len(),
+                    alg
+                )));
+            }
+            let mut sealed = encrypted_key.to_vec();
+            sealed.extend_from_slice(&header_bytes(header, "tag")?);
+            encrypt_or_decrypt_aes(
+                EncryptionMode::Gcm,
+                &sealed,
+                &kek,
+                Some(header_bytes(header, "iv")?),
+                None,
+                AesEncryptionPadding::NoPadding,
+                None,
+                false,
+            )
+        }
+        JwkeyAlgorithm::Rsa1_5
+        | JwkeyAlgorithm::RsaOaep
+        | JwkeyAlgorithm::RsaOaep256
+        | JwkeyAlgorithm::RsaOaep384
+        | JwkeyAlgorithm::RsaOaep521 => {
+            let (padding, digest) = rsa_padding(alg);
+            rsa::decrypt_rsa_inner(
+                key.rsa_private_key()?,
+                encrypted_key,
+                padding,
+                digest,
+                digest,
+            )
+        }
+        JwkeyAlgorithm::EcdhEs => ecdh_es_decrypt(
+            key,
+            header,
+            &algorithm_name(enc)?,
+            content_key_length(enc)?,
+        ),
+        JwkeyAlgorithm::EcdhEsA128kw
+        | JwkeyAlgorithm::EcdhEsA192kw
+        | JwkeyAlgorithm::EcdhEsA256kw => {
+            let kek = ecdh_es_decrypt(
+                key,
+                header,
+                &algorithm_name(alg)?,
+                wrap_key_length(alg)?,
+            )?;
+            aes_key_unwrap(alg, &kek, encrypted_key)
+        }
+        _ => Err(Error::Unsupported(format!("jwe alg {:?}", alg))),
+    }
+}
+
+fn rsa_padding(alg: JwkeyAlgorithm) -> (RsaEncryptionPadding, Option<Digest>) {
+    match alg {
+        JwkeyAlgorithm::RsaOaep => {
+            (RsaEncryptionPadding::Oaep, Some(Digest::Sha1))
+        }
+        JwkeyAlgorithm::RsaOaep256 => {
+            (RsaEncryptionPadding::Oaep, Some(Digest::Sha256))
+        }
+        JwkeyAlgorithm::RsaOaep384 => {
+            (RsaEncryptionPadding::Oaep, Some(Digest::Sha384))
+        }
+        JwkeyAlgorithm::RsaOaep521 => {
+            (RsaEncryptionPadding::Oaep, Some(Digest::Sha512))
+        }
+        _ => (RsaEncryptionPadding::Pkcs1v15, None),
+    }
+}
+
+fn aes_key_wrap(
+    alg: JwkeyAlgorithm,
+    kek: &[u8],
+    cek: &[u8],
+) -> Result<Vec<u8>> {
+    if kek.len() != wrap_key_length(alg)? {
+        return Err(Error::Unsupported(format!(
+            "key length {} for {:?}",
+            kek.len(),
+            alg
+        )));
+    }
+    Ok(match kek.len() {
+        16 => aes_kw::KekAes128::try_from(kek)
+            .context("invalid aes kw key")?
+            .wrap_vec(cek),
+        24 => aes_kw::KekAes192::try_from(kek)
+            .context("invalid aes kw key")?
+            .wrap_vec(cek),
+        _ => aes_kw::KekAes256::try_from(kek)
+            .context("invalid aes kw key")?
+            .wrap_vec(cek),
+    }
+    .context("aes key wrap failed")?)
+}
+
+fn aes_key_unwrap(
+    alg: JwkeyAlgorithm,
+    kek: &[u8],
+    encrypted_key: &[u8],
+) -> Result<Vec<u8>> {
+    if kek.len() != wrap_key_length(alg)? {
+        return Err(Error::Unsupported(format!(
+            "key length {} for {:?}",
+            kek.len(),
+            alg
+        )));
+    }
+    Ok(match kek.len() {
+        16 => aes_kw::KekAes128::try_from(kek)
+            .context("invalid aes kw key")?
+            .unwrap_vec(encrypted_key),
+        24 => aes_kw::KekAes192::try_from(kek)
+            .context("invalid aes kw key")?
+            .unwrap_vec(encrypted_key),
+        _ => aes_kw::KekAes256::try_from(kek)
+            .context("invalid aes kw key")?
+            .unwrap_vec(encrypted_key),
+    }
+    .context("aes key unwrap failed")?)
+}
+
+/// Runs the sender side of ECDH-ES and derives `key_len` bytes with the
+/// Concat KDF, returns the derived key and the `epk` header parameter.
+fn ecdh_es_encrypt(
+    key: &JoseKey,
+    header: &Map<String, Value>,
+    algorithm_id: &str,
+    key_len: usize,
+) -> Result<(Vec<u8>, Map<String, Value>)> {
+    let crv = key.ecdh_curve()?;
+    let (shared_secret, epk) = match crv.as_str() {
+        "P-256" => ecdh_ephemeral::<p256::NistP256>(key, &crv)?,
+        "P-384" => ecdh_ephemeral::<p384::NistP384>(key, &crv)?,
+        "P-521" => ecdh_ephemeral::<p521::NistP521>(key, &crv)?,
+        "X25519" => {
+            let public_key = key.x25519_public_key()?;
+            let ephemeral = x25519_dalek::EphemeralSecret::random_from_rng(
+                rand::thread_rng(),
+            );
+            let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral);
+            let shared_secret = ephemeral.diffie_hellman(&public_key);
+            (
+                shared_secret.as_bytes().to_vec(),
+                json!({
+                    "kty": "OKP",
+                    "crv": "X25519",
+                    "x": base64_encode(ephemeral_public.as_bytes(), true, true)?,
+                }),
+            )
+        }
+        _ => return Err(Error::Unsupported(format!("ecdh curve {}", crv))),
+    };
+    let mut params = Map::new();
+    params.insert("epk".to_string(), epk);
+    let derived = concat_kdf(&shared_secret, algorithm_id, header, key_len)?;
+    Ok((derived, params))
+}
+
+fn ecdh_es_decrypt(
+    key: &JoseKey,
+    header: &Map<String, Value>,
+    algorithm_id: &str,
+    key_len: usize,
+) -> Result<Vec<u8>> {
+    let epk = header
+        .get("epk")
+        .ok_or(Error::Unsupported("jwe header without `epk`".to_string()))?;
+    let crv = epk["crv"].as_str().unwrap_or_default();
+    let shared_secret = match crv {
+        "P-256" => ecdh_static::<p256::NistP256>(key, epk, crv)?,
+        "P-384" => ecdh_static::<p384::NistP384>(key, epk, crv)?,
+        "P-521" => ecdh_static::<p521::NistP521>(key, epk, crv)?,
+        "X25519" => {
+            let ephemeral_public = jwk_to_x25519_public_key(epk)?;
+            key.x25519_secret()?
+                .diffie_hellman(&ephemeral_public)
+                .as_bytes()
+                .to_vec()
+        }
+        _ => return Err(Error::Unsupported(format!("ecdh curve {}", crv))),
+    };
+    concat_kdf(&shared_secret, algorithm_id, header, key_len)
+}
+
+fn ecdh_ephemeral<C>(key: &JoseKey, crv: &str) -> Result<(Vec<u8>, Value)>
+where
+    C: CurveArithmetic + pkcs8::AssociatedOid,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let public_key = key.ecc_public_key::<C>(crv)?;
+    let ephemeral =
+        elliptic_curve::SecretKey::<C>::random(&mut rand::thread_rng());
+    let shared_secret = elliptic_curve::ecdh::diffie_hellman(
+        ephemeral.to_nonzero_scalar(),
+        public_key.as_affine(),
+    );
+    let point = ephemeral.public_key().to_encoded_point(false);
+    let coordinate = |value: Option<&elliptic_curve::FieldBytes<C>>| {
+        base64_encode(
+            value.map(|v| v.as_slice()).unwrap_or_default(),
+            true,
+            true,
+        )
+    };
+    Ok((
+        shared_secret.raw_secret_bytes().to_vec(),
+        json!({
+            "kty": "EC",
+            "crv": crv,
+            "x": coordinate(point.x())?,
+            "y": coordinate(point.y())?,
+        }),
+    ))
+}
+
+fn ecdh_static<C>(key: &JoseKey, epk: &Value, crv: &str) -> Result<Vec<u8>>
+where
+    C: CurveArithmetic + pkcs8::AssociatedOid,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let ephemeral_public = jwk_to_ecc_public_key::<C>(epk, crv)?;
+    let secret_key = key.ecc_secret_key::<C>(crv)?;
+    let shared_secret = elliptic_curve::ecdh::diffie_hellman(
+        secret_key.to_nonzero_scalar(),
+        ephemeral_public.as_affine(),
+    );
+    Ok(shared_secret.raw_secret_bytes().to_vec())
+}
+
+/// NIST SP 800-56A Concat KDF as profiled by RFC 7518 section 4.6.2.
+fn concat_kdf(
+    shared_secret: &[u8],
+    algorithm_id: &str,
+    header: &Map<String, Value>,
+    key_len: usize,
+) -> Result<Vec<u8>> {
+    let mut other_info = Vec::new();
+    for field in [
+        algorithm_id.as_bytes().to_vec(),
+        header_bytes(header, "apu")?,
+        header_bytes(header, "apv")?,
+    ] {
+        other_info.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        other_info.extend_from_slice(&field);
+    }
+    other_info.extend_from_slice(&((key_len * 8) as u32).to_be_bytes());
+    let mut okm = vec![0; key_len];
+    concat_kdf::derive_key_into::<sha2::Sha256>(
+        shared_secret,
+        &other_info,
+        &mut okm,
+    )
+    .context("concatenation derive key faild")?;
+    Ok(okm)
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing::info;
+    use tracing_test::traced_test;
+
+    use crate::{
+        enums::TextEncoding,
+        jwt::{
+            jwe::{
+                decrypt_jwe, generate_jwe, JweDecryptDto, JweDto, JweRecipient,
+            },
+            jwk::{generate_jwk, JoseKey, JwkGenerate},
+            JoseSerialization, JwkeyAlgorithm, JwkeyType,
+        },
+    };
+
+    async fn recipient_keys(alg: JwkeyAlgorithm) -> Vec<JoseKey> {
+        let key_types = match alg {
+            JwkeyAlgorithm::Rsa1_5
+            | JwkeyAlgorithm::RsaOaep
+            | JwkeyAlgorithm::RsaOaep256
+            | JwkeyAlgorithm::RsaOaep384
+            | JwkeyAlgorithm::RsaOaep521 => vec![(JwkeyType::RSA, alg)],
+            JwkeyAlgorithm::EcdhEs
+            | JwkeyAlgorithm::EcdhEsA128kw
+            | JwkeyAlgorithm::EcdhEsA192kw
+            | JwkeyAlgorithm::EcdhEsA256kw => vec![
+                (JwkeyType::X25519, alg),
+                (JwkeyType::EcDSA, JwkeyAlgorithm::ES256),
+                (JwkeyType::EcDSA, JwkeyAlgorithm::ES384),
+                (JwkeyType::EcDSA, JwkeyAlgorithm::ES521),
+            ],
+            _ => vec![(JwkeyType::Symmetric, alg)],
+        };
+        let mut keys = Vec::new();
+        for (key_type, algorithm) in key_types {
+            let key = generate_jwk(JwkGenerate {
+                key_id: Some("kits".to_string()),
+                key_type,
+                algorithm: Some(algorithm),
+                usage: None,
+                operations: None,
+                bits: None,
+                curve_name: None,
+                thumbprint: None,
+            })
+            .await
+            .unwrap();
+            keys.push(JoseKey::Jwk { key });
+        }
+        keys
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_generate_and_decrypt_jwe() {
+        let payload = "plaintext";
+        let encryptions = [
+            JwkeyAlgorithm::A128GCM,
+            JwkeyAlgorithm::A192GCM,
+            JwkeyAlgorithm::A256GCM,
+            JwkeyAlgorithm::A128cbcHs256,
+            JwkeyAlgorithm::A192cbcHs384,
+            JwkeyAlgorithm::A256cbcHs512,
+        ];
+        for alg in [
+            JwkeyAlgorithm::A128KW,
+            JwkeyAlgorithm::A192KW,
+            JwkeyAlgorithm::A256KW,
+            JwkeyAlgorithm::A128GCMKW,
+            JwkeyAlgorithm::A192GCMKW,
+            JwkeyAlgorithm::A256GCMKW,
+            JwkeyAlgorithm::Rsa1_5,
+            JwkeyAlgorithm::RsaOaep,
+            JwkeyAlgorithm::RsaOaep256,
+            JwkeyAlgorithm::RsaOaep384,
+            JwkeyAlgorithm::RsaOaep521,
+            JwkeyAlgorithm::EcdhEs,
+            JwkeyAlgorithm::EcdhEsA128kw,
+            JwkeyAlgorithm::EcdhEsA192kw,
+            JwkeyAlgorithm::EcdhEsA256kw,
+        ] {
+            for (key, enc) in recipient_keys(alg)
+                .await
+                .into_iter()
+                .flat_map(|key| encryptions.map(|enc| (key.clone(), enc)))
+            {
+                for serialization in JoseSerialization::iter() {
+                    info!(
+                        "jwe alg: {:?}, enc: {:?}, {:?}",
+                        alg, enc, serialization
+                    );
+                    let jwe = generate_jwe(JweDto {
+                        payload: payload.to_string(),
+                        payload_encoding: TextEncoding::Utf8,
+                        encryption: enc,
+                        recipients: vec![JweRecipient {
+                            key: key.clone(),
+                            algorithm: Some(alg),
+                            key_id: None,
+                            header: None,
+                        }],
+                        header: Some(r#"{"cty":"text/plain"}"#.to_string()),
+                        aad: Some("aad".to_string()),
+                        aad_encoding: Some(TextEncoding::Utf8),
+                        serialization,
+                    })
+                    .unwrap();
+                    let decryption = decrypt_jwe(JweDecryptDto {
+                        jwe,
+                        key: key.clone(),
+                        output_encoding: TextEncoding::Utf8,
+                    })
+                    .unwrap();
+                    assert_eq!(decryption.payload, payload);
+                    assert_eq!(decryption.header["cty"], "text/plain");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decrypt_rfc7516_a128kw() {
+        let jwe = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.\
+                   6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.\
+                   AxY8DCtDaGlsbGljb3RoZQ.\
+                   KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.\
+                   U0m_YmjN04DJvceFICbCVQ";
+        let decryption = decrypt_jwe(JweDecryptDto {
+            jwe: jwe.to_string(),
+            key: JoseKey::Jwk {
+                key: r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#
+                    .to_string(),
+            },
+            output_encoding: TextEncoding::Utf8,
+        })
+        .unwrap();
+        assert_eq!(decryption.payload, "Live long and prosper.");
+        assert_eq!(decryption.header["enc"], "A128CBC-HS256");
+    }
 }
@@ -1,11 +1,27 @@
 use anyhow::Context;
+use elliptic_curve::{
+    sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+    AffinePoint, CurveArithmetic, FieldBytesSize,
+};
 use jose_jwk::OkpCurves;
-use rsa::RsaPrivateKey;
+use pkcs8::EncodePublicKey;
+use rsa::{
+    traits::{PrivateKeyParts, PublicKeyParts},
+    BigUint, RsaPrivateKey, RsaPublicKey,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use strum::IntoEnumIterator;
+use tracing::info;
 
 use super::{JwkeyAlgorithm, JwkeyOperation, JwkeyType, JwkeyUsage};
-use crate::{enums::RsaKeySize, errors::Result, utils::random_bytes};
+use crate::{
+    codec::{base64_decode, base64_encode},
+    crypto::{ecc, edwards, rsa::key as rsa_key},
+    enums::{Digest, EccCurveName, KeyFormat, Pkcs, RsaKeySize, TextEncoding},
+    errors::{Error, Result},
+    utils::{jwkey_algorithm, jwkey_usage, random_bytes, KeyTuple},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,25 +32,320 @@ pub struct JwkGenerate {
     pub usage: Option<JwkeyUsage>,
     pub operations: Option<Vec<JwkeyOperation>>,
     pub bits: Option<RsaKeySize>,
+    // ecdsa curve, inferred from the algorithm when absent
+    pub curve_name: Option<EccCurveName>,
+    // sets `kid` to the RFC 7638 thumbprint when `key_id` is absent
+    pub thumbprint: Option<Digest>,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwkImport {
+    pub key: String,
+    pub key_type: JwkeyType,
+    // required by ecdsa keys
+    pub curve_name: Option<EccCurveName>,
+    pub pkcs: Pkcs,
+    pub format: KeyFormat,
+    pub encoding: TextEncoding,
+    pub public_only: bool,
+    pub key_id: Option<String>,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    pub usage: Option<JwkeyUsage>,
+    pub operations: Option<Vec<JwkeyOperation>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwkExport {
+    pub key: String,
+    pub pkcs: Pkcs,
+    pub format: KeyFormat,
+    pub encoding: TextEncoding,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwkThumbprint {
+    pub thumbprint: String,
+    // RFC 9278 uri, absent for digests without a registered name
+    pub uri: Option<String>,
+}
+
 #[tauri::command]
 pub(crate) async fn generate_jwk(data: JwkGenerate) -> Result<String> {
-    let mut value = generate_jwk_inner(
-        data.algorithm.unwrap_or(data.key_type.default_algorithm()),
+    let value = generate_jwk_inner(
+        data.key_type,
+        data.algorithm,
+        data.curve_name,
+        data.bits,
     )
     .await?;
-    if let Some(key_id) = data.key_id {
+    let key_id = match (data.key_id, data.thumbprint) {
+        (None, Some(digest)) => Some(jwk_thumbprint_inner(&value, digest)?),
+        (key_id, _) => key_id,
+    };
+    jwk_with_params(value, key_id, data.algorithm, data.usage, data.operations)
+}
+
+#[tauri::command]
+pub(crate) fn import_jwk(data: JwkImport) -> Result<String> {
+    info!(
+        "import jwk, key_type: {:?}, curve_name: {:?}, pkcs: {:?}, format: \
+         {:?}, public_only: {}",
+        data.key_type,
+        data.curve_name,
+        data.pkcs,
+        data.format,
+        data.public_only
+    );
+    let key_bytes = data.encoding.decode(&data.key)?;
+    let (pkcs, format) = (data.pkcs, data.format);
+    let mut value = match data.key_type {
+        JwkeyType::RSA => {
+            match rsa_key::bytes_to_private_key(&key_bytes, pkcs, format) {
+                Ok(private_key) => rsa_private_jwk(&private_key)?,
+                Err(_) => rsa_public_jwk(&rsa_key::bytes_to_public_key(
+                    &key_bytes,
+                    rsa_public_pkcs(pkcs),
+                    format,
+                )?)?,
+            }
+        }
+        JwkeyType::EcDSA => {
+            let curve_name = data.curve_name.ok_or(Error::Unsupported(
+                "ecdsa key without curve name".to_string(),
+            ))?;
+            let crv = ecc_jwk_curve(curve_name);
+            match curve_name {
+                EccCurveName::NistP256 => import_ecc_jwk::<p256::NistP256>(
+                    &key_bytes, pkcs, format, crv,
+                ),
+                EccCurveName::NistP384 => import_ecc_jwk::<p384::NistP384>(
+                    &key_bytes, pkcs, format, crv,
+                ),
+                EccCurveName::NistP521 => import_ecc_jwk::<p521::NistP521>(
+                    &key_bytes, pkcs, format, crv,
+                ),
+                EccCurveName::Secp256k1 => import_ecc_jwk::<k256::Secp256k1>(
+                    &key_bytes, pkcs, format, crv,
+                ),
+                EccCurveName::SM2 => {
+                    import_ecc_jwk::<sm2::Sm2>(&key_bytes, pkcs, format, crv)
+                }
+            }?
+        }
+        JwkeyType::Ed25519 => {
+            match edwards::key::import_curve_25519_private_key(
+                &key_bytes, format,
+            ) {
+                Ok(signing_key) => okp_jwk(
+                    "Ed25519",
+                    signing_key.verifying_key().as_bytes(),
+                    Some(signing_key.as_bytes()),
+                )?,
+                Err(_) => okp_jwk(
+                    "Ed25519",
+                    edwards::key::import_curve_25519_public_key(
+                        &key_bytes, format,
+                    )?
+                    .as_bytes(),
+                    None,
+                )?,
+            }
+        }
+        JwkeyType::X25519 => {
+            match edwards::key::import_x25519_private_key(&key_bytes, format) {
+                Ok(secret) => okp_jwk(
+                    "X25519",
+                    x25519_dalek::PublicKey::from(&secret).as_bytes(),
+                    Some(secret.as_bytes()),
+                )?,
+                Err(_) => okp_jwk(
+                    "X25519",
+                    edwards::key::import_x25519_public_key(&key_bytes, format)?
+                        .as_bytes(),
+                    None,
+                )?,
+            }
+        }
+        JwkeyType::Symmetric => {
+            return Err(Error::Unsupported(
+                "symmetric key in pkcs".to_string(),
+            ));
+        }
+    };
+    if data.public_only {
+        value = public_jwk(&value)?;
+    }
+    jwk_with_params(
+        value,
+        data.key_id,
+        data.algorithm,
+        data.usage,
+        data.operations,
+    )
+}
+
+#[tauri::command]
+pub(crate) fn export_jwk(data: JwkExport) -> Result<KeyTuple> {
+    info!(
+        "export jwk, pkcs: {:?}, format: {:?}",
+        data.pkcs, data.format
+    );
+    let jwk = parse_jwk(&data.key)?;
+    let (pkcs, format) = (data.pkcs, data.format);
+    let private = jwk.get("d").is_some();
+    let crv = jwk["crv"].as_str().unwrap_or_default();
+    let (private_key, public_key) = match jwk["kty"].as_str() {
+        Some("RSA") => (
+            if private {
+                Some(rsa_key::private_key_to_bytes(
+                    jwk_to_rsa_private_key(&jwk)?,
+                    pkcs,
+                    format,
+                )?)
+            } else {
+                None
+            },
+            rsa_key::public_key_to_bytes(
+                jwk_to_rsa_public_key(&jwk)?,
+                rsa_public_pkcs(pkcs),
+                format,
+            )?,
+        ),
+        Some("EC") => match crv {
+            "P-256" => {
+                export_ecc_jwk::<p256::NistP256>(&jwk, crv, pkcs, format)
+            }
+            "P-384" => {
+                export_ecc_jwk::<p384::NistP384>(&jwk, crv, pkcs, format)
+            }
+            "P-521" => {
+                export_ecc_jwk::<p521::NistP521>(&jwk, crv, pkcs, format)
+            }
+            "secp256k1" => {
+                export_ecc_jwk::<k256::Secp256k1>(&jwk, crv, pkcs, format)
+            }
+            "SM2" => export_ecc_jwk::<sm2::Sm2>(&jwk, crv, pkcs, format),
+            _ => Err(Error::Unsupported(format!("jwk crv {}", crv))),
+        }?,
+        Some("OKP") if crv == "Ed25519" => (
+            if private {
+                Some(edwards::key::export_curve_25519_private_key(
+                    &jwk_to_ed25519_signing_key(&jwk)?,
+                    format,
+                )?)
+            } else {
+                None
+            },
+            edwards::key::export_curve_25519_public_key(
+                jwk_to_ed25519_verifying_key(&jwk)?,
+                format,
+            )?,
+        ),
+        Some("OKP") if crv == "X25519" => (
+            if private {
+                Some(edwards::key::export_x25519_private_key(
+                    &jwk_to_x25519_secret(&jwk)?,
+                    format,
+                )?)
+            } else {
+                None
+            },
+            edwards::key::export_x25519_public_key(
+                &jwk_to_x25519_public_key(&jwk)?,
+                format,
+            )?,
+        ),
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "jwk kty {} crv {}",
+                jwk["kty"], jwk["crv"]
+            )));
+        }
+    };
+    let mut tuple = KeyTuple::empty();
+    tuple
+        .private(match private_key {
+            Some(key) => Some(data.encoding.encode(&key)?),
+            None => None,
+        })
+        .public(Some(data.encoding.encode(&public_key)?));
+    Ok(tuple)
+}
+
+#[tauri::command]
+pub(crate) fn jwk_thumbprint(
+    key: String,
+    digest: Digest,
+) -> Result<JwkThumbprint> {
+    info!("jwk thumbprint, digest: {:?}", digest);
+    let thumbprint = jwk_thumbprint_inner(&parse_jwk(&key)?, digest)?;
+    let uri = match digest {
+        Digest::Sha256 => Some("sha-256"),
+        Digest::Sha384 => Some("sha-384"),
+        Digest::Sha512 => Some("sha-512"),
+        Digest::Sha3_256 => Some("sha3-256"),
+        Digest::Sha3_384 => Some("sha3-384"),
+        Digest::Sha3_512 => Some("sha3-512"),
+        Digest::Sha1 | Digest::Sm3 => None,
+    }
+    .map(|name| {
+        format!(
+            "urn:ietf:params:oauth:jwk-thumbprint:{}:{}",
+            name, thumbprint
+        )
+    });
+    Ok(JwkThumbprint { thumbprint, uri })
+}
+
+/// RFC 7638 thumbprint over the required members in lexicographic order.
+pub(crate) fn jwk_thumbprint_inner(
+    jwk: &serde_json::Value,
+    digest: Digest,
+) -> Result<String> {
+    let members: &[&str] = match jwk["kty"].as_str() {
+        Some("RSA") => &["e", "kty", "n"],
+        Some("EC") => &["crv", "kty", "x", "y"],
+        Some("OKP") => &["crv", "kty", "x"],
+        Some("oct") => &["k", "kty"],
+        _ => return Err(Error::Unsupported(format!("jwk kty {}", jwk["kty"]))),
+    };
+    let mut canonical = Vec::with_capacity(members.len());
+    for name in members {
+        let value = jwk[name]
+            .as_str()
+            .ok_or(Error::Unsupported(format!("jwk without `{}`", name)))?;
+        canonical.push(format!(
+            "\"{}\":{}",
+            name,
+            serde_json::to_string(value).context("jwk member failed")?
+        ));
+    }
+    let mut hasher = digest.as_digest();
+    hasher.update(format!("{{{}}}", canonical.join(",")).as_bytes());
+    base64_encode(&hasher.finalize(), true, true)
+}
+
+fn jwk_with_params(
+    mut value: serde_json::Value,
+    key_id: Option<String>,
+    algorithm: Option<JwkeyAlgorithm>,
+    usage: Option<JwkeyUsage>,
+    operations: Option<Vec<JwkeyOperation>>,
+) -> Result<String> {
+    if let Some(key_id) = key_id {
         value["kid"] = serde_json::Value::String(key_id);
     }
-    if let Some(alg) = data.algorithm {
+    if let Some(alg) = algorithm {
         value["alg"] = json!(alg);
     }
-    if let Some(ops) = data.operations
+    if let Some(ops) = operations
         && !ops.is_empty()
     {
         value["key_ops"] = json!(&ops);
     }
-    if let Some(usage) = data.usage {
+    if let Some(usage) = usage {
         value["use"] = serde_json::Value::String(usage.to_string())
     }
 
@@ -43,55 +354,59 @@ pub(crate) async fn generate_jwk(data: JwkGenerate) -> Result<String> {
 }
 
 pub(crate) async fn generate_jwk_inner(
-    algorithm: crate::jwt::JwkeyAlgorithm,
+    key_type: JwkeyType,
+    algorithm: Option<JwkeyAlgorithm>,
+    curve_name: Option<EccCurveName>,
+    bits: Option<RsaKeySize>,
 ) -> Result<serde_json::Value> {
     let mut rng = rand::thread_rng();
 
+    if let (JwkeyType::EcDSA, Some(curve_name)) = (key_type, curve_name) {
+        if let Some(alg) = algorithm
+            && ecc_curve_algorithm(curve_name) != Some(alg)
+        {
+            return Err(Error::Unsupported(format!(
+                "{:?} key with {:?}",
+                curve_name, alg
+            )));
+        }
+        return generate_ecc_jwk(curve_name);
+    }
+    let algorithm = algorithm.unwrap_or(key_type.default_algorithm());
+
     let key = match algorithm {
         JwkeyAlgorithm::Dir
         | JwkeyAlgorithm::HS256
+        | JwkeyAlgorithm::HS384
+        | JwkeyAlgorithm::HS512
         | JwkeyAlgorithm::A128GCM
-        | JwkeyAlgorithm::A128GCMKW
-        | JwkeyAlgorithm::A128KW
-        | JwkeyAlgorithm::A128cbcHs256 => {
-            let key = random_bytes(32)?;
-            jose_jwk::Key::Oct(jose_jwk::Oct { k: key.into() })
-        }
-        JwkeyAlgorithm::HS384
         | JwkeyAlgorithm::A192GCM
-        | JwkeyAlgorithm::A192GCMKW
-        | JwkeyAlgorithm::A192KW
-        | JwkeyAlgorithm::A192cbcHs384 => {
-            let key = random_bytes(48)?;
-            jose_jwk::Key::Oct(jose_jwk::Oct { k: key.into() })
-        }
-        JwkeyAlgorithm::HS512
         | JwkeyAlgorithm::A256GCM
+        | JwkeyAlgorithm::A128GCMKW
+        | JwkeyAlgorithm::A192GCMKW
         | JwkeyAlgorithm::A256GCMKW
+        | JwkeyAlgorithm::A128KW
+        | JwkeyAlgorithm::A192KW
         | JwkeyAlgorithm::A256KW
+        | JwkeyAlgorithm::A128cbcHs256
+        | JwkeyAlgorithm::A192cbcHs384
         | JwkeyAlgorithm::A256cbcHs512 => {
-            let key = random_bytes(64)?;
+            // a direct key is sized for A256GCM and A128CBC-HS256
+            let key =
+                random_bytes(algorithm.symmetric_key_length().unwrap_or(32))?;
             jose_jwk::Key::Oct(jose_jwk::Oct { k: key.into() })
         }
         JwkeyAlgorithm::ES256 => {
-            let secret_key =
-                elliptic_curve::SecretKey::<p256::NistP256>::random(&mut rng);
-            jose_jwk::Key::Ec(jose_jwk::Ec::from(secret_key))
+            return generate_ecc_jwk(EccCurveName::NistP256);
         }
         JwkeyAlgorithm::ES384 => {
-            let secret_key =
-                elliptic_curve::SecretKey::<p384::NistP384>::random(&mut rng);
-            jose_jwk::Key::Ec(jose_jwk::Ec::from(secret_key))
+            return generate_ecc_jwk(EccCurveName::NistP384);
         }
         JwkeyAlgorithm::ES521 => {
-            let secret_key =
-                elliptic_curve::SecretKey::<p521::NistP521>::random(&mut rng);
-            jose_jwk::Key::Ec(jose_jwk::Ec::from(secret_key))
+            return generate_ecc_jwk(EccCurveName::NistP521);
         }
         JwkeyAlgorithm::ES256K => {
-            let secret_key =
-                elliptic_curve::SecretKey::<k256::Secp256k1>::random(&mut rng);
-            jose_jwk::Key::Ec(jose_jwk::Ec::from(secret_key))
+            return generate_ecc_jwk(EccCurveName::Secp256k1);
         }
         JwkeyAlgorithm::RS256
         | JwkeyAlgorithm::PS256
@@ -104,9 +419,11 @@ pub(crate) async fn generate_jwk_inner(
         | JwkeyAlgorithm::RsaOaep256
         | JwkeyAlgorithm::RsaOaep384
         | JwkeyAlgorithm::RsaOaep521 => {
+            let key_size = bits.unwrap_or(RsaKeySize::Rsa2048);
             let private_key =
-                RsaPrivateKey::new(&mut rng, RsaKeySize::Rsa2048 as usize)
-                    .context("generate rsa 2048 key failed")?;
+                RsaPrivateKey::new(&mut rng, key_size as usize).context(
+                    format!("generate rsa {:?} key failed", key_size),
+                )?;
             jose_jwk::Key::Rsa(jose_jwk::Rsa::from(private_key))
         }
 
@@ -136,6 +453,887 @@ pub(crate) async fn generate_jwk_inner(
     Ok(serde_json::to_value(&key).context("serilize jwk failed")?)
 }
 
+fn generate_ecc_jwk(curve_name: EccCurveName) -> Result<serde_json::Value> {
+    let mut rng = rand::thread_rng();
+    let crv = ecc_jwk_curve(curve_name);
+    match curve_name {
+        EccCurveName::NistP256 => ecc_private_jwk(
+            &elliptic_curve::SecretKey::<p256::NistP256>::random(&mut rng),
+            crv,
+        ),
+        EccCurveName::NistP384 => ecc_private_jwk(
+            &elliptic_curve::SecretKey::<p384::NistP384>::random(&mut rng),
+            crv,
+        ),
+        EccCurveName::NistP521 => ecc_private_jwk(
+            &elliptic_curve::SecretKey::<p521::NistP521>::random(&mut rng),
+            crv,
+        ),
+        EccCurveName::Secp256k1 => ecc_private_jwk(
+            &elliptic_curve::SecretKey::<k256::Secp256k1>::random(&mut rng),
+            crv,
+        ),
+        EccCurveName::SM2 => ecc_private_jwk(
+            &elliptic_curve::SecretKey::<sm2::Sm2>::random(&mut rng),
+            crv,
+        ),
+    }
+}
+
+/// Key material handed to the JOSE commands, either as a JWK, a PEM/DER key
+/// or a raw symmetric secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JoseKey {
+    Jwk {
+        key: String,
+    },
+    Pkcs {
+        key: String,
+        pkcs: Pkcs,
+        format: KeyFormat,
+        encoding: TextEncoding,
+    },
+    Secret {
+        key: String,
+        encoding: TextEncoding,
+    },
+}
+
+impl JoseKey {
+    pub(crate) fn jwk(&self) -> Result<Option<serde_json::Value>> {
+        Ok(match self {
+            JoseKey::Jwk { key } => Some(parse_jwk(key)?),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn secret(&self) -> Result<Vec<u8>> {
+        match self {
+            JoseKey::Jwk { key } => {
+                let jwk = parse_jwk(key)?;
+                check_key_type(&jwk, "oct")?;
+                jwk_param(&jwk, "k")
+            }
+            JoseKey::Secret { key, encoding } => encoding.decode(key),
+            JoseKey::Pkcs { .. } => Err(Error::Unsupported(
+                "pkcs key as symmetric secret".to_string(),
+            )),
+        }
+    }
+
+    pub(crate) fn rsa_private_key(&self) -> Result<RsaPrivateKey> {
+        match self {
+            JoseKey::Jwk { key } => jwk_to_rsa_private_key(&parse_jwk(key)?),
+            JoseKey::Pkcs {
+                key,
+                pkcs,
+                format,
+                encoding,
+            } => rsa_key::bytes_to_private_key(
+                &encoding.decode(key)?,
+                *pkcs,
+                *format,
+            ),
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as rsa key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn rsa_public_key(&self) -> Result<RsaPublicKey> {
+        match self {
+            JoseKey::Jwk { key } => jwk_to_rsa_public_key(&parse_jwk(key)?),
+            JoseKey::Pkcs {
+                key,
+                pkcs,
+                format,
+                encoding,
+            } => {
+                let key_bytes = encoding.decode(key)?;
+                rsa_key::bytes_to_public_key(&key_bytes, *pkcs, *format)
+                    .or_else(|_| {
+                        rsa_key::bytes_to_private_key(
+                            &key_bytes, *pkcs, *format,
+                        )
+                        .map(|key| key.to_public_key())
+                    })
+            }
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as rsa key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn ecc_secret_key<C>(
+        &self,
+        crv: &str,
+    ) -> Result<elliptic_curve::SecretKey<C>>
+    where
+        C: CurveArithmetic + pkcs8::AssociatedOid,
+        AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+        FieldBytesSize<C>: ModulusSize,
+    {
+        match self {
+            JoseKey::Jwk { key } => {
+                jwk_to_ecc_secret_key::<C>(&parse_jwk(key)?, crv)
+            }
+            JoseKey::Pkcs {
+                key,
+                pkcs,
+                format,
+                encoding,
+            } => ecc::key::import_ecc_private_key::<C>(
+                &encoding.decode(key)?,
+                *pkcs,
+                *format,
+            ),
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as ecc key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn ecc_public_key<C>(
+        &self,
+        crv: &str,
+    ) -> Result<elliptic_curve::PublicKey<C>>
+    where
+        C: CurveArithmetic + pkcs8::AssociatedOid,
+        AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+        FieldBytesSize<C>: ModulusSize,
+    {
+        match self {
+            JoseKey::Jwk { key } => {
+                jwk_to_ecc_public_key::<C>(&parse_jwk(key)?, crv)
+            }
+            JoseKey::Pkcs {
+                key,
+                pkcs,
+                format,
+                encoding,
+            } => {
+                let key_bytes = encoding.decode(key)?;
+                ecc::key::import_ecc_public_key::<C>(&key_bytes, *format)
+                    .or_else(|_| {
+                        ecc::key::import_ecc_private_key::<C>(
+                            &key_bytes, *pkcs, *format,
+                        )
+                        .map(|key| key.public_key())
+                    })
+            }
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as ecc key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn ed25519_signing_key(
+        &self,
+    ) -> Result<ed25519_dalek::SigningKey> {
+        match self {
+            JoseKey::Jwk { key } => {
+                jwk_to_ed25519_signing_key(&parse_jwk(key)?)
+            }
+            JoseKey::Pkcs {
+                key,
+                format,
+                encoding,
+                ..
+            } => edwards::key::import_curve_25519_private_key(
+                &encoding.decode(key)?,
+                *format,
+            ),
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as ed25519 key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn ed25519_verifying_key(
+        &self,
+    ) -> Result<ed25519_dalek::VerifyingKey> {
+        match self {
+            JoseKey::Jwk { key } => {
+                jwk_to_ed25519_verifying_key(&parse_jwk(key)?)
+            }
+            JoseKey::Pkcs {
+                key,
+                format,
+                encoding,
+                ..
+            } => {
+                let key_bytes = encoding.decode(key)?;
+                edwards::key::import_curve_25519_public_key(&key_bytes, *format)
+                    .or_else(|_| {
+                        edwards::key::import_curve_25519_private_key(
+                            &key_bytes, *format,
+                        )
+                        .map(|key| key.verifying_key())
+                    })
+            }
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as ed25519 key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn x25519_public_key(&self) -> Result<x25519_dalek::PublicKey> {
+        match self {
+            JoseKey::Jwk { key } => jwk_to_x25519_public_key(&parse_jwk(key)?),
+            JoseKey::Pkcs {
+                key,
+                format,
+                encoding,
+                ..
+            } => {
+                let key_bytes = encoding.decode(key)?;
+                edwards::key::import_x25519_public_key(&key_bytes, *format)
+                    .or_else(|_| {
+                        edwards::key::import_x25519_private_key(
+                            &key_bytes, *format,
+                        )
+                        .map(|secret| x25519_dalek::PublicKey::from(&secret))
+                    })
+                    .or_else(|_| {
+                        let verifying_key = self.ed25519_verifying_key()?;
+                        Ok(x25519_dalek::PublicKey::from(
+                            verifying_key.to_montgomery().to_bytes(),
+                        ))
+                    })
+            }
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as x25519 key".to_string()))
+            }
+        }
+    }
+
+    pub(crate) fn x25519_secret(&self) -> Result<x25519_dalek::StaticSecret> {
+        match self {
+            JoseKey::Jwk { key } => jwk_to_x25519_secret(&parse_jwk(key)?),
+            JoseKey::Pkcs {
+                key,
+                format,
+                encoding,
+                ..
+            } => edwards::key::import_x25519_private_key(
+                &encoding.decode(key)?,
+                *format,
+            )
+            .or_else(|_| {
+                let signing_key = self.ed25519_signing_key()?;
+                Ok(x25519_dalek::StaticSecret::from(
+                    signing_key.to_scalar_bytes(),
+                ))
+            }),
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as x25519 key".to_string()))
+            }
+        }
+    }
+
+    /// The JWK `crv` of a key used for ECDH-ES, PEM/DER keys are probed
+    /// against every supported curve.
+    pub(crate) fn ecdh_curve(&self) -> Result<String> {
+        match self {
+            JoseKey::Jwk { key } => parse_jwk(key)?["crv"]
+                .as_str()
+                .map(|crv| crv.to_string())
+                .ok_or(Error::Unsupported("jwk without `crv`".to_string())),
+            JoseKey::Pkcs { .. } => Ok(if self
+                .ecc_public_key::<p256::NistP256>("P-256")
+                .is_ok()
+            {
+                "P-256"
+            } else if self.ecc_public_key::<p384::NistP384>("P-384").is_ok() {
+                "P-384"
+            } else if self.ecc_public_key::<p521::NistP521>("P-521").is_ok() {
+                "P-521"
+            } else if self.x25519_public_key().is_ok() {
+                "X25519"
+            } else {
+                return Err(Error::Unsupported("ecdh key content".to_string()));
+            }
+            .to_string()),
+            JoseKey::Secret { .. } => {
+                Err(Error::Unsupported("secret as ecdh key".to_string()))
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_jwk(input: &str) -> Result<serde_json::Value> {
+    let jwk: serde_json::Value =
+        serde_json::from_str(input).context("invalid jwk json")?;
+    if !jwk.is_object() {
+        return Err(Error::Unsupported("jwk is not an object".to_string()));
+    }
+    Ok(jwk)
+}
+
+pub(crate) fn jwk_param(
+    jwk: &serde_json::Value,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let value = jwk[name]
+        .as_str()
+        .ok_or(Error::Unsupported(format!("jwk without `{}`", name)))?;
+    base64_decode(value, true, true)
+}
+
+fn check_key_type(jwk: &serde_json::Value, kty: &str) -> Result<()> {
+    if jwk["kty"].as_str() != Some(kty) {
+        return Err(Error::Unsupported(format!(
+            "jwk kty {} for {}",
+            jwk["kty"], kty
+        )));
+    }
+    Ok(())
+}
+
+fn check_curve(jwk: &serde_json::Value, crv: &str) -> Result<()> {
+    if jwk["crv"].as_str() != Some(crv) {
+        return Err(Error::Unsupported(format!(
+            "jwk crv {} for {}",
+            jwk["crv"], crv
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn jwk_to_rsa_public_key(
+    jwk: &serde_json::Value,
+) -> Result<RsaPublicKey> {
+    check_key_type(jwk, "RSA")?;
+    let n = BigUint::from_bytes_be(&jwk_param(jwk, "n")?);
+    let e = BigUint::from_bytes_be(&jwk_param(jwk, "e")?);
+    Ok(RsaPublicKey::new(n, e).context("invalid rsa jwk public key")?)
+}
+
+pub(crate) fn jwk_to_rsa_private_key(
+    jwk: &serde_json::Value,
+) -> Result<RsaPrivateKey> {
+    check_key_type(jwk, "RSA")?;
+    let n = BigUint::from_bytes_be(&jwk_param(jwk, "n")?);
+    let e = BigUint::from_bytes_be(&jwk_param(jwk, "e")?);
+    let d = BigUint::from_bytes_be(&jwk_param(jwk, "d")?);
+    let primes = match (jwk_param(jwk, "p"), jwk_param(jwk, "q")) {
+        (Ok(p), Ok(q)) => {
+            vec![BigUint::from_bytes_be(&p), BigUint::from_bytes_be(&q)]
+        }
+        _ => vec![],
+    };
+    Ok(RsaPrivateKey::from_components(n, e, d, primes)
+        .context("invalid rsa jwk private key")?)
+}
+
+pub(crate) fn jwk_to_ecc_public_key<C>(
+    jwk: &serde_json::Value,
+    crv: &str,
+) -> Result<elliptic_curve::PublicKey<C>>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    check_key_type(jwk, "EC")?;
+    check_curve(jwk, crv)?;
+    let mut point = vec![0x04];
+    point.extend_from_slice(&jwk_param(jwk, "x")?);
+    point.extend_from_slice(&jwk_param(jwk, "y")?);
+    Ok(elliptic_curve::PublicKey::<C>::from_sec1_bytes(&point)
+        .context("invalid ecc jwk public key")?)
+}
+
+pub(crate) fn jwk_to_ecc_secret_key<C>(
+    jwk: &serde_json::Value,
+    crv: &str,
+) -> Result<elliptic_curve::SecretKey<C>>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    check_key_type(jwk, "EC")?;
+    check_curve(jwk, crv)?;
+    Ok(
+        elliptic_curve::SecretKey::<C>::from_slice(&jwk_param(jwk, "d")?)
+            .context("invalid ecc jwk private key")?,
+    )
+}
+
+pub(crate) fn jwk_to_ed25519_verifying_key(
+    jwk: &serde_json::Value,
+) -> Result<ed25519_dalek::VerifyingKey> {
+    check_key_type(jwk, "OKP")?;
+    check_curve(jwk, "Ed25519")?;
+    let x: [u8; 32] = jwk_param(jwk, "x")?
+        .try_into()
+        .map_err(|_| Error::Unsupported("ed25519 x length".to_string()))?;
+    Ok(ed25519_dalek::VerifyingKey::from_bytes(&x)
+        .context("invalid ed25519 jwk public key")?)
+}
+
+pub(crate) fn jwk_to_ed25519_signing_key(
+    jwk: &serde_json::Value,
+) -> Result<ed25519_dalek::SigningKey> {
+    check_key_type(jwk, "OKP")?;
+    check_curve(jwk, "Ed25519")?;
+    let d: [u8; 32] = jwk_param(jwk, "d")?
+        .try_into()
+        .map_err(|_| Error::Unsupported("ed25519 d length".to_string()))?;
+    Ok(ed25519_dalek::SigningKey::from_bytes(&d))
+}
+
+pub(crate) fn jwk_to_x25519_public_key(
+    jwk: &serde_json::Value,
+) -> Result<x25519_dalek::PublicKey> {
+    check_key_type(jwk, "OKP")?;
+    check_curve(jwk, "X25519")?;
+    let x: [u8; 32] = jwk_param(jwk, "x")?
+        .try_into()
+        .map_err(|_| Error::Unsupported("x25519 x length".to_string()))?;
+    Ok(x25519_dalek::PublicKey::from(x))
+}
+
+pub(crate) fn jwk_to_x25519_secret(
+    jwk: &serde_json::Value,
+) -> Result<x25519_dalek::StaticSecret> {
+    check_key_type(jwk, "OKP")?;
+    check_curve(jwk, "X25519")?;
+    let d: [u8; 32] = jwk_param(jwk, "d")?
+        .try_into()
+        .map_err(|_| Error::Unsupported("x25519 d length".to_string()))?;
+    Ok(x25519_dalek::StaticSecret::from(d))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwksFilter {
+    pub jwks: String,
+    pub usage: Option<JwkeyUsage>,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    pub key_type: Option<JwkeyType>,
+    pub public_only: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwksProblem {
+    pub index: usize,
+    pub kid: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwksValidation {
+    pub valid: bool,
+    pub problems: Vec<JwksProblem>,
+}
+
+/// Assembles jwks from single jwks or whole jwks documents.
+#[tauri::command]
+pub(crate) fn build_jwks(keys: Vec<String>) -> Result<String> {
+    info!("build jwks: {}", keys.len());
+    let mut jwks = Vec::new();
+    for key in keys {
+        jwks.extend(parse_jwks(&key)?);
+    }
+    jwks_to_string(jwks)
+}
+
+#[tauri::command]
+pub(crate) fn add_jwks_key(jwks: String, key: String) -> Result<String> {
+    let mut keys = parse_jwks(&jwks)?;
+    for jwk in parse_jwks(&key)? {
+        if let Some(kid) = jwk["kid"].as_str()
+            && keys.iter().any(|key| key["kid"].as_str() == Some(kid))
+        {
+            return Err(Error::Unsupported(format!("duplicate kid {}", kid)));
+        }
+        keys.push(jwk);
+    }
+    jwks_to_string(keys)
+}
+
+#[tauri::command]
+pub(crate) fn remove_jwks_key(jwks: String, kid: String) -> Result<String> {
+    let mut keys = parse_jwks(&jwks)?;
+    let size = keys.len();
+    keys.retain(|key| key["kid"].as_str() != Some(kid.as_str()));
+    if keys.len() == size {
+        return Err(Error::Unsupported(format!("jwks without kid {}", kid)));
+    }
+    jwks_to_string(keys)
+}
+
+#[tauri::command]
+pub(crate) fn filter_jwks(data: JwksFilter) -> Result<String> {
+    info!(
+        "filter jwks, usage: {:?}, algorithm: {:?}, key_type: {:?}, \
+         public_only: {}",
+        data.usage, data.algorithm, data.key_type, data.public_only
+    );
+    let mut keys = Vec::new();
+    for jwk in parse_jwks(&data.jwks)? {
+        if data.usage.is_some_and(|usage| {
+            jwk["use"].as_str() != Some(&usage.to_string())
+        }) || data.algorithm.is_some_and(|alg| jwk["alg"] != json!(alg))
+            || data
+                .key_type
+                .is_some_and(|kty| jwk_key_type(&jwk).ok() != Some(kty))
+        {
+            continue;
+        }
+        if data.public_only {
+            if jwk["kty"].as_str() == Some("oct") {
+                continue;
+            }
+            keys.push(public_jwk(&jwk)?);
+        } else {
+            keys.push(jwk);
+        }
+    }
+    jwks_to_string(keys)
+}
+
+#[tauri::command]
+pub(crate) fn validate_jwks(jwks: String) -> Result<JwksValidation> {
+    let keys = parse_jwks(&jwks)?;
+    let mut problems = Vec::new();
+    for (index, jwk) in keys.iter().enumerate() {
+        let kid = jwk["kid"].as_str().map(|kid| kid.to_string());
+        let mut problem = |message: String| {
+            problems.push(JwksProblem {
+                index,
+                kid: kid.clone(),
+                message,
+            })
+        };
+        // kid is optional, only duplicates are ambiguous
+        if let Some(kid) = kid.as_ref()
+            && keys[.. index]
+                .iter()
+                .any(|key| key["kid"].as_str() == Some(kid))
+        {
+            problem(format!("duplicate kid {}", kid));
+        }
+        let kty = match jwk_key_type(jwk) {
+            Ok(kty) => kty,
+            Err(err) => {
+                problem(err.to_string());
+                continue;
+            }
+        };
+        let alg = match jwk.get("alg") {
+            Some(alg) => {
+                match serde_json::from_value::<JwkeyAlgorithm>(alg.clone()) {
+                    Ok(alg) => {
+                        if !jwk_algorithms(kty).contains(&alg) {
+                            problem(format!("alg {:?} for {:?} key", alg, kty));
+                        }
+                        Some(alg)
+                    }
+                    Err(_) => {
+                        problem(format!("unsupported alg {}", alg));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let usage = match jwk["use"].as_str() {
+            Some(usage) => {
+                match JwkeyUsage::iter().find(|u| u.to_string() == usage) {
+                    Some(usage) => {
+                        if !jwk_usages(kty).contains(&usage) {
+                            problem(format!("use {} for {:?} key", usage, kty));
+                        }
+                        if let Some(alg) = alg
+                            && alg.is_signature()
+                                != (usage == JwkeyUsage::Signature)
+                        {
+                            problem(format!("use {} for alg {:?}", usage, alg));
+                        }
+                        Some(usage)
+                    }
+                    None => {
+                        problem(format!("unsupported use {}", usage));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        if let Some(ops) = jwk.get("key_ops") {
+            match serde_json::from_value::<Vec<JwkeyOperation>>(ops.clone()) {
+                Ok(ops) => {
+                    let signature = alg
+                        .map(|alg| alg.is_signature())
+                        .or(usage.map(|usage| usage == JwkeyUsage::Signature));
+                    for op in ops {
+                        let sign = matches!(
+                            op,
+                            JwkeyOperation::Sign | JwkeyOperation::Verify
+                        );
+                        if signature.is_some_and(|signature| signature != sign)
+                        {
+                            problem(format!("key_ops {:?}", op));
+                        }
+                    }
+                }
+                Err(_) => problem(format!("unsupported key_ops {}", ops)),
+            }
+        }
+    }
+    Ok(JwksValidation {
+        valid: problems.is_empty(),
+        problems,
+    })
+}
+
+// the algorithms a jwk may declare, ec keys also serve ECDH-ES key agreement
+// although they are only generated for signing
+fn jwk_algorithms(kty: JwkeyType) -> Vec<JwkeyAlgorithm> {
+    match kty {
+        JwkeyType::EcDSA => [
+            jwkey_algorithm(JwkeyType::EcDSA),
+            jwkey_algorithm(JwkeyType::X25519),
+        ]
+        .concat(),
+        kty => jwkey_algorithm(kty),
+    }
+}
+
+fn jwk_usages(kty: JwkeyType) -> Vec<JwkeyUsage> {
+    match kty {
+        JwkeyType::EcDSA => vec![JwkeyUsage::Encryption, JwkeyUsage::Signature],
+        kty => jwkey_usage(kty),
+    }
+}
+
+/// Reads either a single jwk or a `{"keys":[...]}` document.
+pub(crate) fn parse_jwks(input: &str) -> Result<Vec<serde_json::Value>> {
+    let value = parse_jwk(input)?;
+    match value.get("keys") {
+        Some(serde_json::Value::Array(keys)) => {
+            if keys.iter().any(|key| !key.is_object()) {
+                return Err(Error::Unsupported(
+                    "jwks key is not an object".to_string(),
+                ));
+            }
+            Ok(keys.clone())
+        }
+        Some(_) => {
+            Err(Error::Unsupported("jwks keys is not an array".to_string()))
+        }
+        None => Ok(vec![value]),
+    }
+}
+
+fn jwks_to_string(keys: Vec<serde_json::Value>) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&json!({ "keys": keys }))
+        .context("jwks to string failed")?)
+}
+
+pub(crate) fn jwk_key_type(jwk: &serde_json::Value) -> Result<JwkeyType> {
+    Ok(match (jwk["kty"].as_str(), jwk["crv"].as_str()) {
+        (Some("RSA"), _) => JwkeyType::RSA,
+        (Some("EC"), _) => JwkeyType::EcDSA,
+        (Some("OKP"), Some("Ed25519")) => JwkeyType::Ed25519,
+        (Some("OKP"), Some("X25519")) => JwkeyType::X25519,
+        (Some("oct"), _) => JwkeyType::Symmetric,
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "jwk kty {} crv {}",
+                jwk["kty"], jwk["crv"]
+            )))
+        }
+    })
+}
+
+/// Drops the private members of an asymmetric jwk.
+pub(crate) fn public_jwk(jwk: &serde_json::Value) -> Result<serde_json::Value> {
+    if jwk["kty"].as_str() == Some("oct") {
+        return Err(Error::Unsupported(
+            "public part of a symmetric jwk".to_string(),
+        ));
+    }
+    let mut public = jwk.clone();
+    if let Some(members) = public.as_object_mut() {
+        for name in ["d", "p", "q", "dp", "dq", "qi", "oth"] {
+            members.remove(name);
+        }
+    }
+    Ok(public)
+}
+
+pub(crate) fn ecc_jwk_curve(curve_name: EccCurveName) -> &'static str {
+    match curve_name {
+        EccCurveName::NistP256 => "P-256",
+        EccCurveName::NistP384 => "P-384",
+        EccCurveName::NistP521 => "P-521",
+        EccCurveName::Secp256k1 => "secp256k1",
+        EccCurveName::SM2 => "SM2",
+    }
+}
+
+pub(crate) fn ecc_curve_algorithm(
+    curve_name: EccCurveName,
+) -> Option<JwkeyAlgorithm> {
+    match curve_name {
+        EccCurveName::NistP256 => Some(JwkeyAlgorithm::ES256),
+        EccCurveName::NistP384 => Some(JwkeyAlgorithm::ES384),
+        EccCurveName::NistP521 => Some(JwkeyAlgorithm::ES521),
+        EccCurveName::Secp256k1 => Some(JwkeyAlgorithm::ES256K),
+        EccCurveName::SM2 => None,
+    }
+}
+
+fn rsa_public_pkcs(pkcs: Pkcs) -> Pkcs {
+    match pkcs {
+        Pkcs::Pkcs1 => Pkcs::Pkcs1,
+        _ => Pkcs::Pkcs8,
+    }
+}
+
+fn jwk_uint(value: &BigUint) -> Result<serde_json::Value> {
+    Ok(serde_json::Value::String(base64_encode(
+        &value.to_bytes_be(),
+        true,
+        true,
+    )?))
+}
+
+fn rsa_public_jwk(public_key: &RsaPublicKey) -> Result<serde_json::Value> {
+    Ok(json!({
+        "kty": "RSA",
+        "n": jwk_uint(public_key.n())?,
+        "e": jwk_uint(public_key.e())?,
+    }))
+}
+
+fn rsa_private_jwk(private_key: &RsaPrivateKey) -> Result<serde_json::Value> {
+    let mut jwk = rsa_public_jwk(&private_key.to_public_key())?;
+    jwk["d"] = jwk_uint(private_key.d())?;
+    if let [p, q] = private_key.primes() {
+        jwk["p"] = jwk_uint(p)?;
+        jwk["q"] = jwk_uint(q)?;
+        if let (Some(dp), Some(dq), Some(qi)) = (
+            private_key.dp(),
+            private_key.dq(),
+            private_key.crt_coefficient(),
+        ) {
+            jwk["dp"] = jwk_uint(dp)?;
+            jwk["dq"] = jwk_uint(dq)?;
+            jwk["qi"] = jwk_uint(&qi)?;
+        }
+    }
+    Ok(jwk)
+}
+
+fn ecc_public_jwk<C>(
+    public_key: &elliptic_curve::PublicKey<C>,
+    crv: &str,
+) -> Result<serde_json::Value>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let point = public_key.to_encoded_point(false);
+    let coordinate = |value: Option<&elliptic_curve::FieldBytes<C>>| {
+        base64_encode(
+            value.map(|v| v.as_slice()).unwrap_or_default(),
+            true,
+            true,
+        )
+    };
+    Ok(json!({
+        "kty": "EC",
+        "crv": crv,
+        "x": coordinate(point.x())?,
+        "y": coordinate(point.y())?,
+    }))
+}
+
+fn ecc_private_jwk<C>(
+    secret_key: &elliptic_curve::SecretKey<C>,
+    crv: &str,
+) -> Result<serde_json::Value>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let mut jwk = ecc_public_jwk(&secret_key.public_key(), crv)?;
+    jwk["d"] = serde_json::Value::String(base64_encode(
+        &secret_key.to_bytes(),
+        true,
+        true,
+    )?);
+    Ok(jwk)
+}
+
+fn import_ecc_jwk<C>(
+    input: &[u8],
+    pkcs: Pkcs,
+    format: KeyFormat,
+    crv: &str,
+) -> Result<serde_json::Value>
+where
+    C: CurveArithmetic + pkcs8::AssociatedOid,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    match ecc::key::import_ecc_private_key::<C>(input, pkcs, format) {
+        Ok(secret_key) => ecc_private_jwk(&secret_key, crv),
+        Err(_) => ecc_public_jwk(
+            &ecc::key::import_ecc_public_key::<C>(input, format)?,
+            crv,
+        ),
+    }
+}
+
+fn export_ecc_jwk<C>(
+    jwk: &serde_json::Value,
+    crv: &str,
+    pkcs: Pkcs,
+    format: KeyFormat,
+) -> Result<(Option<Vec<u8>>, Vec<u8>)>
+where
+    C: CurveArithmetic + pkcs8::AssociatedOid,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+    elliptic_curve::PublicKey<C>: EncodePublicKey,
+{
+    let private_key = if jwk.get("d").is_some() {
+        Some(ecc::key::export_ecc_private_key(
+            &jwk_to_ecc_secret_key::<C>(jwk, crv)?,
+            pkcs,
+            format,
+        )?)
+    } else {
+        None
+    };
+    let public_key = ecc::key::export_ecc_public_key(
+        jwk_to_ecc_public_key::<C>(jwk, crv)?,
+        format,
+    )?;
+    Ok((private_key, public_key))
+}
+
+fn okp_jwk(crv: &str, x: &[u8], d: Option<&[u8]>) -> Result<serde_json::Value> {
+    let mut jwk = json!({
+        "kty": "OKP",
+        "crv": crv,
+        "x": base64_encode(x, true, true)?,
+    });
+    if let Some(d) = d {
+        jwk["d"] = serde_json::Value::String(base64_encode(d, true, true)?);
+    }
+    Ok(jwk)
+}
+
 #[cfg(test)]
 mod test {
     use num_bigint::BigInt;
@@ -145,12 +1343,21 @@ mod test {
 
     use super::JwkeyAlgorithm;
     use crate::{
-        enums::RsaKeySize,
+        crypto::{ecc, edwards, rsa},
+        enums::{
+            Digest, EccCurveName, EdwardsCurveName, KeyFormat, Pkcs,
+            RsaKeySize, TextEncoding,
+        },
         jwt::{
-            jwk::{generate_jwk, JwkGenerate},
-            JwkeyOperation, JwkeyType,
+            jwk::{
+                add_jwks_key, build_jwks, ecc_jwk_curve, export_jwk,
+                filter_jwks, generate_jwk, import_jwk, jwk_param,
+                jwk_thumbprint, parse_jwk, parse_jwks, remove_jwks_key,
+                validate_jwks, JwkExport, JwkGenerate, JwkImport, JwksFilter,
+            },
+            JwkeyOperation, JwkeyType, JwkeyUsage,
         },
-        utils::random_bytes,
+        utils::{jwkey_algorithm, random_bytes, KeyTuple},
     };
 
     #[tokio::test]
@@ -176,6 +1383,8 @@ mod test {
                         usage: None,
                         operations: Some(ops.clone()),
                         bits,
+                        curve_name: None,
+                        thumbprint: None,
                     })
                     .await
                     .unwrap()
@@ -191,4 +1400,340 @@ mod test {
             BigInt::from_bytes_be(num_bigint::Sign::Plus, &random_bytes);
         info!("output: {}", b_int.to_str_radix(36));
     }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_import_and_export_jwk() {
+        let secret =
+            x25519_dalek::StaticSecret::random_from_rng(rand::thread_rng());
+        let x25519 = KeyTuple::new(
+            String::from_utf8(
+                edwards::key::export_x25519_private_key(
+                    &secret,
+                    KeyFormat::Pem,
+                )
+                .unwrap(),
+            )
+            .unwrap(),
+            String::from_utf8(
+                edwards::key::export_x25519_public_key(
+                    &x25519_dalek::PublicKey::from(&secret),
+                    KeyFormat::Pem,
+                )
+                .unwrap(),
+            )
+            .unwrap(),
+        );
+        let mut keys = vec![
+            (
+                JwkeyType::RSA,
+                None,
+                Pkcs::Pkcs1,
+                rsa::key::generate_rsa(
+                    RsaKeySize::Rsa2048,
+                    Pkcs::Pkcs1,
+                    KeyFormat::Pem,
+                    TextEncoding::Utf8,
+                )
+                .await
+                .unwrap(),
+            ),
+            (
+                JwkeyType::Ed25519,
+                None,
+                Pkcs::Pkcs8,
+                edwards::key::generate_edwards(
+                    EdwardsCurveName::Curve25519,
+                    KeyFormat::Pem,
+                    TextEncoding::Utf8,
+                )
+                .await
+                .unwrap(),
+            ),
+            (JwkeyType::X25519, None, Pkcs::Pkcs8, x25519),
+        ];
+        for curve_name in EccCurveName::iter() {
+            keys.push((
+                JwkeyType::EcDSA,
+                Some(curve_name),
+                Pkcs::Sec1,
+                ecc::key::generate_ecc(
+                    curve_name,
+                    Pkcs::Sec1,
+                    KeyFormat::Pem,
+                    TextEncoding::Utf8,
+                )
+                .await
+                .unwrap(),
+            ));
+        }
+        for (key_type, curve_name, pkcs, KeyTuple(private_key, public_key)) in
+            keys
+        {
+            info!("import jwk: {:?}, {:?}", key_type, curve_name);
+            let import = |key: &Option<String>, public_only: bool| {
+                import_jwk(JwkImport {
+                    key: key.clone().unwrap(),
+                    key_type,
+                    curve_name,
+                    pkcs,
+                    format: KeyFormat::Pem,
+                    encoding: TextEncoding::Utf8,
+                    public_only,
+                    key_id: Some("kits".to_string()),
+                    algorithm: None,
+                    usage: None,
+                    operations: None,
+                })
+                .unwrap()
+            };
+            let private_jwk = import(&private_key, false);
+            let public_jwk = import(&private_key, true);
+            assert_eq!(public_jwk, import(&public_key, false));
+            assert!(!public_jwk.contains("\"d\""));
+
+            let export = |jwk: String| {
+                export_jwk(JwkExport {
+                    key: jwk,
+                    pkcs,
+                    format: KeyFormat::Pem,
+                    encoding: TextEncoding::Utf8,
+                })
+                .unwrap()
+            };
+            let KeyTuple(exported_private, exported_public) =
+                export(private_jwk);
+            assert_eq!(exported_private, private_key);
+            assert_eq!(exported_public, public_key);
+            let KeyTuple(exported_private, exported_public) =
+                export(public_jwk);
+            assert!(exported_private.is_none());
+            assert_eq!(exported_public, public_key);
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_jwks() {
+        let mut keys = Vec::new();
+        for (kid, key_type, algorithm, usage) in [
+            (
+                "ec",
+                JwkeyType::EcDSA,
+                JwkeyAlgorithm::ES256,
+                JwkeyUsage::Signature,
+            ),
+            (
+                "rsa",
+                JwkeyType::RSA,
+                JwkeyAlgorithm::RsaOaep,
+                JwkeyUsage::Encryption,
+            ),
+            (
+                "oct",
+                JwkeyType::Symmetric,
+                JwkeyAlgorithm::HS256,
+                JwkeyUsage::Signature,
+            ),
+        ] {
+            keys.push(
+                generate_jwk(JwkGenerate {
+                    key_id: Some(kid.to_string()),
+                    key_type,
+                    algorithm: Some(algorithm),
+                    usage: Some(usage),
+                    operations: None,
+                    bits: None,
+                    curve_name: None,
+                    thumbprint: None,
+                })
+                .await
+                .unwrap(),
+            );
+        }
+        let okp = generate_jwk(JwkGenerate {
+            key_id: Some("okp".to_string()),
+            key_type: JwkeyType::Ed25519,
+            algorithm: Some(JwkeyAlgorithm::EdDSA),
+            usage: Some(JwkeyUsage::Signature),
+            operations: Some(vec![
+                JwkeyOperation::Sign,
+                JwkeyOperation::Verify,
+            ]),
+            bits: None,
+            curve_name: None,
+            thumbprint: None,
+        })
+        .await
+        .unwrap();
+
+        let jwks = build_jwks(keys.clone()).unwrap();
+        assert_eq!(parse_jwks(&jwks).unwrap().len(), 3);
+        assert!(validate_jwks(jwks.clone()).unwrap().valid);
+        // RFC 7518 appendix C, an ec key agreement key without kid
+        let ecdh = r#"{"kty":"EC","crv":"P-256","alg":"ECDH-ES+A128KW","use":"enc","x":"weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ","y":"e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck"}"#;
+        let validation = validate_jwks(
+            build_jwks([keys.clone(), vec![ecdh.to_string()]].concat())
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(validation.valid, "{:?}", validation.problems);
+
+        let jwks = add_jwks_key(jwks, okp.clone()).unwrap();
+        assert_eq!(parse_jwks(&jwks).unwrap().len(), 4);
+        assert!(add_jwks_key(jwks.clone(), okp).is_err());
+
+        let filter = |jwks: &str, usage, algorithm, key_type, public_only| {
+            parse_jwks(
+                &filter_jwks(JwksFilter {
+                    jwks: jwks.to_string(),
+                    usage,
+                    algorithm,
+                    key_type,
+                    public_only,
+                })
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            filter(&jwks, Some(JwkeyUsage::Signature), None, None, false).len(),
+            3
+        );
+        assert_eq!(
+            filter(&jwks, None, Some(JwkeyAlgorithm::RsaOaep), None, false)[0]
+                ["kid"],
+            "rsa"
+        );
+        assert_eq!(
+            filter(&jwks, None, None, Some(JwkeyType::Ed25519), false)[0]
+                ["kid"],
+            "okp"
+        );
+        let public = filter(&jwks, None, None, None, true);
+        assert_eq!(public.len(), 3);
+        assert!(public.iter().all(|key| key.get("d").is_none()));
+
+        let jwks = remove_jwks_key(jwks, "okp".to_string()).unwrap();
+        assert_eq!(parse_jwks(&jwks).unwrap().len(), 3);
+        assert!(remove_jwks_key(jwks, "okp".to_string()).is_err());
+
+        let invalid = build_jwks(vec![
+            keys[0].clone(),
+            keys[0].clone(),
+            r#"{"kty":"EC","kid":"bad","alg":"RS256","use":"enc"}"#.to_string(),
+            r#"{"kty":"oct","k":"","use":"sig","key_ops":["encrypt"]}"#
+                .to_string(),
+        ])
+        .unwrap();
+        let validation = validate_jwks(invalid).unwrap();
+        assert!(!validation.valid);
+        for problem in validation.problems.iter() {
+            info!("{:?}", problem);
+        }
+        // duplicate kid, RS256 on an ec key, use enc for RS256 and key_ops
+        // encrypt for use sig
+        assert_eq!(validation.problems.len(), 4);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_jwk_thumbprint() {
+        // RFC 7638 section 3.1
+        let rsa = r#"{"kty":"RSA","n":"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw","e":"AQAB","alg":"RS256","kid":"2011-04-29"}"#;
+        let thumbprint =
+            jwk_thumbprint(rsa.to_string(), Digest::Sha256).unwrap();
+        assert_eq!(
+            thumbprint.thumbprint,
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+        // RFC 8037 appendix A.3 and RFC 9278 section 3
+        let okp = r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+        let thumbprint =
+            jwk_thumbprint(okp.to_string(), Digest::Sha256).unwrap();
+        assert_eq!(
+            thumbprint.uri.unwrap(),
+            "urn:ietf:params:oauth:jwk-thumbprint:sha-256:\
+             kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+        assert!(jwk_thumbprint(okp.to_string(), Digest::Sha1)
+            .unwrap()
+            .uri
+            .is_none());
+
+        for digest in Digest::iter() {
+            let jwk = parse_jwk(
+                &generate_jwk(JwkGenerate {
+                    key_id: None,
+                    key_type: JwkeyType::EcDSA,
+                    algorithm: None,
+                    usage: None,
+                    operations: None,
+                    bits: None,
+                    curve_name: None,
+                    thumbprint: Some(digest),
+                })
+                .await
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(
+                jwk["kid"].as_str().unwrap(),
+                jwk_thumbprint(jwk.to_string(), digest).unwrap().thumbprint
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_generate_jwk_key_length() {
+        let generate = |key_type, algorithm, curve_name, bits| async move {
+            generate_jwk(JwkGenerate {
+                key_id: None,
+                key_type,
+                algorithm,
+                usage: None,
+                operations: None,
+                bits,
+                curve_name,
+                thumbprint: None,
+            })
+            .await
+            .map(|jwk| parse_jwk(&jwk).unwrap())
+        };
+        for alg in jwkey_algorithm(JwkeyType::Symmetric) {
+            let jwk = generate(JwkeyType::Symmetric, Some(alg), None, None)
+                .await
+                .unwrap();
+            assert_eq!(
+                Some(jwk_param(&jwk, "k").unwrap().len()),
+                alg.symmetric_key_length().or(Some(32))
+            );
+        }
+        for bits in RsaKeySize::iter() {
+            let jwk = generate(
+                JwkeyType::RSA,
+                Some(JwkeyAlgorithm::PS256),
+                None,
+                Some(bits),
+            )
+            .await
+            .unwrap();
+            assert_eq!(jwk_param(&jwk, "n").unwrap().len() * 8, bits as usize);
+        }
+        for curve_name in EccCurveName::iter() {
+            let jwk = generate(JwkeyType::EcDSA, None, Some(curve_name), None)
+                .await
+                .unwrap();
+            assert_eq!(jwk["crv"], ecc_jwk_curve(curve_name));
+        }
+        assert!(generate(
+            JwkeyType::EcDSA,
+            Some(JwkeyAlgorithm::ES384),
+            Some(EccCurveName::NistP256),
+            None
+        )
+        .await
+        .is_err());
+    }
 }
@@ -1,6 +1,665 @@
-use crate::errors::Result;
+use anyhow::Context;
+use crypto_common::BlockSizeUser;
+use digest::{
+    block_buffer::Eager,
+    core_api::{BufferKindUser, CoreProxy, FixedOutputCore, UpdateCore},
+    generic_array::typenum::{IsLess, Le, NonZero, U256},
+    Digest, HashMarker,
+};
+use hkdf::hmac::{Hmac, Mac};
+use rsa::{Pkcs1v15Sign, Pss};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use signature::{Signer, Verifier};
+use tracing::{debug, info};
+
+use super::{jwk::JoseKey, JoseSerialization, JwkeyAlgorithm};
+use crate::{
+    codec::{base64_decode, base64_encode},
+    enums::TextEncoding,
+    errors::{Error, Result},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsSigner {
+    pub key: JoseKey,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    pub key_id: Option<String>,
+    // extra protected header parameters, as a json object
+    pub header: Option<String>,
+    // unprotected header parameters, ignored by the compact serialization
+    pub unprotected: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsDto {
+    pub payload: String,
+    pub payload_encoding: TextEncoding,
+    pub signers: Vec<JwsSigner>,
+    pub serialization: JoseSerialization,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsVerifyDto {
+    pub jws: String,
+    pub key: JoseKey,
+    pub algorithm: Option<JwkeyAlgorithm>,
+    pub output_encoding: TextEncoding,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JwsVerification {
+    pub header: Value,
+    pub payload: String,
+    pub valid: bool,
+}
+
+#[tauri::command]
+pub(crate) fn generate_jws(data: JwsDto) -> Result<String> {
+    info!(
+        "generate jws, serialization: {:?}, signers: {}",
+        data.serialization,
+        data.signers.len()
+    );
+    let payload = data.payload_encoding.decode(&data.payload)?;
+    generate_jws_inner(&payload, &data.signers, data.serialization)
+}
 
 #[tauri::command]
-pub(crate) fn generate_jws() -> Result<String> {
-    Ok("".to_string())
+pub(crate) fn verify_jws(data: JwsVerifyDto) -> Result<JwsVerification> {
+    info!("verify jws, algorithm: {:?}", data.algorithm);
+    let (payload, signatures) = parse_jws(&data.jws)?;
+    let mut verified = None;
+    for signature in signatures.iter() {
+        if verify_signature(signature, &data.key, data.algorithm)? {
+            verified = Some(signature);
+            break;
+        }
+    }
+    let header = verified.or(signatures.first()).map(|s| s.header.clone());
+    Ok(JwsVerification {
+        header: header.unwrap_or_default(),
+        payload: data.output_encoding.encode(&payload)?,
+        valid: verified.is_some(),
+    })
+}
+
+pub(crate) struct JwsSignature {
+    pub header: Value,
+    pub signing_input: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+pub(crate) fn generate_jws_inner(
+    payload: &[u8],
+    signers: &[JwsSigner],
+    serialization: JoseSerialization,
+) -> Result<String> {
+    if signers.is_empty() {
+        return Err(Error::Unsupported("jws without signer".to_string()));
+    }
+    if serialization != JoseSerialization::General && signers.len() > 1 {
+        return Err(Error::Unsupported(format!(
+            "{:?} jws with multiple signers",
+            serialization
+        )));
+    }
+    let encoded_payload = base64_encode(payload, true, true)?;
+    let mut signatures = Vec::with_capacity(signers.len());
+    for signer in signers {
+        let (alg, protected) = protected_header(signer)?;
+        let protected = base64_encode(
+            &serde_json::to_vec(&protected).context("jws header failed")?,
+            true,
+            true,
+        )?;
+        let signing_input = format!("{}.{}", protected, encoded_payload);
+        let signature = base64_encode(
+            &sign(alg, &signer.key, signing_input.as_bytes())?,
+            true,
+            true,
+        )?;
+        let mut entry = json!({
+            "protected": protected,
+            "signature": signature,
+        });
+        if let Some(unprotected) = signer.unprotected.as_ref() {
+            entry["header"] = Value::Object(parse_header(unprotected)?);
+        }
+        signatures.push(entry);
+    }
+
+    Ok(match serialization {
+        JoseSerialization::Compact => {
+            let entry = &signatures[0];
+            format!(
+                "{}.{}.{}",
+                entry["protected"].as_str().unwrap_or_default(),
+                encoded_payload,
+                entry["signature"].as_str().unwrap_or_default()
+            )
+        }
+        JoseSerialization::Flattened => {
+            let mut jws = signatures.remove(0);
+            jws["payload"] = Value::String(encoded_payload);
+            serde_json::to_string_pretty(&jws).context("jws to json failed")?
+        }
+        JoseSerialization::General => serde_json::to_string_pretty(&json!({
+            "payload": encoded_payload,
+            "signatures": signatures,
+        }))
+        .context("jws to json failed")?,
+    })
+}
+
+pub(crate) fn parse_jws(input: &str) -> Result<(Vec<u8>, Vec<JwsSignature>)> {
+    let input = input.trim();
+    if input.starts_with('{') {
+        let jws: Value =
+            serde_json::from_str(input).context("invalid jws json")?;
+        let encoded_payload = jws["payload"]
+            .as_str()
+            .ok_or(Error::Unsupported("jws without payload".to_string()))?;
+        let entries = match jws["signatures"].as_array() {
+            Some(signatures) => signatures.clone(),
+            None => vec![jws.clone()],
+        };
+        let mut signatures = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let protected = entry["protected"].as_str().unwrap_or_default();
+            let mut header = decode_header(protected)?;
+            if let Some(unprotected) = entry["header"].as_object() {
+                for (name, value) in unprotected {
+                    header.entry(name.clone()).or_insert(value.clone());
+                }
+            }
+            signatures.push(JwsSignature {
+                header: Value::Object(header),
+                signing_input: format!("{}.{}", protected, encoded_payload)
+                    .into_bytes(),
+                signature: base64_decode(
+                    entry["signature"].as_str().unwrap_or_default(),
+                    true,
+                    true,
+                )?,
+            });
+        }
+        Ok((base64_decode(encoded_payload, true, true)?, signatures))
+    } else {
+        let parts = input.split('.').collect::<Vec<&str>>();
+        if parts.len() != 3 {
+            return Err(Error::Unsupported(format!(
+                "compact jws with {} parts",
+                parts.len()
+            )));
+        }
+        let signature = JwsSignature {
+            header: Value::Object(decode_header(parts[0])?),
+            signing_input: format!("{}.{}", parts[0], parts[1]).into_bytes(),
+            signature: base64_decode(parts[2], true, true)?,
+        };
+        Ok((base64_decode(parts[1], true, true)?, vec![signature]))
+    }
+}
+
+pub(crate) fn verify_signature(
+    signature: &JwsSignature,
+    key: &JoseKey,
+    algorithm: Option<JwkeyAlgorithm>,
+) -> Result<bool> {
+    let alg = match header_algorithm(&signature.header) {
+        Ok(alg) => alg,
+        Err(_) => return Ok(false),
+    };
+    if algorithm.is_some_and(|expected| expected != alg) {
+        return Ok(false);
+    }
+    // a key that does not fit this alg only invalidates this signature
+    match verify(alg, key, &signature.signing_input, &signature.signature) {
+        Ok(valid) => Ok(valid),
+        Err(e) => {
+            debug!("jws signature with {:?} not verified: {}", alg, e);
+            Ok(false)
+        }
+    }
+}
+
+pub(crate) fn parse_header(input: &str) -> Result<Map<String, Value>> {
+    match serde_json::from_str::<Value>(input)
+        .context("invalid jose header json")?
+    {
+        Value::Object(header) => Ok(header),
+        _ => Err(Error::Unsupported(
+            "jose header is not an object".to_string(),
+        )),
+    }
+}
+
+pub(crate) fn decode_header(input: &str) -> Result<Map<String, Value>> {
+    let header = base64_decode(input, true, true)?;
+    parse_header(&String::from_utf8(header).context("invalid utf-8 header")?)
+}
+
+pub(crate) fn header_algorithm(header: &Value) -> Result<JwkeyAlgorithm> {
+    Ok(
+        serde_json::from_value::<JwkeyAlgorithm>(header["alg"].clone())
+            .context(format!("unsupported alg {}", header["alg"]))?,
+    )
+}
+
+fn protected_header(signer: &JwsSigner) -> Result<(JwkeyAlgorithm, Value)> {
+    let jwk = signer.key.jwk()?;
+    let alg = match signer.algorithm {
+        Some(alg) => alg,
+        None => jwk
+            .as_ref()
+            .and_then(|jwk| {
+                serde_json::from_value::<JwkeyAlgorithm>(jwk["alg"].clone())
+                    .ok()
+            })
+            .ok_or(Error::Unsupported(
+                "jws algorithm is required".to_string(),
+            ))?,
+    };
+    let mut header = match signer.header.as_ref() {
+        Some(header) => parse_header(header)?,
+        None => Map::new(),
+    };
+    header.insert("alg".to_string(), json!(alg));
+    let kid = signer
+        .key_id
+        .clone()
+        .or(jwk.and_then(|jwk| jwk["kid"].as_str().map(|kid| kid.to_string())));
+    if let Some(kid) = kid {
+        header.insert("kid".to_string(), Value::String(kid));
+    }
+    Ok((alg, Value::Object(header)))
+}
+
+pub(crate) fn sign(
+    alg: JwkeyAlgorithm,
+    key: &JoseKey,
+    input: &[u8],
+) -> Result<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    Ok(match alg {
+        JwkeyAlgorithm::HS256 => {
+            hmac_sign::<sha2::Sha256>(&key.secret()?, input)?
+        }
+        JwkeyAlgorithm::HS384 => {
+            hmac_sign::<sha2::Sha384>(&key.secret()?, input)?
+        }
+        JwkeyAlgorithm::HS512 => {
+            hmac_sign::<sha2::Sha512>(&key.secret()?, input)?
+        }
+        JwkeyAlgorithm::RS256 => key
+            .rsa_private_key()?
+            .sign(
+                Pkcs1v15Sign::new::<sha2::Sha256>(),
+                &sha2::Sha256::digest(input),
+            )
+            .context("rs256 sign failed")?,
+        JwkeyAlgorithm::RS384 => key
+            .rsa_private_key()?
+            .sign(
+                Pkcs1v15Sign::new::<sha2::Sha384>(),
+                &sha2::Sha384::digest(input),
+            )
+            .context("rs384 sign failed")?,
+        JwkeyAlgorithm::RS512 => key
+            .rsa_private_key()?
+            .sign(
+                Pkcs1v15Sign::new::<sha2::Sha512>(),
+                &sha2::Sha512::digest(input),
+            )
+            .context("rs512 sign failed")?,
+        JwkeyAlgorithm::PS256 => key
+            .rsa_private_key()?
+            .sign_with_rng(
+                &mut rng,
+                Pss::new::<sha2::Sha256>(),
+                &sha2::Sha256::digest(input),
+            )
+            .context("ps256 sign failed")?,
+        JwkeyAlgorithm::PS384 => key
+            .rsa_private_key()?
+            .sign_with_rng(
+                &mut rng,
+                Pss::new::<sha2::Sha384>(),
+                &sha2::Sha384::digest(input),
+            )
+            .context("ps384 sign failed")?,
+        JwkeyAlgorithm::PS512 => key
+            .rsa_private_key()?
+            .sign_with_rng(
+                &mut rng,
+                Pss::new::<sha2::Sha512>(),
+                &sha2::Sha512::digest(input),
+            )
+            .context("ps512 sign failed")?,
+        JwkeyAlgorithm::ES256 => {
+            let signing_key = p256::ecdsa::SigningKey::from(
+                key.ecc_secret_key::<p256::NistP256>("P-256")?,
+            );
+            let signature: p256::ecdsa::Signature =
+                signing_key.try_sign(input).context("es256 sign failed")?;
+            signature.to_vec()
+        }
+        JwkeyAlgorithm::ES384 => {
+            let signing_key = p384::ecdsa::SigningKey::from(
+                key.ecc_secret_key::<p384::NistP384>("P-384")?,
+            );
+            let signature: p384::ecdsa::Signature =
+                signing_key.try_sign(input).context("es384 sign failed")?;
+            signature.to_vec()
+        }
+        JwkeyAlgorithm::ES521 => {
+            let signing_key = p521::ecdsa::SigningKey::from_bytes(
+                &key.ecc_secret_key::<p521::NistP521>("P-521")?.to_bytes(),
+            )
+            .context("es512 signing key failed")?;
+            let signature: p521::ecdsa::Signature =
+                signing_key.try_sign(input).context("es512 sign failed")?;
+            signature.to_vec()
+        }
+        JwkeyAlgorithm::ES256K => {
+            let signing_key = k256::ecdsa::SigningKey::from(
+                key.ecc_secret_key::<k256::Secp256k1>("secp256k1")?,
+            );
+            let signature: k256::ecdsa::Signature =
+                signing_key.try_sign(input).context("es256k sign failed")?;
+            signature.to_vec()
+        }
+        JwkeyAlgorithm::EdDSA => key
+            .ed25519_signing_key()?
+            .try_sign(input)
+            .context("eddsa sign failed")?
+            .to_vec(),
+        _ => return Err(Error::Unsupported(format!("jws alg {:?}", alg))),
+    })
+}
+
+pub(crate) fn verify(
+    alg: JwkeyAlgorithm,
+    key: &JoseKey,
+    input: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    Ok(match alg {
+        JwkeyAlgorithm::HS256 => {
+            hmac_verify::<sha2::Sha256>(&key.secret()?, input, signature)?
+        }
+        JwkeyAlgorithm::HS384 => {
+            hmac_verify::<sha2::Sha384>(&key.secret()?, input, signature)?
+        }
+        JwkeyAlgorithm::HS512 => {
+            hmac_verify::<sha2::Sha512>(&key.secret()?, input, signature)?
+        }
+        JwkeyAlgorithm::RS256 => key
+            .rsa_public_key()?
+            .verify(
+                Pkcs1v15Sign::new::<sha2::Sha256>(),
+                &sha2::Sha256::digest(input),
+                signature,
+            )
+            .is_ok(),
+        JwkeyAlgorithm::RS384 => key
+            .rsa_public_key()?
+            .verify(
+                Pkcs1v15Sign::new::<sha2::Sha384>(),
+                &sha2::Sha384::digest(input),
+                signature,
+            )
+            .is_ok(),
+        JwkeyAlgorithm::RS512 => key
+            .rsa_public_key()?
+            .verify(
+                Pkcs1v15Sign::new::<sha2::Sha512>(),
+                &sha2::Sha512::digest(input),
+                signature,
+            )
+            .is_ok(),
+        JwkeyAlgorithm::PS256 => key
+            .rsa_public_key()?
+            .verify(
+                Pss::new::<sha2::Sha256>(),
+                &sha2::Sha256::digest(input),
+                signature,
+            )
+            .is_ok(),
+        JwkeyAlgorithm::PS384 => key
+            .rsa_public_key()?
+            .verify(
+                Pss::new::<sha2::Sha384>(),
+                &sha2::Sha384::digest(input),
+                signature,
+            )
+            .is_ok(),
+        JwkeyAlgorithm::PS512 => key
+            .rsa_public_key()?
+            .verify(
+                Pss::new::<sha2::Sha512>(),
+                &sha2::Sha512::digest(input),
+                signature,
+            )
+            .is_ok(),
+        JwkeyAlgorithm::ES256 => {
+            let verifying_key = p256::ecdsa::VerifyingKey::from(
+                key.ecc_public_key::<p256::NistP256>("P-256")?,
+            );
+            p256::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|s| verifying_key.verify(input, &s).is_ok())
+        }
+        JwkeyAlgorithm::ES384 => {
+            let verifying_key = p384::ecdsa::VerifyingKey::from(
+                key.ecc_public_key::<p384::NistP384>("P-384")?,
+            );
+            p384::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|s| verifying_key.verify(input, &s).is_ok())
+        }
+        JwkeyAlgorithm::ES521 => {
+            let verifying_key = p521::ecdsa::VerifyingKey::from_affine(
+                *key.ecc_public_key::<p521::NistP521>("P-521")?.as_affine(),
+            )
+            .context("es512 verifying key failed")?;
+            p521::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|s| verifying_key.verify(input, &s).is_ok())
+        }
+        JwkeyAlgorithm::ES256K => {
+            let verifying_key = k256::ecdsa::VerifyingKey::from(
+                key.ecc_public_key::<k256::Secp256k1>("secp256k1")?,
+            );
+            k256::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|s| verifying_key.verify(input, &s).is_ok())
+        }
+        JwkeyAlgorithm::EdDSA => {
+            let verifying_key = key.ed25519_verifying_key()?;
+            ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|s| verifying_key.verify(input, &s).is_ok())
+        }
+        _ => return Err(Error::Unsupported(format!("jws alg {:?}", alg))),
+    })
+}
+
+pub(crate) fn hmac_sign<D>(key: &[u8], input: &[u8]) -> Result<Vec<u8>>
+where
+    D: CoreProxy,
+    D::Core: HashMarker
+        + UpdateCore
+        + FixedOutputCore
+        + BufferKindUser<BufferKind = Eager>
+        + Default
+        + Clone,
+    <D::Core as BlockSizeUser>::BlockSize: IsLess<U256>,
+    Le<<D::Core as BlockSizeUser>::BlockSize, U256>: NonZero,
+{
+    let mut mac = Hmac::<D>::new_from_slice(key).context("invalid hmac key")?;
+    mac.update(input);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+pub(crate) fn hmac_verify<D>(
+    key: &[u8],
+    input: &[u8],
+    signature: &[u8],
+) -> Result<bool>
+where
+    D: CoreProxy,
+    D::Core: HashMarker
+        + UpdateCore
+        + FixedOutputCore
+        + BufferKindUser<BufferKind = Eager>
+        + Default
+        + Clone,
+    <D::Core as BlockSizeUser>::BlockSize: IsLess<U256>,
+    Le<<D::Core as BlockSizeUser>::BlockSize, U256>: NonZero,
+{
+    let mut mac = Hmac::<D>::new_from_slice(key).context("invalid hmac key")?;
+    mac.update(input);
+    Ok(mac.verify_slice(signature).is_ok())
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+    use tracing::info;
+    use tracing_test::traced_test;
+
+    use crate::{
+        enums::TextEncoding,
+        jwt::{
+            jwk::{generate_jwk, JoseKey, JwkGenerate},
+            jws::{generate_jws, verify_jws, JwsDto, JwsSigner, JwsVerifyDto},
+            JoseSerialization, JwkeyAlgorithm, JwkeyType,
+        },
+        utils::jwkey_algorithm,
+    };
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_generate_and_verify_jws() {
+        let payload = r#"{"sub":"kits"}"#;
+        for kty in JwkeyType::iter() {
+            for alg in jwkey_algorithm(kty) {
+                if !alg.is_signature() {
+                    continue;
+                }
+                let jwk = generate_jwk(JwkGenerate {
+                    key_id: Some("kits".to_string()),
+                    key_type: kty,
+                    algorithm: Some(alg),
+                    usage: None,
+                    operations: None,
+                    bits: None,
+                    curve_name: None,
+                    thumbprint: None,
+                })
+                .await
+                .unwrap();
+                for serialization in JoseSerialization::iter() {
+                    info!("jws alg: {:?}, {:?}", alg, serialization);
+                    let jws = generate_jws(JwsDto {
+                        payload: payload.to_string(),
+                        payload_encoding: TextEncoding::Utf8,
+                        signers: vec![JwsSigner {
+                            key: JoseKey::Jwk { key: jwk.clone() },
+                            algorithm: None,
+                            key_id: None,
+                            header: Some(r#"{"typ":"JWT"}"#.to_string()),
+                            unprotected: None,
+                        }],
+                        serialization,
+                    })
+                    .unwrap();
+                    let verification = verify_jws(JwsVerifyDto {
+                        jws,
+                        key: JoseKey::Jwk { key: jwk.clone() },
+                        algorithm: Some(alg),
+                        output_encoding: TextEncoding::Utf8,
+                    })
+                    .unwrap();
+                    assert!(verification.valid);
+                    assert_eq!(verification.payload, payload);
+                    assert_eq!(verification.header["kid"], "kits");
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_verify_jws_with_mismatched_signature() {
+        let generate = |key_type| {
+            generate_jwk(JwkGenerate {
+                key_id: None,
+                key_type,
+                algorithm: None,
+                usage: None,
+                operations: None,
+                bits: None,
+                curve_name: None,
+                thumbprint: None,
+            })
+        };
+        let secret = generate(JwkeyType::Symmetric).await.unwrap();
+        let rsa = generate(JwkeyType::RSA).await.unwrap();
+        let signer = |key: &String, algorithm| JwsSigner {
+            key: JoseKey::Jwk { key: key.clone() },
+            algorithm: Some(algorithm),
+            key_id: None,
+            header: None,
+            unprotected: None,
+        };
+        let jws = generate_jws(JwsDto {
+            payload: "kits".to_string(),
+            payload_encoding: TextEncoding::Utf8,
+            signers: vec![
+                signer(&secret, JwkeyAlgorithm::HS256),
+                signer(&rsa, JwkeyAlgorithm::RS256),
+            ],
+            serialization: JoseSerialization::General,
+        })
+        .unwrap();
+        // the hs256 signature can not be checked with an rsa key
+        let verification = verify_jws(JwsVerifyDto {
+            jws: jws.clone(),
+            key: JoseKey::Jwk { key: rsa.clone() },
+            algorithm: None,
+            output_encoding: TextEncoding::Utf8,
+        })
+        .unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.header["alg"], "RS256");
+
+        let verification = verify_jws(JwsVerifyDto {
+            jws,
+            key: JoseKey::Jwk { key: rsa },
+            algorithm: Some(JwkeyAlgorithm::HS256),
+            output_encoding: TextEncoding::Utf8,
+        })
+        .unwrap();
+        assert!(!verification.valid);
+    }
+
+    #[test]
+    fn test_verify_rfc7515_hs256() {
+        let jws = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.\
+                   eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ.\
+                   dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let key = r#"{"kty":"oct","k":"AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow"}"#;
+        let verification = verify_jws(JwsVerifyDto {
+            jws: jws.to_string(),
+            key: JoseKey::Jwk {
+                key: key.to_string(),
+            },
+            algorithm: Some(JwkeyAlgorithm::HS256),
+            output_encoding: TextEncoding::Utf8,
+        })
+        .unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.header["typ"], "JWT");
+    }
 }
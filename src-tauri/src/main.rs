@@ -60,32 +60,75 @@ fn main() -> Result<()> {
             crypto::edwards::ecies_edwards,
             // encrytion
             crypto::aes::crypto_aes,
+            crypto::chacha::crypto_chacha,
+            crypto::sm4::crypto_sm4,
             crypto::rsa::crypto_rsa,
             crypto::ecc::ecies,
+            crypto::hpke::hpke,
+            // signature
+            crypto::rsa::sign_rsa,
+            crypto::rsa::verify_rsa,
+            crypto::ecc::sign_ecc,
+            crypto::ecc::verify_ecc,
+            crypto::edwards::sign_edwards,
+            crypto::edwards::verify_edwards,
             // format
             crypto::rsa::key::transfer_rsa_key,
             crypto::ecc::key::transfer_ecc_key,
             crypto::edwards::key::transfer_edwards_key,
+            // digest
+            crypto::hash::hash,
+            // mac
+            crypto::mac::generate_mac,
+            crypto::mac::verify_mac,
             // kdf
             crypto::kdf::kdf,
+            crypto::ecdh::ecdh,
+            crypto::sm2::sm2_key_exchange,
             // jwt
             jwt::jws::generate_jws,
+            jwt::jws::verify_jws,
+            jwt::claims::generate_jwt,
+            jwt::claims::validate_jwt,
             jwt::jwe::generate_jwe,
+            jwt::jwe::decrypt_jwe,
+            jwt::decode::decode_jose,
             jwt::jwk::generate_jwk,
+            jwt::jwk::import_jwk,
+            jwt::jwk::export_jwk,
+            jwt::jwk::build_jwks,
+            jwt::jwk::add_jwks_key,
+            jwt::jwk::remove_jwks_key,
+            jwt::jwk::filter_jwks,
+            jwt::jwk::validate_jwks,
+            jwt::jwk::jwk_thumbprint,
             // common
             codec::convert_encoding,
             utils::random_id,
             utils::rsa_key_size,
             utils::digests,
+            utils::hash_algorithms,
             utils::elliptic_curve,
             utils::edwards,
+            utils::eddsa_variant,
+            utils::chacha_variant,
+            utils::mac_algorithm,
+            utils::ecdh_curve,
             utils::kdfs,
             utils::ecies_enc_alg,
+            utils::ecies_profile,
+            utils::hpke_mode,
+            utils::hpke_kem,
+            utils::hpke_kdf,
+            utils::hpke_aead,
+            utils::ecdsa_signature_format,
             utils::rsa_encryption_padding,
+            utils::rsa_signature_padding,
             utils::jwkey_type,
             utils::jwkey_algorithm,
             utils::jwkey_usage,
             utils::jwkey_operation,
+            utils::jose_serialization,
         ])
         .run(tauri::generate_context!())
         .context("error while running tauri application")?;
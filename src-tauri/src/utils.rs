@@ -4,14 +4,20 @@ use strum::IntoEnumIterator;
 
 use super::{
     enums::{
-        Digest, EccCurveName, EciesEncryptionAlgorithm, EdwardsCurveName, Kdf,
-        RsaEncryptionPadding,
+        ChaChaVariant, Digest, EccCurveName, EcdhCurveName,
+        EcdsaSignatureFormat, EciesEncryptionAlgorithm, EciesProfile,
+        EddsaVariant, EdwardsCurveName, ExtendedDigest, HashAlgorithm,
+        HpkeAead, HpkeKdf, HpkeKem, HpkeMode, Kdf, MacAlgorithm,
+        RsaEncryptionPadding, RsaSignaturePadding,
     },
     errors::Result,
 };
 use crate::{
     enums::RsaKeySize,
-    jwt::{JwkeyAlgorithm, JwkeyOperation, JwkeyType, JwkeyUsage},
+    jwt::{
+        JoseSerialization, JwkeyAlgorithm, JwkeyOperation, JwkeyType,
+        JwkeyUsage,
+    },
 };
 #[derive(Serialize, Deserialize)]
 pub struct KeyTuple(pub Option<String>, pub Option<String>);
@@ -62,6 +68,29 @@ pub fn edwards() -> Vec<EdwardsCurveName> {
     EdwardsCurveName::iter().collect::<Vec<EdwardsCurveName>>()
 }
 
+#[tauri::command]
+pub fn eddsa_variant() -> Vec<EddsaVariant> {
+    EddsaVariant::iter().collect::<Vec<EddsaVariant>>()
+}
+
+#[tauri::command]
+pub fn chacha_variant() -> Vec<ChaChaVariant> {
+    ChaChaVariant::iter().collect::<Vec<ChaChaVariant>>()
+}
+
+#[tauri::command]
+pub fn mac_algorithm() -> Vec<MacAlgorithm> {
+    MacAlgorithm::iter().collect::<Vec<MacAlgorithm>>()
+}
+
+#[tauri::command]
+pub fn ecdh_curve() -> Vec<EcdhCurveName> {
+    EccCurveName::iter()
+        .map(EcdhCurveName::Ecc)
+        .chain(EdwardsCurveName::iter().map(EcdhCurveName::Edwards))
+        .collect::<Vec<EcdhCurveName>>()
+}
+
 #[tauri::command]
 pub fn kdfs() -> Vec<Kdf> {
     Kdf::iter().collect::<Vec<Kdf>>()
@@ -72,11 +101,49 @@ pub fn digests() -> Vec<Digest> {
     Digest::iter().collect::<Vec<Digest>>()
 }
 
+#[tauri::command]
+pub fn hash_algorithms() -> Vec<HashAlgorithm> {
+    Digest::iter()
+        .map(HashAlgorithm::Digest)
+        .chain(ExtendedDigest::iter().map(HashAlgorithm::Extended))
+        .collect::<Vec<HashAlgorithm>>()
+}
+
 #[tauri::command]
 pub fn ecies_enc_alg() -> Vec<EciesEncryptionAlgorithm> {
     EciesEncryptionAlgorithm::iter().collect::<Vec<EciesEncryptionAlgorithm>>()
 }
 
+#[tauri::command]
+pub fn ecies_profile() -> Vec<EciesProfile> {
+    EciesProfile::iter().collect::<Vec<EciesProfile>>()
+}
+
+#[tauri::command]
+pub fn hpke_mode() -> Vec<HpkeMode> {
+    HpkeMode::iter().collect::<Vec<HpkeMode>>()
+}
+
+#[tauri::command]
+pub fn hpke_kem() -> Vec<HpkeKem> {
+    HpkeKem::iter().collect::<Vec<HpkeKem>>()
+}
+
+#[tauri::command]
+pub fn hpke_kdf() -> Vec<HpkeKdf> {
+    HpkeKdf::iter().collect::<Vec<HpkeKdf>>()
+}
+
+#[tauri::command]
+pub fn hpke_aead() -> Vec<HpkeAead> {
+    HpkeAead::iter().collect::<Vec<HpkeAead>>()
+}
+
+#[tauri::command]
+pub fn ecdsa_signature_format() -> Vec<EcdsaSignatureFormat> {
+    EcdsaSignatureFormat::iter().collect::<Vec<EcdsaSignatureFormat>>()
+}
+
 #[tauri::command]
 pub fn rsa_key_size() -> Vec<RsaKeySize> {
     RsaKeySize::iter().collect::<Vec<RsaKeySize>>()
@@ -87,6 +154,11 @@ pub fn rsa_encryption_padding() -> Vec<RsaEncryptionPadding> {
     RsaEncryptionPadding::iter().collect::<Vec<RsaEncryptionPadding>>()
 }
 
+#[tauri::command]
+pub fn rsa_signature_padding() -> Vec<RsaSignaturePadding> {
+    RsaSignaturePadding::iter().collect::<Vec<RsaSignaturePadding>>()
+}
+
 #[tauri::command]
 pub(crate) fn jwkey_algorithm(kty: JwkeyType) -> Vec<JwkeyAlgorithm> {
     match kty {
@@ -97,6 +169,11 @@ pub(crate) fn jwkey_algorithm(kty: JwkeyType) -> Vec<JwkeyAlgorithm> {
             JwkeyAlgorithm::PS256,
             JwkeyAlgorithm::PS384,
             JwkeyAlgorithm::PS512,
+            JwkeyAlgorithm::Rsa1_5,
+            JwkeyAlgorithm::RsaOaep,
+            JwkeyAlgorithm::RsaOaep256,
+            JwkeyAlgorithm::RsaOaep384,
+            JwkeyAlgorithm::RsaOaep521,
         ],
         JwkeyType::EcDSA => vec![
             JwkeyAlgorithm::ES256,
@@ -154,3 +231,8 @@ pub async fn jwkey_type() -> Vec<JwkeyType> {
 pub async fn jwkey_operation() -> Vec<JwkeyOperation> {
     JwkeyOperation::iter().collect::<Vec<JwkeyOperation>>()
 }
+
+#[tauri::command]
+pub async fn jose_serialization() -> Vec<JoseSerialization> {
+    JoseSerialization::iter().collect::<Vec<JoseSerialization>>()
+}